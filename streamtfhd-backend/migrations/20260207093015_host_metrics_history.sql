@@ -0,0 +1,16 @@
+-- Add migration script here
+CREATE TABLE host_metrics_history (
+    id                      BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    resolution              INTEGER NOT NULL,
+    sampled_at              BIGINT NOT NULL,
+    cpu                     REAL NOT NULL,
+    used_memory             BIGINT NOT NULL,
+    total_memory            BIGINT NOT NULL,
+    disk_used               BIGINT NOT NULL,
+    disk_total              BIGINT NOT NULL,
+    download_kbps           BIGINT NOT NULL,
+    upload_kbps             BIGINT NOT NULL
+);
+
+CREATE INDEX idx_host_metrics_history_resolution_sampled_at
+    ON host_metrics_history (resolution, sampled_at);
@@ -0,0 +1,6 @@
+-- Add migration script here
+ALTER TABLE live_stream_history
+    ADD COLUMN avg_cpu              REAL,
+    ADD COLUMN peak_cpu             REAL,
+    ADD COLUMN peak_memory          BIGINT,
+    ADD COLUMN net_tx_bytes         BIGINT;
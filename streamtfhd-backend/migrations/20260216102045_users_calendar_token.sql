@@ -0,0 +1,3 @@
+-- Add migration script here
+ALTER TABLE users
+    ADD COLUMN calendar_token TEXT UNIQUE;
@@ -0,0 +1,10 @@
+-- Add migration script here
+CREATE TABLE live_stream_detached (
+    live_stream             BIGINT PRIMARY KEY,
+    owner                   TEXT NOT NULL,
+    pid                     INTEGER NOT NULL,
+    actual_start            BIGINT,
+    schedule_end            BIGINT,
+    bit_rate                BIGINT NOT NULL,
+    detached_at             BIGINT NOT NULL
+)
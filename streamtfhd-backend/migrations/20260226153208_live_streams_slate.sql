@@ -0,0 +1,7 @@
+-- Add migration script here
+ALTER TABLE live_streams
+    ADD COLUMN slate_video BIGINT,
+    ADD CONSTRAINT fk_live_stream_slate_video
+        FOREIGN KEY (slate_video)
+        REFERENCES videos(id)
+        ON DELETE SET NULL;
@@ -0,0 +1,68 @@
+-- Add migration script here
+CREATE TABLE audios (
+    id                      BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    owner                   TEXT NOT NULL,
+    title                   TEXT NOT NULL,
+    file                    TEXT NOT NULL UNIQUE,
+    bit_rate                INTEGER NOT NULL,
+    sample_rate             INTEGER NOT NULL,
+    channels                INTEGER NOT NULL,
+    length                  INTEGER NOT NULL,
+    size                    INTEGER NOT NULL,
+    uploaded_at             INTEGER NOT NULL,
+
+    CONSTRAINT fk_audio_user
+        FOREIGN KEY (owner)
+        REFERENCES users(id)
+);
+
+CREATE TABLE images (
+    id                      BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    owner                   TEXT NOT NULL,
+    title                   TEXT NOT NULL,
+    file                    TEXT NOT NULL UNIQUE,
+    width                   INTEGER NOT NULL,
+    height                  INTEGER NOT NULL,
+    size                    INTEGER NOT NULL,
+    uploaded_at             INTEGER NOT NULL,
+
+    CONSTRAINT fk_image_user
+        FOREIGN KEY (owner)
+        REFERENCES users(id)
+);
+
+-- In image_audio mode `video` is the optional looping background video.
+ALTER TABLE live_streams
+    ALTER COLUMN video DROP NOT NULL,
+    ADD COLUMN mode TEXT NOT NULL DEFAULT 'video',
+    ADD COLUMN background_image BIGINT,
+    ADD COLUMN slate_image BIGINT,
+    ADD COLUMN slate_audio BIGINT,
+    ADD CONSTRAINT fk_live_stream_background_image
+        FOREIGN KEY (background_image)
+        REFERENCES images(id),
+    ADD CONSTRAINT fk_live_stream_slate_image
+        FOREIGN KEY (slate_image)
+        REFERENCES images(id)
+        ON DELETE SET NULL,
+    ADD CONSTRAINT fk_live_stream_slate_audio
+        FOREIGN KEY (slate_audio)
+        REFERENCES audios(id)
+        ON DELETE SET NULL;
+
+CREATE TABLE live_stream_audios (
+    live_stream             BIGINT NOT NULL,
+    position                INTEGER NOT NULL,
+    audio                   BIGINT NOT NULL,
+
+    PRIMARY KEY (live_stream, position),
+
+    CONSTRAINT fk_live_stream_audio_live_stream
+        FOREIGN KEY (live_stream)
+        REFERENCES live_streams(id)
+        ON DELETE CASCADE,
+
+    CONSTRAINT fk_live_stream_audio_audio
+        FOREIGN KEY (audio)
+        REFERENCES audios(id)
+);
@@ -0,0 +1,6 @@
+-- Add migration script here
+ALTER TABLE live_streams
+    ADD COLUMN trim_start BIGINT,
+    ADD COLUMN trim_end BIGINT,
+    ADD COLUMN resume BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN resume_position BIGINT;
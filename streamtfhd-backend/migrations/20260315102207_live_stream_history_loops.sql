@@ -0,0 +1,4 @@
+-- Add migration script here
+ALTER TABLE live_stream_history
+    ADD COLUMN loop_iteration       INTEGER,
+    ADD COLUMN loop_position        BIGINT;
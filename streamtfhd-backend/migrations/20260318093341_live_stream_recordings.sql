@@ -0,0 +1,21 @@
+-- Add migration script here
+ALTER TABLE live_streams
+    ADD COLUMN record BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE live_stream_recordings (
+    history             BIGINT NOT NULL,
+    position            INTEGER NOT NULL,
+    video               BIGINT NOT NULL,
+
+    PRIMARY KEY (history, position),
+
+    CONSTRAINT fk_live_stream_recording_history
+        FOREIGN KEY (history)
+        REFERENCES live_stream_history(id)
+        ON DELETE CASCADE,
+
+    CONSTRAINT fk_live_stream_recording_video
+        FOREIGN KEY (video)
+        REFERENCES videos(id)
+        ON DELETE CASCADE
+);
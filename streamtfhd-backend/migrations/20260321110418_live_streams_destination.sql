@@ -0,0 +1,9 @@
+-- Add migration script here
+ALTER TABLE live_streams
+    ADD COLUMN destination JSONB;
+
+UPDATE live_streams
+    SET destination = jsonb_build_object('kind', 'rtmp', 'url', rtmp_url, 'stream_key', stream_key);
+
+ALTER TABLE live_streams
+    ALTER COLUMN destination SET NOT NULL;
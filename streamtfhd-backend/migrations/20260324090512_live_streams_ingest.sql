@@ -0,0 +1,5 @@
+-- Add migration script here
+ALTER TABLE live_streams
+    ADD COLUMN ingest_protocol TEXT,
+    ADD COLUMN ingest_port INTEGER UNIQUE,
+    ADD COLUMN ingest_key TEXT UNIQUE;
@@ -0,0 +1,3 @@
+-- Add migration script here
+ALTER TABLE live_streams
+    ADD COLUMN destination_test JSONB;
@@ -0,0 +1,9 @@
+-- Add migration script here
+ALTER TABLE live_streams
+    ADD COLUMN dry_run BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE live_stream_history
+    ADD COLUMN dry_run BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE live_stream_detached
+    ADD COLUMN dry_run BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,3 @@
+-- Add migration script here
+ALTER TABLE live_stream_history
+    ADD COLUMN events JSONB NOT NULL DEFAULT '[]'::jsonb;
@@ -0,0 +1,5 @@
+-- Add migration script here
+ALTER TABLE live_streams
+    ADD COLUMN tags TEXT[] NOT NULL DEFAULT '{}';
+
+CREATE INDEX live_streams_tags_idx ON live_streams USING GIN (tags);
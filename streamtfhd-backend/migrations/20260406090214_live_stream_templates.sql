@@ -0,0 +1,16 @@
+-- Add migration script here
+CREATE TABLE live_stream_templates (
+    id                      BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    owner                   TEXT NOT NULL,
+    name                    TEXT NOT NULL,
+    settings                JSONB NOT NULL,
+    created_at              BIGINT NOT NULL,
+    updated_at              BIGINT NOT NULL,
+
+    CONSTRAINT fk_live_stream_template_user
+        FOREIGN KEY (owner)
+        REFERENCES users(id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX live_stream_templates_owner_idx ON live_stream_templates (owner);
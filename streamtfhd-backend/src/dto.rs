@@ -15,4 +15,21 @@ pub mod live_stream_start;
 pub mod live_stream_write_history;
 pub mod live_stream_history;
 pub mod live_stream_history_search;
-pub mod live_stream_monitor;
\ No newline at end of file
+pub mod live_stream_monitor;
+pub mod metrics_state;
+pub mod dashboard_metrics_history;
+pub mod live_stream_schedule;
+pub mod live_stream_detached;
+pub mod live_stream_swap_source;
+pub mod gallery_get_audios;
+pub mod gallery_get_images;
+pub mod live_stream_source;
+pub mod live_stream_overlay;
+pub mod live_stream_overlay_text;
+pub mod live_stream_destination;
+pub mod live_stream_ingest;
+pub mod live_stream_test_destination;
+pub mod live_stream_runner;
+pub mod live_stream_event;
+pub mod live_stream_bulk;
+pub mod live_stream_template;
\ No newline at end of file
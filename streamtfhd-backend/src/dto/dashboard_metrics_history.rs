@@ -0,0 +1,20 @@
+use serde::Serialize;
+use sqlx::prelude::FromRow;
+
+#[derive(Debug, FromRow, Serialize)]
+pub struct MetricsSample {
+    pub sampled_at: i64,
+    pub cpu: f32,               // In percent
+    pub used_memory: i64,       // In Kilobytes
+    pub total_memory: i64,      // In Kilobytes
+    pub disk_used: i64,         // In Kilobytes
+    pub disk_total: i64,        // In Kilobytes
+    pub download_kbps: i64,     // In Kilobytes
+    pub upload_kbps: i64        // In Kilobytes
+}
+
+#[derive(Debug, Serialize)]
+pub struct MetricsHistory {
+    pub resolution: i32,        // In seconds
+    pub samples: Vec<MetricsSample>
+}
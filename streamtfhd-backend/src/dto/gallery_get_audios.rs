@@ -0,0 +1,16 @@
+use serde::Serialize;
+use sqlx::FromRow;
+
+#[derive(Debug, FromRow, Serialize)]
+pub struct Audio {
+    pub id: i64,
+    pub owner: String,
+    pub title: String,
+    pub file: String,
+    pub bit_rate: i32,
+    pub sample_rate: i32,
+    pub channels: i32,
+    pub length: i32,
+    pub size: i32,
+    pub uploaded_at: i32
+}
\ No newline at end of file
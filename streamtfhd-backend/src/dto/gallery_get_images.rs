@@ -0,0 +1,14 @@
+use serde::Serialize;
+use sqlx::FromRow;
+
+#[derive(Debug, FromRow, Serialize)]
+pub struct Image {
+    pub id: i64,
+    pub owner: String,
+    pub title: String,
+    pub file: String,
+    pub width: i32,
+    pub height: i32,
+    pub size: i32,
+    pub uploaded_at: i32
+}
\ No newline at end of file
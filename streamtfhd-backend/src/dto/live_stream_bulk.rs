@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+pub const BULK_START: &str = "start";
+pub const BULK_STOP: &str = "stop";
+pub const BULK_CANCEL: &str = "cancel";
+pub const BULK_RESCHEDULE: &str = "reschedule";
+pub const BULK_DELETE: &str = "delete";
+
+#[derive(Debug, Deserialize)]
+pub struct BulkRequest {
+    pub action: String,
+    pub ids: Option<Vec<i64>>,
+    pub group: Option<String>,          // Every live stream of the user with this tag, instead of `ids`
+    pub schedule_start: Option<i64>,    // Reschedule only, the same for every live stream
+    pub schedule_end: Option<i64>
+}
+
+// Outcome of the action on one live stream, in the order of the request.
+#[derive(Debug, Serialize)]
+pub struct BulkResult {
+    pub id: i64,
+    pub success: bool,
+    pub error: Option<String>
+}
+
+#[derive(Debug, Serialize)]
+pub struct StreamGroup {
+    pub tag: String,
+    pub live_streams: i64
+}
@@ -1,10 +1,29 @@
-#[derive(serde::Deserialize)]
+use crate::dto::live_stream_destination::Destination;
+use crate::dto::live_stream_overlay::Overlay;
+
+#[derive(serde::Deserialize, Default)]
 pub struct CreateLiveStreamData {
     pub title: String,
-    pub video: i32,
-    pub rtmp_url: String,
-    pub stream_key: String,
+    pub mode: Option<String>,
+    pub video: Option<i32>,
+    pub background_image: Option<i64>,
+    pub audios: Option<Vec<i64>>,
+    pub rtmp_url: Option<String>,      // RTMP destination, when there is no `destination`
+    pub stream_key: Option<String>,
     pub stream_loop: i32,
     pub schedule_start: Option<i64>,
     pub schedule_end: Option<i64>,
+    pub slate_video: Option<i64>,
+    pub slate_image: Option<i64>,
+    pub slate_audio: Option<i64>,
+    pub overlays: Option<Vec<Overlay>>,
+    pub trim_start: Option<i64>,    // In milliseconds of the video
+    pub trim_end: Option<i64>,
+    pub resume: Option<bool>,
+    pub record: Option<bool>,
+    pub preview: Option<bool>,      // Low bit rate copy for the dashboard while running
+    pub dry_run: Option<bool>,      // Sent to a local sink instead of the destination
+    pub destination: Option<Destination>,
+    pub ingest_protocol: Option<String>,    // rtmp or srt, ingest mode only
+    pub tags: Option<Vec<String>>,          // Groups for bulk operations
 }
\ No newline at end of file
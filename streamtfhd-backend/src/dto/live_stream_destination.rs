@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+
+fn default_srt_latency() -> u32 {
+    2000
+}
+
+fn default_segment_seconds() -> u32 {
+    4
+}
+
+fn default_playlist_size() -> u32 {
+    6
+}
+
+// Where HLS or DASH segments and their playlist go.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PushTarget {
+    // Uploaded with HTTP PUT, `url` is the playlist or manifest.
+    Http { url: String },
+    // Written to `{UPLOAD_DIRECTORY}/live/{live stream id}/{name}`.
+    Directory { name: String }
+}
+
+// Where a live stream is sent. Stored as JSON next to `rtmp_url` and
+// `stream_key`, which keep the address of the destination for listings and
+// the schedule conflict check.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Destination {
+    // `backup_urls` take the same stream key. They are tried in order when
+    // the URL before fails, or all sent to at once when `redundant`.
+    Rtmp {
+        url: String,
+        stream_key: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        backup_urls: Vec<String>,
+        #[serde(default)]
+        redundant: bool
+    },
+    Rtmps {
+        url: String,
+        stream_key: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        backup_urls: Vec<String>,
+        #[serde(default)]
+        redundant: bool
+    },
+    // Caller mode, the platform listens.
+    Srt {
+        host: String,
+        port: u16,
+        stream_id: Option<String>,
+        passphrase: Option<String>,
+        #[serde(default = "default_srt_latency")]
+        latency_ms: u32
+    },
+    Hls {
+        target: PushTarget,
+        #[serde(default = "default_segment_seconds")]
+        segment_seconds: u32,
+        #[serde(default = "default_playlist_size")]
+        playlist_size: u32
+    },
+    Dash {
+        target: PushTarget,
+        #[serde(default = "default_segment_seconds")]
+        segment_seconds: u32,
+        #[serde(default = "default_playlist_size")]
+        window_size: u32
+    }
+}
+
+impl Destination {
+    // `rtmp_url` and `stream_key` of the destination.
+    pub fn address(&self) -> (String, String) {
+        let target_address = |target: &PushTarget| match target {
+            PushTarget::Http { url } => url.clone(),
+            PushTarget::Directory { name } => format!("local:{}", name)
+        };
+
+        match self {
+            Destination::Rtmp { url, stream_key, .. } | Destination::Rtmps { url, stream_key, .. } => {
+                (url.clone(), stream_key.clone())
+            }
+            Destination::Srt { host, port, stream_id, .. } => {
+                (format!("srt://{}:{}", host, port), stream_id.clone().unwrap_or_default())
+            }
+            Destination::Hls { target, .. } | Destination::Dash { target, .. } => {
+                (target_address(target), String::new())
+            }
+        }
+    }
+
+    // Addresses of the primary and backup endpoints, without the stream key.
+    pub fn endpoints(&self) -> Vec<String> {
+        match self {
+            Destination::Rtmp { url, backup_urls, .. } | Destination::Rtmps { url, backup_urls, .. } => {
+                std::iter::once(url).chain(backup_urls).cloned().collect()
+            }
+            _ => vec![self.address().0]
+        }
+    }
+
+    pub fn is_redundant(&self) -> bool {
+        match self {
+            Destination::Rtmp { redundant, .. } | Destination::Rtmps { redundant, .. } => *redundant,
+            _ => false
+        }
+    }
+}
+
+// Muxer, muxer options and target of the output ffmpeg for a destination.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DestinationOutput {
+    pub format: String,
+    pub options: Vec<(String, String)>,
+    pub target: String,
+    pub local_dir: Option<String>   // Created before ffmpeg starts
+}
@@ -0,0 +1,14 @@
+use sqlx::prelude::FromRow;
+
+// A live stream left running on shutdown, to be re-adopted on the next start.
+#[derive(Debug, FromRow)]
+pub struct DetachedStream {
+    pub live_stream: i64,
+    pub owner: String,
+    pub pid: i32,
+    pub actual_start: Option<i64>,
+    pub schedule_end: Option<i64>,
+    pub bit_rate: i64,
+    pub detached_at: i64,
+    pub dry_run: bool
+}
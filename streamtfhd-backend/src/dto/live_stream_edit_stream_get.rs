@@ -1,5 +1,10 @@
 use sqlx::prelude::FromRow;
 
+use crate::dto::live_stream_destination::Destination;
+use crate::dto::live_stream_ingest::IngestAddress;
+use crate::dto::live_stream_test_destination::DestinationTest;
+use crate::dto::live_stream_overlay::Overlay;
+
 #[derive(Debug, FromRow, serde::Serialize)]
 pub struct Video {
     pub id: i64,
@@ -8,14 +13,47 @@ pub struct Video {
     pub thumbnail: String,
 }
 
+#[derive(Debug, FromRow, serde::Serialize)]
+pub struct Image {
+    pub id: i64,
+    pub title: String,
+    pub file: String,
+}
+
+#[derive(Debug, FromRow, serde::Serialize)]
+pub struct Audio {
+    pub id: i64,
+    pub title: String,
+    pub file: String,
+    pub length: i32,
+}
+
 #[derive(serde::Serialize)]
 pub struct LiveStream {
     pub id: i64,
     pub title: String,
+    pub mode: String,
     pub video: Option<Video>,
+    pub background_image: Option<Image>,
+    pub audios: Vec<Audio>,
     pub rtmp_url: String,
     pub stream_key: String,
     pub stream_loop: i32,
     pub schedule_start: Option<i64>,
-    pub schedule_end: Option<i64>
+    pub schedule_end: Option<i64>,
+    pub slate_video: Option<Video>,
+    pub slate_image: Option<Image>,
+    pub slate_audio: Option<Audio>,
+    pub overlays: Vec<Overlay>,
+    pub trim_start: Option<i64>,
+    pub trim_end: Option<i64>,
+    pub resume: bool,
+    pub resume_position: Option<i64>,
+    pub record: bool,
+    pub preview: bool,
+    pub dry_run: bool,
+    pub destination: Destination,
+    pub ingest: Option<IngestAddress>,  // Ingest mode only
+    pub destination_test: Option<DestinationTest>,
+    pub tags: Vec<String>
 }
\ No newline at end of file
@@ -1,13 +1,64 @@
+use sqlx::{FromRow, types::Json};
+
+use crate::dto::live_stream_destination::Destination;
+use crate::dto::live_stream_overlay::Overlay;
+
 #[derive(serde::Deserialize)]
 
 #[derive(Debug)]
 pub struct LiveStream {
     pub id: i64,
     pub title: String,
-    pub video: i64,
-    pub rtmp_url: String,
-    pub stream_key: String,
+    pub mode: Option<String>,
+    pub video: Option<i64>,
+    pub background_image: Option<i64>,
+    pub audios: Option<Vec<i64>>,
+    pub rtmp_url: Option<String>,      // RTMP destination, when there is no `destination`
+    pub stream_key: Option<String>,
     pub stream_loop: i32,
     pub schedule_start: Option<i64>,
-    pub schedule_end: Option<i64>
+    pub schedule_end: Option<i64>,
+    pub slate_video: Option<i64>,
+    pub slate_image: Option<i64>,
+    pub slate_audio: Option<i64>,
+    pub overlays: Option<Vec<Overlay>>,    // Unchanged when missing
+    pub trim_start: Option<i64>,            // In milliseconds of the video
+    pub trim_end: Option<i64>,
+    pub resume: Option<bool>,
+    pub record: Option<bool>,
+    pub preview: Option<bool>,
+    pub dry_run: Option<bool>,
+    pub destination: Option<Destination>,
+    pub ingest_protocol: Option<String>,    // rtmp or srt, ingest mode only
+    pub reset_ingest_key: Option<bool>,     // Turns away encoders using the old key
+    pub restart: Option<bool>,              // Allows restarting a running live stream to apply the edit
+    pub tags: Option<Vec<String>>           // Unchanged when missing
+}
+
+// What the job of a live stream is built from when it starts. Editing any of
+// it while the job exists takes a new job.
+#[derive(Debug, PartialEq, FromRow)]
+pub struct StreamSettings {
+    pub mode: String,
+    pub video: Option<i64>,
+    pub background_image: Option<i64>,
+    pub audios: Vec<i64>,
+    pub slate_video: Option<i64>,
+    pub slate_image: Option<i64>,
+    pub slate_audio: Option<i64>,
+    pub stream_loop: i32,
+    pub trim_start: Option<i64>,
+    pub trim_end: Option<i64>,
+    pub record: bool,
+    pub preview: bool,
+    pub dry_run: bool,
+    pub destination: Json<Destination>,
+    pub ingest_protocol: Option<String>
+}
+
+// Whether the edit was stored, and whether the job of the live stream was
+// started again to apply it.
+pub struct EditOutcome {
+    pub update: bool,
+    pub restarted: bool
 }
\ No newline at end of file
@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+// One transition of a live stream job, in the order they happened during a
+// run. Sent to monitor clients as it happens and kept with the history of
+// the run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamEvent {
+    pub stream_id: i64,
+    #[serde(skip)]
+    pub owner: String,
+    pub sequence: u32,          // Starts at 0 with every run
+    pub from: String,           // `StreamStatus::as_str` values
+    pub to: String,
+    pub reason: Option<String>, // Why the run failed
+    pub at: i64                 // Unix timestamp
+}
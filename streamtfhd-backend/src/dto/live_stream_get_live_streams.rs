@@ -8,7 +8,9 @@ pub struct LiveStream {
     pub live_stream_schedule_start: Option<i64>,
     pub live_stream_schedule_end: Option<i64>,
     pub live_stream_started_at: Option<i64>,
-    pub video_id: i64,
+    pub live_stream_mode: String,
+    pub live_stream_tags: Vec<String>,
+    pub video_id: Option<i64>,
     pub video_thumbnail: String,
     pub video_width: i32,
     pub video_height: i32,
@@ -1,5 +1,8 @@
 use serde::Serialize;
 use sqlx::prelude::FromRow;
+use sqlx::types::Json;
+
+use crate::dto::live_stream_event::StreamEvent;
 
 #[derive(Debug, FromRow, Serialize)]
 pub struct History {
@@ -7,5 +10,14 @@ pub struct History {
     pub live_stream_title: String,
     pub video_thumbnail: String,
     pub start_time: i64,
-    pub end_time: i64
+    pub end_time: i64,
+    pub avg_cpu: Option<f32>,
+    pub peak_cpu: Option<f32>,
+    pub peak_memory: Option<i64>,
+    pub net_tx_bytes: Option<i64>,
+    pub loop_iteration: Option<i32>,
+    pub loop_position: Option<i64>,
+    pub recordings: Vec<i64>,   // Gallery video IDs, in recording order
+    pub dry_run: bool,
+    pub events: Json<Vec<StreamEvent>>
 }
\ No newline at end of file
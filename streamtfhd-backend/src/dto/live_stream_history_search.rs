@@ -7,5 +7,12 @@ pub struct History {
     pub live_stream_title: String,
     pub video_thumbnail: String,
     pub start_time: i64,
-    pub end_time: i64
+    pub end_time: i64,
+    pub avg_cpu: Option<f32>,
+    pub peak_cpu: Option<f32>,
+    pub peak_memory: Option<i64>,
+    pub net_tx_bytes: Option<i64>,
+    pub loop_iteration: Option<i32>,
+    pub loop_position: Option<i64>,
+    pub recordings: Vec<i64>    // Gallery video IDs, in recording order
 }
\ No newline at end of file
@@ -0,0 +1,9 @@
+// Where an encoder such as OBS pushes an ingest live stream to. For RTMP the
+// key is part of `url` and `stream_key` can be anything, for SRT `url` is
+// complete and `stream_key` is empty.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct IngestAddress {
+    pub protocol: String,
+    pub url: String,
+    pub stream_key: String
+}
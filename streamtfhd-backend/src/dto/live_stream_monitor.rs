@@ -1,10 +1,26 @@
 use serde::Serialize;
 
+use crate::dto::live_stream_event::StreamEvent;
+use crate::dto::live_stream_state::{EndpointProgress, LoopProgress, ProcessResources};
+
 #[derive(Serialize)]
 pub struct TickMessage {
     pub id: i64,
     pub schedule_start: Option<i64>,
     pub schedule_end: Option<i64>,
     pub started_at: Option<i64>,
-    pub status: String
+    pub status: String,
+    pub resources: ProcessResources,
+    pub loop_progress: LoopProgress,
+    pub endpoints: EndpointProgress,
+    pub preview: Option<String>,    // File to request from the preview endpoint
+    pub dry_run: bool
+}
+
+// Sent right away for every transition of a live stream of the client, to
+// clients asking for events in their auth message.
+#[derive(Serialize)]
+pub struct EventMessage {
+    pub msg_type: &'static str,
+    pub event: StreamEvent
 }
\ No newline at end of file
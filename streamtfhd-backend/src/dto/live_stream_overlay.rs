@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverlayPosition {
+    TopLeft,
+    TopCenter,
+    TopRight,
+    Center,
+    BottomLeft,
+    BottomCenter,
+    BottomRight
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TickerEdge {
+    Top,
+    Bottom
+}
+
+// Where the text of a text or ticker overlay comes from. Both can also be
+// replaced on a running stream through the overlay text endpoint.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum TextContent {
+    Static { text: String },
+    // Fetched again every `interval` seconds, the body is used as it is.
+    Url { url: String, interval: u64 }
+}
+
+fn default_font_size() -> u32 {
+    32
+}
+
+fn default_color() -> String {
+    String::from("white")
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextStyle {
+    #[serde(default = "default_font_size")]
+    pub font_size: u32,
+    #[serde(default = "default_color")]
+    pub color: String,
+    // Color of the box drawn behind the text, e.g. black@0.5.
+    #[serde(default)]
+    pub background: Option<String>
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        TextStyle {
+            font_size: default_font_size(),
+            color: default_color(),
+            background: None
+        }
+    }
+}
+
+// One overlay layer of a live stream, drawn in list order over the video.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Overlay {
+    Watermark {
+        image: i64,
+        position: OverlayPosition,
+        opacity: f32,       // From 0 to 1
+        scale: f32          // Width of the image relative to the video width
+    },
+    Text {
+        content: TextContent,
+        position: OverlayPosition,
+        #[serde(default)]
+        style: TextStyle
+    },
+    Clock {
+        format: String,     // strftime format, in the server time zone
+        position: OverlayPosition,
+        #[serde(default)]
+        style: TextStyle
+    },
+    Ticker {
+        content: TextContent,
+        edge: TickerEdge,
+        speed: u32,         // In pixels per second
+        #[serde(default)]
+        style: TextStyle
+    }
+}
+
+// Overlay state of a live stream job. The text files live in `dir`, which
+// is removed together with the job.
+#[derive(Debug)]
+pub struct OverlayRuntime {
+    pub dir: PathBuf,
+    pub config: Vec<Overlay>,
+    pub layers: Vec<OverlayLayer>,
+    pub generation: u32     // Bumped on every change, stops the old URL pollers
+}
+
+impl Drop for OverlayRuntime {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+// An overlay with its files resolved, ready to be drawn by the output ffmpeg.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OverlayLayer {
+    Watermark {
+        file: String,
+        position: OverlayPosition,
+        opacity: f32,
+        scale: f32
+    },
+    // Text and clock overlays. The clock expands the time functions ffmpeg
+    // finds in its text file.
+    Text {
+        text_file: PathBuf,
+        expand: bool,
+        position: OverlayPosition,
+        style: TextStyle
+    },
+    Ticker {
+        text_file: PathBuf,
+        edge: TickerEdge,
+        speed: u32,
+        style: TextStyle
+    }
+}
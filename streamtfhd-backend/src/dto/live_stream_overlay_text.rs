@@ -0,0 +1,6 @@
+#[derive(Debug, serde::Deserialize)]
+pub struct OverlayTextData {
+    pub live_stream_id: i64,
+    pub overlay: usize,     // Index in the overlays of the live stream
+    pub text: String
+}
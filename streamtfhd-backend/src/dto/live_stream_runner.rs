@@ -0,0 +1,62 @@
+use futures_util::future::BoxFuture;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::mpsc::UnboundedReceiver;
+
+use crate::dto::live_stream_state::{
+    FfmpegLaunch,
+    FfmpegProgress,
+    LiveStreamState,
+    PipelineCommand,
+    SourceItem
+};
+
+// What the output of a job reports while it runs.
+#[derive(Debug, Clone)]
+pub enum RunnerEvent {
+    Progress(FfmpegProgress),   // About once a second, the output is live from the first one
+    SlaveFailed(usize),         // One output of the tee muxer gave up, the others go on
+    EndpointFailed(String),     // The destination went away
+    Error(String),
+    Finished                    // The pipeline ran dry
+}
+
+// The process of a running output.
+pub trait OutputProcess: Send + Sync {
+    fn pid(&self) -> Option<u32>;
+
+    // Ends the output and waits until it is gone. A graceful stop lets it
+    // flush its outputs first.
+    fn stop(self: Box<Self>, graceful: bool) -> BoxFuture<'static, ()>;
+}
+
+pub struct RunnerOutput {
+    pub process: Box<dyn OutputProcess>,
+    pub events: UnboundedReceiver<RunnerEvent>    // Closed once the output is gone
+}
+
+// What a job plays into its pipeline.
+pub struct Playlist {
+    pub items: Vec<SourceItem>,
+    pub passes: Option<u32>,    // None loops forever
+    pub resume_at: Option<i64>, // Where the first video starts, in milliseconds
+    pub commands: UnboundedReceiver<PipelineCommand>
+}
+
+// Runs the media side of live stream jobs, everything else about a job is
+// kept in `LiveStreamState`.
+pub trait StreamRunner: Send + Sync {
+    // Starts playing the playlist of a job into a new pipeline, which lives
+    // until the playlist is done or its commands are closed. Returns the
+    // input of the output.
+    fn start_pipeline(
+        &self,
+        state: &Arc<LiveStreamState>,
+        stream_id: i64,
+        playlist: Playlist
+    ) -> Result<PathBuf, std::io::Error>;
+
+    // Starts an output reading the pipeline and sending it to the
+    // destination. Called again for every restart or failover of the job.
+    fn spawn_output(&self, launch: &FfmpegLaunch) -> Result<RunnerOutput, std::io::Error>;
+}
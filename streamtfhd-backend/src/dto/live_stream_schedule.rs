@@ -0,0 +1,37 @@
+use serde::Serialize;
+use sqlx::prelude::FromRow;
+
+// A scheduled window of another live stream, used for conflict checks.
+#[derive(Debug, Clone, FromRow)]
+pub struct ScheduleWindow {
+    pub id: i64,
+    pub owner: String,
+    pub title: String,
+    pub schedule_start: i64,
+    pub schedule_end: Option<i64>,
+    pub bit_rate: i32
+}
+
+// A live stream being created or edited, checked against the other schedules.
+#[derive(Debug, FromRow)]
+pub struct ScheduleCandidate {
+    pub id: Option<i64>,
+    pub owner: String,
+    pub mode: String,
+    pub video: Option<i64>,
+    pub endpoints: Vec<String>, // Primary and backup addresses of the destination
+    pub stream_key: String,
+    pub schedule_start: Option<i64>,
+    pub schedule_end: Option<i64>,
+    pub dry_run: bool           // Does not hold its destination
+}
+
+#[derive(Debug, FromRow, Serialize)]
+pub struct CalendarEntry {
+    pub live_stream_id: i64,
+    pub live_stream_title: String,
+    pub schedule_start: i64,
+    pub schedule_end: Option<i64>,
+    pub video_title: String,
+    pub video_thumbnail: String
+}
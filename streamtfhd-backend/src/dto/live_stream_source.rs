@@ -0,0 +1,27 @@
+// Plays `video` as it is.
+pub const MODE_VIDEO: &str = "video";
+// Loops a background image, or `video` as a background, under an audio playlist.
+pub const MODE_IMAGE_AUDIO: &str = "image_audio";
+// Relays what an encoder such as OBS pushes to the ingest address of the
+// live stream, `video` is played while nothing is pushed.
+pub const MODE_INGEST: &str = "ingest";
+pub const INGEST_RTMP: &str = "rtmp";
+pub const INGEST_SRT: &str = "srt";
+// `stream_loop` of a live stream that loops until it is stopped or its schedule ends.
+pub const INFINITE_LOOP: i32 = -1;
+
+// What a live stream being created or edited plays, checked before it is stored.
+#[derive(Debug, Clone)]
+pub struct LiveStreamSource {
+    pub mode: String,
+    pub video: Option<i64>,
+    pub background_image: Option<i64>,
+    pub audios: Vec<i64>,
+    pub slate_video: Option<i64>,
+    pub slate_image: Option<i64>,
+    pub slate_audio: Option<i64>,
+    pub stream_loop: i32,
+    pub trim_start: Option<i64>,    // In milliseconds of `video`, video mode only
+    pub trim_end: Option<i64>,
+    pub ingest_protocol: Option<String>     // rtmp or srt, ingest mode only
+}
\ No newline at end of file
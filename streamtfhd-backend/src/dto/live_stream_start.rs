@@ -1,13 +1,39 @@
 use sqlx::{FromRow, Type};
+use sqlx::types::Json;
+
+use crate::dto::live_stream_destination::Destination;
+use crate::dto::live_stream_overlay::Overlay;
 
 #[derive(Debug, FromRow, Clone, Type)]
 pub struct LiveStreamData {
     pub id: i64,
     pub owner: String,
-    pub video_file: String,
-    pub rtmp_url: String,
-    pub stream_key: String,
+    pub title: String,
+    pub mode: String,
+    pub video_file: Option<String>,
+    pub video_bit_rate: i32,
+    pub video_width: i32,
+    pub video_height: i32,
+    pub video_frame_rate: i32,
+    pub video_length: i32,              // In seconds
+    pub background_image_file: Option<String>,
+    pub audio_files: Vec<String>,       // In playlist order
+    pub slate_file: Option<String>,
+    pub slate_image_file: Option<String>,
+    pub slate_audio_file: Option<String>,
     pub stream_loop: i32,
     pub schedule_start: Option<i64>,
     pub schedule_end: Option<i64>,
+    pub overlays: Json<Vec<Overlay>>,
+    pub trim_start: Option<i64>,        // In milliseconds of the video
+    pub trim_end: Option<i64>,
+    pub resume: bool,
+    pub resume_position: Option<i64>,   // Where the previous run stopped, in milliseconds
+    pub record: bool,
+    pub preview: bool,
+    pub dry_run: bool,
+    pub destination: Json<Destination>,
+    pub ingest_protocol: Option<String>,
+    pub ingest_port: Option<i32>,
+    pub ingest_key: Option<String>,
 }
\ No newline at end of file
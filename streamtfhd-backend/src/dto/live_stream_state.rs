@@ -1,32 +1,306 @@
 use dashmap::DashMap;
-use tokio::{process::Child, sync::Notify};
-use std::sync::Arc;
+use tokio::sync::{Notify, broadcast, mpsc::UnboundedSender};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use crate::dto::live_stream_destination::DestinationOutput;
+use crate::dto::live_stream_event::StreamEvent;
+use crate::dto::live_stream_overlay::{OverlayLayer, OverlayRuntime};
+use crate::dto::live_stream_runner::{OutputProcess, StreamRunner};
+use crate::dto::metrics_state::MetricsState;
 
 #[derive(Debug, Clone, serde::Serialize)]
 pub enum StreamStatus {
     Offline,
     Scheduled,
+    Queued,
     Starting,
     Live,
+    Degraded,
     Done,
     Stopped,
     Cancelled,
+    Interrupted,
     Failed(String),
 }
 
+// Every value returned by `StreamStatus::as_str`.
+pub const STREAM_STATES: [&str; 11] = [
+    "offline",
+    "scheduled",
+    "queued",
+    "starting",
+    "live",
+    "degraded",
+    "done",
+    "stopped",
+    "cancelled",
+    "interrupted",
+    "failed"
+];
+
+impl StreamStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StreamStatus::Offline => "offline",
+            StreamStatus::Scheduled => "scheduled",
+            StreamStatus::Queued => "queued",
+            StreamStatus::Starting => "starting",
+            StreamStatus::Live => "live",
+            StreamStatus::Degraded => "degraded",
+            StreamStatus::Done => "done",
+            StreamStatus::Stopped => "stopped",
+            StreamStatus::Cancelled => "cancelled",
+            StreamStatus::Interrupted => "interrupted",
+            StreamStatus::Failed(_) => "failed"
+        }
+    }
+
+    // A job in a final status is on its way out of the state and never
+    // changes again.
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            StreamStatus::Done
+                | StreamStatus::Stopped
+                | StreamStatus::Cancelled
+                | StreamStatus::Interrupted
+                | StreamStatus::Failed(_)
+        )
+    }
+
+    // Whether a job in this status may move to `next`. Any job that is not
+    // finished yet may finish.
+    pub fn can_become(&self, next: &StreamStatus) -> bool {
+        if self.is_final() {
+            return false;
+        }
+
+        if next.is_final() {
+            return true;
+        }
+
+        matches!(
+            (self, next),
+            (StreamStatus::Offline, StreamStatus::Scheduled | StreamStatus::Queued | StreamStatus::Starting)
+                // Re-adopted jobs are live from the start.
+                | (StreamStatus::Offline, StreamStatus::Live)
+                | (StreamStatus::Scheduled, StreamStatus::Queued | StreamStatus::Starting)
+                | (StreamStatus::Queued, StreamStatus::Starting)
+                | (StreamStatus::Starting, StreamStatus::Live | StreamStatus::Degraded)
+                | (StreamStatus::Live, StreamStatus::Degraded)
+                | (StreamStatus::Degraded, StreamStatus::Live)
+        )
+    }
+}
+
+// Values parsed from ffmpeg `-progress` output.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct FfmpegProgress {
+    pub bitrate_kbps: f64,
+    pub fps: f64,
+    pub speed: f64,
+    pub drop_frames: u64,
+    pub dup_frames: u64,
+    pub out_time_ms: i64,   // In microseconds, ffmpeg naming
+    pub total_size: u64     // In bytes
+}
+
+// Resource usage of the ffmpeg process group of a job, sampled periodically.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct ProcessResources {
+    pub cpu_percent: f32,       // Sum over the group, 100 means one full core
+    pub memory_kb: u64,         // Resident set size, in Kilobytes
+    pub net_tx_bytes: u64,      // Bytes sent to the destination
+    pub disk_read_bytes: u64,   // Bytes read from disk
+    #[serde(skip)]
+    pub samples: u64,
+    #[serde(skip)]
+    pub cpu_percent_sum: f64,
+    #[serde(skip)]
+    pub peak_cpu_percent: f32,
+    #[serde(skip)]
+    pub peak_memory_kb: u64
+}
+
+// Picture looped for as long as a feeder runs.
+#[derive(Debug, Clone)]
+pub enum Background {
+    Image(String),
+    Video(String)
+}
+
+// One input of a live stream, played into its pipeline by a feeder ffmpeg.
+#[derive(Debug, Clone)]
+pub enum SourceItem {
+    // Played from `start` to `end`, in milliseconds of the video.
+    Video {
+        file: String,
+        start: i64,
+        end: Option<i64>
+    },
+    // One audio track over the background, ends with the track.
+    ImageAudio {
+        background: Background,
+        audio: String
+    },
+    // Standby content, looped and encoded to match the main content. Without
+    // an audio it plays silence.
+    Slate {
+        background: Background,
+        audio: Option<String>,
+        width: i32,
+        height: i32,
+        frame_rate: i32,
+        bit_rate: i64
+    },
+    // Live input pushed to `url`, relayed while it is connected. The
+    // fallback video, `fallback_length` milliseconds long, fills the gaps.
+    Ingest {
+        url: String,
+        fallback: Option<String>,
+        fallback_length: i64
+    },
+    // Played by the relay of an ingest live stream while nothing is pushed,
+    // looped from `start` and encoded to the ingest profile. Without a file
+    // it plays black frames and silence.
+    Filler {
+        file: Option<String>,
+        start: i64
+    }
+}
+
+// Where the video of a live stream is, so its next run can resume there.
+#[derive(Debug, Clone, Default)]
+pub struct PlaybackPosition {
+    pub position_ms: Option<i64>,       // None once the video has played to its end
+    pub is_advancing: bool,             // Only while the video of the live stream plays
+    pub last_out_time: Option<i64>      // out_time_ms of the output ffmpeg, in microseconds
+}
+
+impl PlaybackPosition {
+    pub fn advance(&mut self, out_time: i64) {
+        if !self.is_advancing {
+            return;
+        }
+
+        if let (Some(position), Some(last)) = (self.position_ms.as_mut(), self.last_out_time) && out_time > last {
+            *position += (out_time - last) / 1000;
+        }
+
+        self.last_out_time = Some(out_time);
+    }
+}
+
+// Which pass over the playlist a job is at, the position comes from its
+// playback when the video is playing.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct LoopProgress {
+    pub iteration: u32,             // Starts at 1, 0 until the first item plays
+    pub count: Option<u32>,         // None loops until the live stream is stopped
+    pub position_ms: Option<i64>,   // In the video, video mode only
+    pub length_ms: Option<i64>      // Length of one pass, video mode only
+}
+
+// Endpoints of the destination of a job, primary first and without the
+// stream key, and which of them the output ffmpeg sends to.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct EndpointProgress {
+    pub endpoints: Vec<String>,
+    pub active: Vec<usize>,     // Every endpoint still up when redundant
+    pub redundant: bool,
+    pub failovers: u32,         // Switches to the next endpoint so far
+    #[serde(skip)]
+    pub streak: u32,            // Switches since an endpoint last held
+    #[serde(skip)]
+    pub switched_at: Option<Instant>
+}
+
+pub enum PipelineCommand {
+    // Replace the playlist, starting the first item right away. An empty
+    // playlist switches to the slate.
+    Swap(Vec<SourceItem>),
+    // Start the current item again with a new feeder.
+    RestartFeeder,
+    // Stop following the job and leave the feeder running.
+    Detach
+}
+
+// Segmented archive of what the output ffmpeg sends out, written next to the
+// gallery videos.
+#[derive(Debug, Clone)]
+pub struct RecordingOutput {
+    pub upload_dir: String,
+    pub prefix: String,         // Every segment file of the job starts with it
+    pub title: String,          // Of the live stream, the segments are named after it
+    pub segment_seconds: u64,
+    pub format: String          // mkv or mp4
+}
+
+// Low bit rate copy of what goes out, HLS or a JPEG snapshot, served to the
+// dashboard while the job runs.
+#[derive(Debug, Clone)]
+pub struct PreviewOutput {
+    pub dir: String,            // Emptied every time ffmpeg is spawned
+    pub format: String,         // hls or jpeg
+    pub height: i32,
+    pub video_bit_rate: i64,    // In kbps, hls only
+    pub snapshot_seconds: u64   // Between two snapshots, jpeg only
+}
+
+// Everything needed to spawn the output ffmpeg again for the same live stream.
+#[derive(Debug, Clone)]
+pub struct FfmpegLaunch {
+    pub input: PathBuf,
+    pub outputs: Vec<DestinationOutput>,    // One per endpoint, primary first
+    pub active: usize,                      // Output sent to, unless redundant
+    pub redundant: bool,
+    // With overlays the video is encoded with the settings below instead of copied.
+    pub overlays: Vec<OverlayLayer>,
+    pub width: i32,
+    pub frame_rate: i32,
+    pub video_bit_rate: i64,    // In kbps
+    pub recording: Option<RecordingOutput>,
+    pub preview: Option<PreviewOutput>
+}
+
 pub struct StreamJob {
     pub id: i64,
     pub owner: String,
     pub schedule_start: Option<i64>,
     pub schedule_end: Option<i64>,
+    pub bit_rate: i64,          // In kbps, from the video or the image audio profile
+    pub dry_run: bool,
     pub actual_start: Option<i64>,
     pub actual_stop: Option<i64>,
     pub status: StreamStatus,
-    pub child: Option<Child>,
+    pub output: Option<Box<dyn OutputProcess>>,
+    pub pid: Option<u32>,       // ffmpeg group leader, also set for re-adopted jobs
+    pub feeder_pid: Option<u32>,
+    pub pipeline: Option<UnboundedSender<PipelineCommand>>,
+    pub launch: Option<FfmpegLaunch>,
+    pub overlays: Option<OverlayRuntime>,
+    pub slate: Option<SourceItem>,
     pub cancel_notify: Arc<Notify>,
-    pub is_finalized: bool
+    pub schedule_notify: Arc<Notify>,   // The schedule of the job was edited
+    pub events: Vec<StreamEvent>,   // Transitions of this run, oldest first
+    pub progress: FfmpegProgress,
+    pub playback: PlaybackPosition,
+    pub loops: LoopProgress,
+    pub endpoints: EndpointProgress,
+    pub restart_count: u32,     // Watchdog restarts since the output was last healthy
+    pub ffmpeg_restarts: u32,   // Output ffmpeg spawned again, for any reason
+    pub pipeline_error: Option<String>, // Why the pipeline gave up, fails the job when the output finishes
+    pub generation: u32,        // Bumped every time the output ffmpeg is spawned again
+    pub resources: ProcessResources
 }
 
 pub struct LiveStreamState {
-    pub jobs: DashMap<i64, StreamJob>
-}
\ No newline at end of file
+    pub jobs: DashMap<i64, StreamJob>,
+    pub metrics: Arc<MetricsState>,
+    pub admission_lock: Mutex<()>,
+    pub runner: Arc<dyn StreamRunner>,
+    pub events: broadcast::Sender<StreamEvent>,
+    pub upload_dir: String      // UPLOAD_DIRECTORY, read once at startup
+}
@@ -0,0 +1,5 @@
+#[derive(Debug, serde::Deserialize)]
+pub struct SwapSourceData {
+    pub live_stream_id: i64,
+    pub videos: Vec<i64>    // Played in this order
+}
@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, types::Json};
+
+use crate::dto::live_stream_destination::Destination;
+use crate::dto::live_stream_overlay::Overlay;
+
+pub const RECURRENCE_DAILY: &str = "daily";
+pub const RECURRENCE_WEEKLY: &str = "weekly";
+
+// When a show comes back. Occurrences are `first_start` plus whole days or
+// weeks, so they stay at the same UTC time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Recurrence {
+    pub every: String,          // daily or weekly
+    pub first_start: i64        // Unix timestamp
+}
+
+// Settings of a new live stream kept for reuse. A missing field is taken from
+// what the live stream is cloned from, or left to the defaults of a new one.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TemplateSettings {
+    pub title: Option<String>,
+    pub mode: Option<String>,
+    pub video: Option<i32>,
+    pub background_image: Option<i64>,
+    pub audios: Option<Vec<i64>>,
+    pub stream_loop: Option<i32>,
+    pub slate_video: Option<i64>,
+    pub slate_image: Option<i64>,
+    pub slate_audio: Option<i64>,
+    pub overlays: Option<Vec<Overlay>>,
+    pub trim_start: Option<i64>,
+    pub trim_end: Option<i64>,
+    pub resume: Option<bool>,
+    pub record: Option<bool>,
+    pub preview: Option<bool>,
+    pub dry_run: Option<bool>,
+    pub destination: Option<Destination>,
+    pub ingest_protocol: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub recurrence: Option<Recurrence>,
+    pub duration: Option<i64>   // In seconds, sets the schedule end from the start
+}
+
+#[derive(Debug, FromRow, Serialize)]
+pub struct Template {
+    pub id: i64,
+    pub name: String,
+    pub settings: Json<TemplateSettings>,
+    pub created_at: i64,
+    pub updated_at: i64
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TemplateData {
+    pub id: Option<i64>,                // Update only
+    pub name: String,
+    #[serde(default)]
+    pub settings: TemplateSettings,
+    pub from_live_stream: Option<i64>   // Starts from the settings of this live stream, `settings` go over them
+}
+
+// A new live stream made from an existing one or from a template.
+#[derive(Debug, Deserialize)]
+pub struct CloneRequest {
+    pub live_stream: Option<i64>,
+    pub template: Option<i64>,
+    #[serde(default)]
+    pub overrides: TemplateSettings,
+    pub schedule_start: Option<i64>,    // Instead of the next occurrence of the recurrence
+    pub schedule_end: Option<i64>       // Instead of the start plus the duration
+}
@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize)]
+pub struct TestDestinationData {
+    pub live_stream_id: i64,
+    pub video: Option<i64>      // Pushed instead of the test pattern
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProbeOutcome {
+    Success,
+    Unreachable,    // No connection, or the handshake did not go through
+    Rejected,       // Connected, then turned away, usually a wrong stream key
+    Failed
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EndpointProbe {
+    pub endpoint: String,       // Without the stream key
+    pub outcome: ProbeOutcome,
+    pub message: Option<String> // The ffmpeg line the outcome was read from
+}
+
+// Result of the last destination test of a live stream, stored with it until
+// the destination is edited.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DestinationTest {
+    pub tested_at: i64,
+    pub endpoints: Vec<EndpointProbe>   // Primary first
+}
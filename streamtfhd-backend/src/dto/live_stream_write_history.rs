@@ -1,7 +1,19 @@
+use sqlx::types::Json;
+
+use crate::dto::live_stream_event::StreamEvent;
+
 pub struct History {
     pub owner: String,
     pub live_stream: i64,
     pub start_time: i64,
     pub end_time: i64,
-    pub end_status: String
+    pub end_status: String,
+    pub avg_cpu: Option<f32>,
+    pub peak_cpu: Option<f32>,
+    pub peak_memory: Option<i64>,
+    pub net_tx_bytes: Option<i64>,
+    pub loop_iteration: Option<i32>,    // Pass over the playlist the live stream ended in
+    pub loop_position: Option<i64>,     // Where in the video, in milliseconds
+    pub dry_run: bool,
+    pub events: Json<Vec<StreamEvent>>  // Transitions of the run, in order
 }
\ No newline at end of file
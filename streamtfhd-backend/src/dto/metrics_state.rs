@@ -0,0 +1,63 @@
+use dashmap::DashMap;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+
+use crate::models::websocket_dashboard_metrics::Metrics;
+
+// Upper bounds of the HTTP request latency histogram, in seconds.
+pub const HTTP_LATENCY_BUCKETS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HttpRequestKey {
+    pub method: String,
+    pub route: String,
+    pub status: u16
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HttpRequestStats {
+    pub buckets: [u64; HTTP_LATENCY_BUCKETS.len()],
+    pub sum: f64,
+    pub count: u64
+}
+
+pub struct MetricsState {
+    pub host: RwLock<Option<Metrics>>,
+    pub http_requests: DashMap<HttpRequestKey, HttpRequestStats>,
+    pub ingest_queue_depth: AtomicI64,
+    pub ffmpeg_restarts_total: AtomicU64
+}
+
+impl MetricsState {
+    pub fn new() -> Self {
+        MetricsState {
+            host: RwLock::new(None),
+            http_requests: DashMap::new(),
+            ingest_queue_depth: AtomicI64::new(0),
+            ffmpeg_restarts_total: AtomicU64::new(0)
+        }
+    }
+
+    pub fn start_ingest(&self) -> IngestGuard<'_> {
+        self.ingest_queue_depth.fetch_add(1, Ordering::Relaxed);
+
+        IngestGuard { metrics_state: self }
+    }
+}
+
+// Counts a gallery ingest as queued until it is dropped.
+pub struct IngestGuard<'a> {
+    metrics_state: &'a MetricsState
+}
+
+impl Drop for IngestGuard<'_> {
+    fn drop(&mut self) {
+        self.metrics_state.ingest_queue_depth.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl Default for MetricsState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
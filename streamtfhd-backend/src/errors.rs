@@ -99,6 +99,9 @@ pub enum AppError {
 
     #[error("Live stream conflict")]
     LiveStreamConflict,
+
+    #[error("{0}")]
+    LiveStreamLimitExceeded(String),
 }
 
 impl ResponseError for AppError {
@@ -135,7 +138,8 @@ impl ResponseError for AppError {
             AppError::LiveStreamFailed => StatusCode::INTERNAL_SERVER_ERROR,
             AppError::LiveStreamAlreadyLive => StatusCode::CONFLICT,
             AppError::LiveStreamAlreadyScheduled => StatusCode::CONFLICT,
-            AppError::LiveStreamConflict => StatusCode::CONFLICT
+            AppError::LiveStreamConflict => StatusCode::CONFLICT,
+            AppError::LiveStreamLimitExceeded(_) => StatusCode::TOO_MANY_REQUESTS
         }
     }
 
@@ -5,8 +5,10 @@ pub mod dto;
 pub mod utils;
 pub mod errors;
 
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use actix_web::{web, App, HttpServer};
+use actix_web::dev::Service;
 use actix_cors::Cors;
 use dashmap::DashMap;
 use models::database;
@@ -42,6 +44,12 @@ use crate::views::{
     gallery_delete_all_videos::delete_all_videos,
     gallery_search_video::search_video,
     gallery_import_from_drive::import_from_drive,
+    gallery_upload_audio::upload_audio,
+    gallery_get_audios::get_audios,
+    gallery_delete_audio::delete_audio,
+    gallery_upload_image::upload_image,
+    gallery_get_images::get_images,
+    gallery_delete_image::delete_image,
     live_stream_create_stream::create_live_stream as live_stream_create_stream,
     live_stream_get_videos::get_videos as live_stream_get_videos,
     live_stream_create_stream_search_video::search_video as create_stream_search_video,
@@ -57,10 +65,26 @@ use crate::views::{
     history::get_histories,
     history_delete::delete_history,
     history_search::search_history,
-    history_delete_all::delete_all
+    history_delete_all::delete_all,
+    metrics::metrics,
+    dashboard_metrics_history::get_metrics_history,
+    live_stream_calendar::get_calendar,
+    live_stream_calendar_feed::{get_feed_token, get_feed},
+    live_stream_swap_source::swap_source,
+    live_stream_overlay_text::update_overlay_text,
+    live_stream_test_destination::test_destination,
+    live_stream_preview::get_preview,
+    live_stream_bulk::{bulk, get_groups},
+    live_stream_template::{create_template, get_templates, update_template, delete_template},
+    live_stream_clone::clone_live_stream
 };
 use crate::models::websocket_dashboard_metrics::metrics_collector;
+use crate::utils::live_stream_resources::resources_collector;
+use crate::utils::live_stream_shutdown::{ShutdownConfig, readopt_streams, shutdown_streams};
+use crate::utils::live_stream_runner::FfmpegRunner;
 use crate::dto::live_stream_state::LiveStreamState;
+use crate::dto::metrics_state::MetricsState;
+use crate::utils::prometheus::observe_http_request;
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
@@ -126,6 +150,13 @@ async fn main() -> std::io::Result<()> {
             process::exit(8);
         }
     };
+    let upload_dir = match var("UPLOAD_DIRECTORY") {
+        Ok(val) => val,
+        Err(_err) => {
+            eprintln!("Missing UPLOAD_DIRECTORY key in env.");
+            process::exit(9);
+        }
+    };
 
     let pool = match database::pool(
         database_user,
@@ -150,15 +181,31 @@ async fn main() -> std::io::Result<()> {
         }
     }
 
+    let metrics_state = Arc::new(MetricsState::new());
     let state = Arc::new(LiveStreamState {
-        jobs: DashMap::new()
+        jobs: DashMap::new(),
+        metrics: metrics_state.clone(),
+        admission_lock: Mutex::new(()),
+        runner: Arc::new(FfmpegRunner),
+        events: broadcast::channel(256).0,
+        upload_dir
     });
+    let shutdown_config = ShutdownConfig::from_env();
     let (tx, _) = broadcast::channel(16);
+
+    // Take back the live streams left running by the previous shutdown
+    readopt_streams(&state, &pool).await;
     
     // Start background metrics task
-    tokio::spawn(metrics_collector(tx.clone()));
+    tokio::spawn(metrics_collector(tx.clone(), metrics_state.clone(), pool.clone()));
+
+    // Start background per stream resource accounting task
+    tokio::spawn(resources_collector(state.clone()));
 
-    HttpServer::new(move || {
+    let shutdown_state = state.clone();
+    let shutdown_pool = pool.clone();
+
+    let server = HttpServer::new(move || {
         let cors = Cors::default()
             .allowed_origin(&allowed_origin)
             .allowed_methods(vec!["GET", "POST", "PUT", "DELETE"])
@@ -169,11 +216,29 @@ async fn main() -> std::io::Result<()> {
             .supports_credentials()
             .max_age(3600);
 
+        let http_metrics_state = metrics_state.clone();
+
         App::new()
+            .wrap_fn(move |req, srv| {
+                let started_at = Instant::now();
+                let method = req.method().to_string();
+                let http_metrics_state = http_metrics_state.clone();
+                let fut = srv.call(req);
+
+                async move {
+                    let res = fut.await?;
+                    let route = res.request().match_pattern().unwrap_or_else(|| String::from("unmatched"));
+
+                    observe_http_request(&http_metrics_state, &method, &route, res.status().as_u16(), started_at.elapsed());
+
+                    Ok(res)
+                }
+            })
             .wrap(cors)
             .app_data(actix_web::web::Data::new(pool.clone()))
             .app_data(web::Data::new(tx.clone()))
             .app_data(web::Data::new(state.clone()))
+            .app_data(web::Data::new(metrics_state.clone()))
             
             .route("/hello", web::get().to(hello_world))
             
@@ -183,6 +248,7 @@ async fn main() -> std::io::Result<()> {
             .route("/login", web::post().to(login))
             .route("/check-credentials", web::get().to(check_credentials))
             .route("/websocket-dashboard-metrics", web::get().to(websocket_dashboard_metrics))
+            .route("/dashboard/metrics-history", web::get().to(get_metrics_history))
             .route("/get-server-time", web::get().to(server_time))
             .route("/get-avatar", web::get().to(get_avatar))
             .route("/settings/profile/get", web::get().to(settings_profile_settings_get))
@@ -199,6 +265,12 @@ async fn main() -> std::io::Result<()> {
             .route("/gallery/delete-all-videos", web::get().to(delete_all_videos))
             .route("/gallery/search-video", web::get().to(search_video))
             .route("/gallery/import-from-drive", web::get().to(import_from_drive))
+            .route("/gallery/upload-audio", web::post().to(upload_audio))
+            .route("/gallery/get-audios/{page}/{page_size}/{order}", web::get().to(get_audios))
+            .route("/gallery/delete-audio/{audio_id}", web::get().to(delete_audio))
+            .route("/gallery/upload-image", web::post().to(upload_image))
+            .route("/gallery/get-images/{page}/{page_size}/{order}", web::get().to(get_images))
+            .route("/gallery/delete-image/{image_id}", web::get().to(delete_image))
             .route("/live-stream/create-stream", web::post().to(live_stream_create_stream))
             .route("/live-stream/create-stream/search-video", web::get().to(create_stream_search_video))
             .route("/live-stream/get-videos", web::get().to(live_stream_get_videos))
@@ -211,10 +283,25 @@ async fn main() -> std::io::Result<()> {
             .route("/live-stream/stop/{live_stream_id}", web::get().to(stop_stream))
             .route("/live-stream/cancel/{live_stream_id}", web::get().to(cancel_stream))
             .route("/live-stream/monitor", web::get().to(monitor))
+            .route("/live-stream/swap-source", web::post().to(swap_source))
+            .route("/live-stream/test-destination", web::post().to(test_destination))
+            .route("/live-stream/preview/{id}/{file}", web::get().to(get_preview))
+            .route("/live-stream/bulk", web::post().to(bulk))
+            .route("/live-stream/groups", web::get().to(get_groups))
+            .route("/live-stream/templates/create", web::post().to(create_template))
+            .route("/live-stream/templates/get", web::get().to(get_templates))
+            .route("/live-stream/templates/update", web::post().to(update_template))
+            .route("/live-stream/templates/delete/{id}", web::get().to(delete_template))
+            .route("/live-stream/clone", web::post().to(clone_live_stream))
+            .route("/live-stream/overlay-text", web::post().to(update_overlay_text))
+            .route("/live-stream/calendar", web::get().to(get_calendar))
+            .route("/live-stream/calendar/feed-token", web::get().to(get_feed_token))
+            .route("/live-stream/calendar/feed/{token}", web::get().to(get_feed))
             .route("/history/get", web::get().to(get_histories))
             .route("/history/delete/{id}", web::get().to(delete_history))
             .route("/history/search", web::get().to(search_history))
             .route("/history/delete-all", web::get().to(delete_all))
+            .route("/metrics", web::get().to(metrics))
 
             .route("/uploads/images", web::get().to(uploads_images))
             .route("/uploads/videos", web::get().to(uploads_videos))
@@ -222,6 +309,12 @@ async fn main() -> std::io::Result<()> {
 
     })
     .bind(("127.0.0.1", port))?
-    .run()
-    .await
+    .run();
+
+    // Returns once the server has been stopped by a signal and its workers drained
+    let result = server.await;
+
+    shutdown_streams(&shutdown_state, &shutdown_config, &shutdown_pool).await;
+
+    result
 }
\ No newline at end of file
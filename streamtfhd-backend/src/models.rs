@@ -39,4 +39,23 @@ pub mod history_delete;
 pub mod history_search;
 pub mod history_delete_all;
 pub mod get_avatar;
-pub mod setup_account_check;
\ No newline at end of file
+pub mod setup_account_check;
+pub mod metrics;
+pub mod dashboard_metrics_history;
+pub mod live_stream_schedule;
+pub mod live_stream_detached;
+pub mod live_stream_swap_source;
+pub mod gallery_upload_audio;
+pub mod gallery_get_audios;
+pub mod gallery_delete_audio;
+pub mod gallery_upload_image;
+pub mod gallery_get_images;
+pub mod gallery_delete_image;
+pub mod live_stream_audio_playlist;
+pub mod live_stream_overlay;
+pub mod live_stream_playback;
+pub mod live_stream_recording;
+pub mod live_stream_ingest;
+pub mod live_stream_test_destination;
+pub mod live_stream_bulk;
+pub mod live_stream_template;
\ No newline at end of file
@@ -0,0 +1,228 @@
+use sqlx::{Pool, Postgres};
+use tracing::{error, debug, warn};
+use std::env::var;
+
+use crate::{
+    dto::dashboard_metrics_history::MetricsSample,
+    errors::AppError,
+    models::websocket_dashboard_metrics::Metrics
+};
+
+// Samples are stored as collected (every ~5 seconds) and rolled up into one
+// row per minute. Both resolutions are kept for their own retention period.
+pub const RAW_RESOLUTION: i32 = 5;
+pub const MINUTE_RESOLUTION: i32 = 60;
+
+const DEFAULT_RAW_RETENTION: i64 = 86400;           // 1 day
+const DEFAULT_MINUTE_RETENTION: i64 = 2592000;      // 30 days
+
+fn retention_from_env(key: &str, default: i64) -> i64 {
+    match var(key) {
+        Ok(val) => match val.trim().parse::<i64>() {
+            Ok(val) if val > 0 => val,
+            _ => {
+                warn!("Invalid {} value in env file, using {} seconds.", key, default);
+
+                default
+            }
+        },
+        Err(_) => default
+    }
+}
+
+pub fn raw_retention() -> i64 {
+    retention_from_env("METRICS_HISTORY_RAW_RETENTION", DEFAULT_RAW_RETENTION)
+}
+
+pub fn minute_retention() -> i64 {
+    retention_from_env("METRICS_HISTORY_MINUTE_RETENTION", DEFAULT_MINUTE_RETENTION)
+}
+
+pub async fn insert_sample(
+    metrics: &Metrics,
+    sampled_at: i64,
+    pool: &Pool<Postgres>
+) -> bool {
+    let insert = sqlx::query(
+        "INSERT INTO host_metrics_history (
+                    resolution,
+                    sampled_at,
+                    cpu,
+                    used_memory,
+                    total_memory,
+                    disk_used,
+                    disk_total,
+                    download_kbps,
+                    upload_kbps
+                ) VALUES (
+                    $1, $2, $3, $4, $5, $6, $7, $8, $9
+                )"
+    )
+        .bind(RAW_RESOLUTION)
+        .bind(sampled_at)
+        .bind(metrics.cpu)
+        .bind(metrics.used_memory as i64)
+        .bind(metrics.total_memory as i64)
+        .bind(metrics.disk_used as i64)
+        // `disk_available` holds the total disk space, see `metrics_collector`.
+        .bind(metrics.disk_available as i64)
+        .bind(metrics.download_kbps as i64)
+        .bind(metrics.upload_kbps as i64)
+        .execute(pool)
+        .await;
+
+    match insert {
+        Ok(_) => true,
+        Err(err) => {
+            error!("Failed to store host metrics sample to the database.");
+            debug!("{}", err);
+
+            false
+        }
+    }
+}
+
+// Rolls the raw samples in [from, to) up into one row per minute.
+pub async fn downsample(
+    from: i64,
+    to: i64,
+    pool: &Pool<Postgres>
+) -> bool {
+    let insert = sqlx::query(
+        r#"
+        INSERT INTO host_metrics_history (
+                resolution,
+                sampled_at,
+                cpu,
+                used_memory,
+                total_memory,
+                disk_used,
+                disk_total,
+                download_kbps,
+                upload_kbps
+        )
+        SELECT
+                $1,
+                (sampled_at / $1) * $1 AS bucket,
+                AVG(cpu)::REAL,
+                AVG(used_memory)::BIGINT,
+                MAX(total_memory),
+                AVG(disk_used)::BIGINT,
+                MAX(disk_total),
+                AVG(download_kbps)::BIGINT,
+                AVG(upload_kbps)::BIGINT
+        FROM host_metrics_history
+        WHERE resolution = $2
+            AND sampled_at >= $3
+            AND sampled_at < $4
+        GROUP BY bucket
+        "#
+    )
+        .bind(MINUTE_RESOLUTION)
+        .bind(RAW_RESOLUTION)
+        .bind(from)
+        .bind(to)
+        .execute(pool)
+        .await;
+
+    match insert {
+        Ok(_) => true,
+        Err(err) => {
+            error!("Failed to downsample host metrics history.");
+            debug!("{}", err);
+
+            false
+        }
+    }
+}
+
+pub async fn prune(
+    resolution: i32,
+    older_than: i64,
+    pool: &Pool<Postgres>
+) -> bool {
+    let delete = sqlx::query(
+        "DELETE FROM host_metrics_history
+                WHERE resolution = $1
+                    AND sampled_at < $2"
+    )
+        .bind(resolution)
+        .bind(older_than)
+        .execute(pool)
+        .await;
+
+    match delete {
+        Ok(_) => true,
+        Err(err) => {
+            error!("Failed to prune host metrics history.");
+            debug!("{}", err);
+
+            false
+        }
+    }
+}
+
+pub async fn get_last_sample_time(
+    resolution: i32,
+    pool: &Pool<Postgres>
+) -> Option<i64> {
+    let res: Result<Option<i64>, sqlx::Error> = sqlx::query_scalar(
+        "SELECT MAX(sampled_at) FROM host_metrics_history WHERE resolution = $1"
+    )
+        .bind(resolution)
+        .fetch_one(pool)
+        .await;
+
+    match res {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to get last host metrics sample time.");
+            debug!("{}", err);
+
+            None
+        }
+    }
+}
+
+pub async fn get_history(
+    from: i64,
+    to: i64,
+    resolution: i32,
+    pool: &Pool<Postgres>
+) -> Result<Vec<MetricsSample>, AppError> {
+    let result = sqlx::query_as::<_, MetricsSample>(
+        r#"
+        SELECT
+                sampled_at,
+                cpu,
+                used_memory,
+                total_memory,
+                disk_used,
+                disk_total,
+                download_kbps,
+                upload_kbps
+        FROM host_metrics_history
+        WHERE resolution = $1
+            AND sampled_at >= $2
+            AND sampled_at <= $3
+        ORDER BY sampled_at ASC
+        "#
+    )
+        .bind(resolution)
+        .bind(from)
+        .bind(to)
+        .fetch_all(pool)
+        .await;
+
+    let samples = match result {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to get host metrics history.");
+            debug!("{}", err);
+
+            return Err(AppError::Database(err));
+        }
+    };
+
+    Ok(samples)
+}
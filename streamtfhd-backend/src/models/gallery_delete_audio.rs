@@ -0,0 +1,101 @@
+use sqlx::{Pool, Postgres};
+use tracing::{error, debug};
+use crate::errors::AppError;
+use std::fs;
+
+// Slates let go of a deleted audio on their own, playlists do not.
+async fn is_audio_used_in_live_stream(
+    audio_id: i64,
+    pool: &Pool<Postgres>
+) -> Result<bool, AppError> {
+    let result: Result<i64, sqlx::Error> = sqlx::query_scalar(
+        "SELECT COUNT(audio) FROM live_stream_audios WHERE audio = $1"
+    )
+    .bind(audio_id)
+    .fetch_one(pool)
+    .await;
+
+    let count = match result {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to get audio info.");
+            debug!("{}", err);
+
+            return Err(AppError::Database(err));
+        }
+    };
+
+    Ok(count > 0)
+}
+
+pub async fn get_audio_owner(
+    audio_id: i64,
+    pool: &Pool<Postgres>
+) -> Result<Option<String>, AppError> {
+    let res: Result<Option<String>, sqlx::Error> = sqlx::query_scalar(
+        "SELECT owner FROM audios WHERE id = $1"
+    )
+        .bind(audio_id)
+        .fetch_optional(pool)
+        .await;
+
+    let result = match res {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to get audio owner.");
+            debug!("{}", err);
+
+            return Err(AppError::Database(err));
+        }
+    };
+
+    Ok(result)
+}
+
+pub async fn delete_audio(
+    audio_id: i64,
+    upload_directory: &String,
+    pool: &Pool<Postgres>
+) -> Result<bool, AppError> {
+    if is_audio_used_in_live_stream(audio_id, pool).await? {
+        error!("Failed to delete audio. The audio is used in live stream");
+        return Err(AppError::Conflict("Failed to delete audio. The audio is used in live stream".to_string()))
+    }
+
+    let res: Result<Option<String>, sqlx::Error> = sqlx::query_scalar(
+        r#"
+        DELETE FROM audios
+        WHERE id = $1
+        RETURNING file
+        "#
+    )
+        .bind(audio_id)
+        .fetch_optional(pool)
+        .await;
+    let file = match res {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to delete audio from database.");
+            debug!("{}", err);
+
+            return Err(AppError::Database(err));
+        }
+    };
+
+    let file = match file {
+        Some(val) => val,
+        None => return Ok(false)
+    };
+
+    match fs::remove_file(format!("{}/audios/{}", upload_directory, file)) {
+        Ok(_) => (),
+        Err(err) => {
+            error!("Failed to delete audio file.");
+            debug!("{}", err);
+
+            return Err(AppError::IO(err));
+        }
+    }
+
+    Ok(true)
+}
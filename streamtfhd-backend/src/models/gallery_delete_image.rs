@@ -0,0 +1,106 @@
+use sqlx::{Pool, Postgres};
+use tracing::{error, debug};
+use crate::errors::AppError;
+use std::fs;
+
+// Slates let go of a deleted image on their own, backgrounds and watermarks do not.
+async fn is_image_used_in_live_stream(
+    image_id: i64,
+    pool: &Pool<Postgres>
+) -> Result<bool, AppError> {
+    let result: Result<i64, sqlx::Error> = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(id)
+        FROM live_streams
+        WHERE background_image = $1
+            OR overlays @> jsonb_build_array(jsonb_build_object('kind', 'watermark', 'image', $1::bigint))
+        "#
+    )
+    .bind(image_id)
+    .fetch_one(pool)
+    .await;
+
+    let count = match result {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to get image info.");
+            debug!("{}", err);
+
+            return Err(AppError::Database(err));
+        }
+    };
+
+    Ok(count > 0)
+}
+
+pub async fn get_image_owner(
+    image_id: i64,
+    pool: &Pool<Postgres>
+) -> Result<Option<String>, AppError> {
+    let res: Result<Option<String>, sqlx::Error> = sqlx::query_scalar(
+        "SELECT owner FROM images WHERE id = $1"
+    )
+        .bind(image_id)
+        .fetch_optional(pool)
+        .await;
+
+    let result = match res {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to get image owner.");
+            debug!("{}", err);
+
+            return Err(AppError::Database(err));
+        }
+    };
+
+    Ok(result)
+}
+
+pub async fn delete_image(
+    image_id: i64,
+    upload_directory: &String,
+    pool: &Pool<Postgres>
+) -> Result<bool, AppError> {
+    if is_image_used_in_live_stream(image_id, pool).await? {
+        error!("Failed to delete image. The image is used in live stream");
+        return Err(AppError::Conflict("Failed to delete image. The image is used in live stream".to_string()))
+    }
+
+    let res: Result<Option<String>, sqlx::Error> = sqlx::query_scalar(
+        r#"
+        DELETE FROM images
+        WHERE id = $1
+        RETURNING file
+        "#
+    )
+        .bind(image_id)
+        .fetch_optional(pool)
+        .await;
+    let file = match res {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to delete image from database.");
+            debug!("{}", err);
+
+            return Err(AppError::Database(err));
+        }
+    };
+
+    let file = match file {
+        Some(val) => val,
+        None => return Ok(false)
+    };
+
+    match fs::remove_file(format!("{}/gallery_images/{}", upload_directory, file)) {
+        Ok(_) => (),
+        Err(err) => {
+            error!("Failed to delete image file.");
+            debug!("{}", err);
+
+            return Err(AppError::IO(err));
+        }
+    }
+
+    Ok(true)
+}
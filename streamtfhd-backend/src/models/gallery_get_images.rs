@@ -0,0 +1,71 @@
+use sqlx::{Pool, Postgres};
+use crate::{dto::gallery_get_images::Image, errors::AppError};
+use tracing::{error, debug};
+
+pub async fn get_total_images(
+    pool: &Pool<Postgres>,
+    user_id: &String
+) -> Result<i64, AppError> {
+    let result: Result<i64, sqlx::Error> = sqlx::query_scalar(
+        "SELECT COUNT(id) FROM images WHERE owner = $1"
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await;
+
+    match result {
+        Ok(val) => Ok(val),
+        Err(err) => {
+            error!("Failed to get total images.");
+            debug!("{}", err);
+
+            Err(AppError::Database(err))
+        }
+    }
+}
+
+pub async fn get_images_paginated(
+    pool: &Pool<Postgres>,
+    user_id: &String,
+    page: u32,
+    page_size: u32,
+    order: &str,
+) -> Result<Vec<Image>, AppError> {
+    let offset = (page.saturating_sub(1) * page_size) as i64;
+
+    let order_clause = match order.to_lowercase().as_str() {
+        "oldest" => "ASC",
+        "newest" => "DESC",
+        _ => "DESC",
+    };
+
+    let sql = format!(
+        r#"
+        SELECT id, owner, title, file, width, height, size, uploaded_at
+        FROM images
+        WHERE owner = $1
+        ORDER BY id {}
+        LIMIT $2 OFFSET $3
+        "#,
+        order_clause
+    );
+
+    let images = sqlx::query_as::<_, Image>(&sql)
+    .bind(user_id)
+    .bind(page_size as i64)
+    .bind(offset)
+    .fetch_all(pool)
+    .await;
+
+    let images_ret = match images {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to get images from database.");
+            debug!("{}", err);
+
+            return Err(AppError::Database(err));
+        }
+    };
+
+    Ok(images_ret)
+}
\ No newline at end of file
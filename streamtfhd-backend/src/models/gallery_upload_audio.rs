@@ -0,0 +1,286 @@
+use sqlx::{Pool, Postgres};
+use crate::errors::AppError;
+use actix_multipart::Multipart;
+use sanitize_filename::sanitize;
+use futures_util::StreamExt;
+use std::path::Path;
+use uuid::Uuid;
+use tokio::{fs::File, io::AsyncWriteExt};
+use tracing::{error, debug, info};
+use crate::utils::time::current_unix_timestamp;
+use std::fs;
+use std::process::Command;
+
+#[derive(Debug, Default, PartialEq)]
+struct AudioInfo {
+    bit_rate: i64,      // In kbps
+    sample_rate: i64,
+    channels: i64,
+    length: i64         // In seconds
+}
+
+// Parses the `key=value` lines printed by ffprobe with `-of default=noprint_wrappers=1`.
+fn parse_audio_info(output: &str) -> Result<AudioInfo, AppError> {
+    let mut info = AudioInfo::default();
+    let mut has_duration = false;
+
+    for line in output.lines() {
+        let (key, value) = match line.trim().split_once('=') {
+            Some(val) => val,
+            None => continue
+        };
+
+        // Some containers report N/A for the stream values.
+        if value == "N/A" {
+            continue;
+        }
+
+        match key {
+            "sample_rate" => info.sample_rate = value.parse().map_err(|_| AppError::ParseIntError)?,
+            "channels" => info.channels = value.parse().map_err(|_| AppError::ParseIntError)?,
+            "bit_rate" => {
+                let bit_rate: i64 = value.parse().map_err(|_| AppError::ParseIntError)?;
+
+                info.bit_rate = bit_rate / 1000;
+            }
+            "duration" => {
+                let duration: f64 = value.parse().map_err(|_| AppError::ParseFloatError)?;
+
+                info.length = duration as i64;
+                has_duration = true;
+            }
+            _ => ()
+        }
+    }
+
+    if !has_duration || info.sample_rate == 0 {
+        return Err(AppError::BadRequest("The file has no audio stream".to_string()));
+    }
+
+    Ok(info)
+}
+
+// ffprobe -v error -select_streams a:0 -show_entries stream=sample_rate,channels:format=duration,bit_rate -of default=noprint_wrappers=1 audio.mp3
+fn get_audio_info(
+    audio_file: &String,
+    upload_directory: &String
+) -> Result<AudioInfo, AppError> {
+    let audio_path = format!("{}/audios/{}", upload_directory, audio_file);
+    let command = Command::new("ffprobe")
+        .args([
+                "-v", "error",
+                "-select_streams", "a:0",
+                "-show_entries", "stream=sample_rate,channels:format=duration,bit_rate",
+                "-of", "default=noprint_wrappers=1",
+                &audio_path
+            ])
+        .output();
+
+    info!("Getting audio info.");
+    info!("ffprobe -v error -select_streams a:0 -show_entries stream=sample_rate,channels:format=duration,bit_rate -of default=noprint_wrappers=1 {}", audio_path);
+
+    let command_output = match command {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to execute ffprobe program while getting audio info.");
+            debug!("{}", err);
+
+            return Err(AppError::InternalError(String::from("Failed to execute ffprobe program while getting audio info.")));
+        }
+    };
+    let command_status_code = command_output.status.code().unwrap_or_default();
+
+    if command_status_code > 0 {
+        let stderr = String::from_utf8_lossy(&command_output.stderr);
+
+        debug!("{:?}", stderr);
+
+        return Err(AppError::InternalError(String::from("Failed to get audio info.")));
+    }
+
+    let output = String::from_utf8_lossy(&command_output.stdout).to_string();
+
+    parse_audio_info(&output)
+}
+
+async fn save_audio_info_to_database(
+    pool: &Pool<Postgres>,
+    upload_directory: &String,
+    audio_title: &String,
+    audio_file: &String,
+    owner: &String
+) -> Result<bool, AppError> {
+    let timestamp = current_unix_timestamp();
+    let audio_file_path = format!("{}/audios/{}", upload_directory, audio_file);
+    let audio_info = get_audio_info(audio_file, upload_directory)?;
+    let audio_size = match fs::metadata(audio_file_path) {
+        Ok(val) => val.len() / 1024,
+        Err(err) => {
+            error!("Failed to get audio size.");
+            debug!("{}", err);
+
+            return Err(AppError::IO(err));
+        }
+    };
+
+    let insert = sqlx::query(
+        "INSERT INTO audios (
+                    owner,
+                    title,
+                    file,
+                    bit_rate,
+                    sample_rate,
+                    channels,
+                    length,
+                    size,
+                    uploaded_at
+                ) VALUES (
+                    $1, $2, $3, $4, $5, $6, $7, $8, $9
+                )"
+    )
+        .bind(owner)
+        .bind(audio_title)
+        .bind(audio_file)
+        .bind(audio_info.bit_rate as i32)
+        .bind(audio_info.sample_rate as i32)
+        .bind(audio_info.channels as i32)
+        .bind(audio_info.length as i32)
+        .bind(audio_size as i32)
+        .bind(timestamp as i32)
+        .execute(pool)
+        .await;
+
+    match insert {
+        Ok(_) => Ok(true),
+        Err(err) => {
+            error!("Failed to store audio data to the database.");
+            debug!("{}", err);
+
+            Err(AppError::Database(err))
+        }
+    }
+}
+
+pub async fn upload_audio(
+    mut payload: Multipart,
+    pool: &Pool<Postgres>,
+    upload_directory: &String,
+    owner: &String
+) -> Result<bool, AppError> {
+    let create_dir = format!("{}/audios", upload_directory);
+    match tokio::fs::create_dir_all(&create_dir).await {
+        Ok(_) => (),
+        Err(err) => {
+            error!("Failed to create uploads directory.");
+            debug!("{}", err);
+
+            return Err(AppError::IO(err));
+        }
+    }
+
+    while let Some(item) = payload.next().await {
+        let mut field = item?;
+
+        // ===== MIME TYPE VALIDATION =====
+        let valid_mime = match field.content_type() {
+            Some(mime) => matches!(
+                mime.essence_str(),
+                "audio/mpeg" | "audio/mp4" | "audio/x-m4a" | "audio/aac" | "audio/ogg" | "audio/flac" | "audio/x-flac" | "audio/wav" | "audio/x-wav"
+            ),
+            None => false,
+        };
+
+        if !valid_mime {
+            info!("An attemp to upload audio with unsupported format.");
+            return Err(AppError::BadRequest("Only MP3, M4A, AAC, OGG, FLAC, and WAV files are allowed".to_string()));
+        }
+
+        // ===== EXTENSION VALIDATION =====
+        let filename = field
+            .content_disposition()
+            .get_filename()
+            .map(sanitize)
+            .ok_or_else(|| AppError::BadRequest("Missing filename".to_string()))?;
+
+        let ext = Path::new(&filename)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase())
+            .ok_or_else(|| AppError::BadRequest("Invalid file extension".to_string()))?;
+
+        if !matches!(ext.as_str(), "mp3" | "m4a" | "aac" | "ogg" | "flac" | "wav") {
+            return Err(AppError::BadRequest("Only MP3, M4A, AAC, OGG, FLAC, and WAV files are allowed".to_string()));
+        }
+
+        // ===== SAVE FILE =====
+        let stored_name = format!("{}-{}", Uuid::new_v4(), filename);
+        let filepath = format!("{}/audios/{}", upload_directory, stored_name);
+        let mut file = match File::create(&filepath).await {
+            Ok(val) => val,
+            Err(err) => {
+                error!("Failed to create upload file.");
+                debug!("{}", err);
+
+                return Err(AppError::InternalError(String::from("Failed to create upload file")));
+            }
+        };
+
+        while let Some(chunk) = field.next().await {
+            let data = match chunk {
+                Ok(val) => val,
+                Err(err) => {
+                    error!("Failed to read chunk data.");
+                    debug!("{}", err);
+
+                    return Err(AppError::MultipartError(err));
+                }
+            };
+
+            match file.write_all(&data).await {
+                Ok(_) => (),
+                Err(err) => {
+                    error!("Failed to write chunk data.");
+                    debug!("{}", err);
+
+                    return Err(AppError::IO(err));
+                }
+            }
+        }
+
+        let audio_title = match Path::new(&filename).file_stem().and_then(|s| s.to_str()) {
+            Some(val) => val,
+            None => &filename
+        };
+
+        // A file ffprobe cannot read is not kept around.
+        if let Err(err) = save_audio_info_to_database(pool, upload_directory, &audio_title.to_string(), &stored_name, owner).await {
+            let _ = tokio::fs::remove_file(&filepath).await;
+
+            return Err(err);
+        }
+    }
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn audio_info_is_parsed_from_ffprobe_output() {
+        let output = "sample_rate=44100\nchannels=2\nduration=215.466667\nbit_rate=320000\n";
+
+        assert_eq!(parse_audio_info(output).unwrap(), AudioInfo {
+            bit_rate: 320,
+            sample_rate: 44100,
+            channels: 2,
+            length: 215
+        });
+    }
+
+    #[test]
+    fn missing_audio_stream_is_rejected() {
+        assert!(parse_audio_info("duration=12.0\nbit_rate=128000\n").is_err());
+    }
+}
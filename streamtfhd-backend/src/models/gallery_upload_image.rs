@@ -0,0 +1,242 @@
+use sqlx::{Pool, Postgres};
+use crate::errors::AppError;
+use actix_multipart::Multipart;
+use sanitize_filename::sanitize;
+use futures_util::StreamExt;
+use std::path::Path;
+use uuid::Uuid;
+use tokio::{fs::File, io::AsyncWriteExt};
+use tracing::{error, debug, info};
+use crate::utils::time::current_unix_timestamp;
+use std::fs;
+use std::process::Command;
+
+struct ImageResolution {
+    width: i64,
+    height: i64
+}
+
+// ffprobe -v error -select_streams v:0 -show_entries stream=width,height -of csv=s=x:p=0 image.jpg
+fn get_image_resolution(
+    image_file: &String,
+    upload_directory: &String
+) -> Result<ImageResolution, AppError> {
+    let image_path = format!("{}/gallery_images/{}", upload_directory, image_file);
+
+    let command = Command::new("ffprobe")
+        .args([
+                "-v", "error",
+                "-select_streams", "v:0",
+                "-show_entries", "stream=width,height",
+                "-of", "csv=s=x:p=0",
+                &image_path
+            ])
+        .output();
+
+    info!("Getting image resolution.");
+    info!("ffprobe -v error -select_streams v:0 -show_entries stream=width,height -of csv=s=x:p=0 {}", image_path);
+
+    let command_output = match command {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to execute ffprobe program while getting image resolution.");
+            debug!("{}", err);
+
+            return Err(AppError::InternalError(String::from("Failed to execute ffprobe program while getting image resolution.")));
+        }
+    };
+    let command_status_code = command_output.status.code().unwrap_or_default();
+
+    if command_status_code > 0 {
+        let stderr = String::from_utf8_lossy(&command_output.stderr);
+
+        debug!("{:?}", stderr);
+
+        return Err(AppError::InternalError(String::from("Failed to get image resolution")));
+    }
+
+    let output = String::from_utf8_lossy(&command_output.stdout).to_string();
+    let (width_str, height_str) = match output.trim().split_once('x') {
+        Some(val) => val,
+        None => {
+            error!("Failed to get image resolution.");
+
+            return Err(AppError::InternalError("Failed to get image resolution.".to_string()));
+        }
+    };
+    let width: i64 = match width_str.trim().parse() {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to parse value to integer.");
+            debug!("{}", err);
+
+            return Err(AppError::ParseIntError);
+        }
+    };
+    let height: i64 = match height_str.trim().parse() {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to parse value to integer.");
+            debug!("{}", err);
+
+            return Err(AppError::ParseIntError);
+        }
+    };
+
+    Ok(ImageResolution { width, height })
+}
+
+async fn save_image_info_to_database(
+    pool: &Pool<Postgres>,
+    upload_directory: &String,
+    image_title: &String,
+    image_file: &String,
+    owner: &String
+) -> Result<bool, AppError> {
+    let timestamp = current_unix_timestamp();
+    let image_file_path = format!("{}/gallery_images/{}", upload_directory, image_file);
+    let image_resolution = get_image_resolution(image_file, upload_directory)?;
+    let image_size = match fs::metadata(image_file_path) {
+        Ok(val) => val.len() / 1024,
+        Err(err) => {
+            error!("Failed to get image size.");
+            debug!("{}", err);
+
+            return Err(AppError::IO(err));
+        }
+    };
+
+    let insert = sqlx::query(
+        "INSERT INTO images (
+                    owner,
+                    title,
+                    file,
+                    width,
+                    height,
+                    size,
+                    uploaded_at
+                ) VALUES (
+                    $1, $2, $3, $4, $5, $6, $7
+                )"
+    )
+        .bind(owner)
+        .bind(image_title)
+        .bind(image_file)
+        .bind(image_resolution.width as i32)
+        .bind(image_resolution.height as i32)
+        .bind(image_size as i32)
+        .bind(timestamp as i32)
+        .execute(pool)
+        .await;
+
+    match insert {
+        Ok(_) => Ok(true),
+        Err(err) => {
+            error!("Failed to store image data to the database.");
+            debug!("{}", err);
+
+            Err(AppError::Database(err))
+        }
+    }
+}
+
+pub async fn upload_image(
+    mut payload: Multipart,
+    pool: &Pool<Postgres>,
+    upload_directory: &String,
+    owner: &String
+) -> Result<bool, AppError> {
+    let create_dir = format!("{}/gallery_images", upload_directory);
+    match tokio::fs::create_dir_all(&create_dir).await {
+        Ok(_) => (),
+        Err(err) => {
+            error!("Failed to create uploads directory.");
+            debug!("{}", err);
+
+            return Err(AppError::IO(err));
+        }
+    }
+
+    while let Some(item) = payload.next().await {
+        let mut field = item?;
+
+        // ===== MIME TYPE VALIDATION =====
+        let valid_mime = match field.content_type() {
+            Some(mime) => matches!(
+                mime.essence_str(),
+                "image/jpeg" | "image/png" | "image/webp"
+            ),
+            None => false,
+        };
+
+        if !valid_mime {
+            info!("An attemp to upload image with unsupported format.");
+            return Err(AppError::BadRequest("Only JPG, PNG, and WEBP files are allowed".to_string()));
+        }
+
+        // ===== EXTENSION VALIDATION =====
+        let filename = field
+            .content_disposition()
+            .get_filename()
+            .map(sanitize)
+            .ok_or_else(|| AppError::BadRequest("Missing filename".to_string()))?;
+
+        let ext = Path::new(&filename)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase())
+            .ok_or_else(|| AppError::BadRequest("Invalid file extension".to_string()))?;
+
+        if !matches!(ext.as_str(), "jpg" | "jpeg" | "png" | "webp") {
+            return Err(AppError::BadRequest("Only JPG, PNG, and WEBP files are allowed".to_string()));
+        }
+
+        // ===== SAVE FILE =====
+        let stored_name = format!("{}-{}", Uuid::new_v4(), filename);
+        let filepath = format!("{}/gallery_images/{}", upload_directory, stored_name);
+        let mut file = match File::create(&filepath).await {
+            Ok(val) => val,
+            Err(err) => {
+                error!("Failed to create upload file.");
+                debug!("{}", err);
+
+                return Err(AppError::InternalError(String::from("Failed to create upload file")));
+            }
+        };
+
+        while let Some(chunk) = field.next().await {
+            let data = match chunk {
+                Ok(val) => val,
+                Err(err) => {
+                    error!("Failed to read chunk data.");
+                    debug!("{}", err);
+
+                    return Err(AppError::MultipartError(err));
+                }
+            };
+
+            match file.write_all(&data).await {
+                Ok(_) => (),
+                Err(err) => {
+                    error!("Failed to write chunk data.");
+                    debug!("{}", err);
+
+                    return Err(AppError::IO(err));
+                }
+            }
+        }
+
+        let image_title = match Path::new(&filename).file_stem().and_then(|s| s.to_str()) {
+            Some(val) => val,
+            None => &filename
+        };
+
+        if let Err(err) = save_image_info_to_database(pool, upload_directory, &image_title.to_string(), &stored_name, owner).await {
+            let _ = tokio::fs::remove_file(&filepath).await;
+
+            return Err(err);
+        }
+    }
+
+    Ok(true)
+}
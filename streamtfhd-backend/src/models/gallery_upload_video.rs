@@ -282,7 +282,7 @@ fn get_video_resolution(
 }
 
 // ffmpeg -i input.mp4 -vf "thumbnail,scale=320:-1" -frames:v 1 output.jpg
-async fn create_video_thumbnail(
+pub async fn create_video_thumbnail(
     video_file: &String,
     upload_directory: &String
 ) -> Result<String, AppError> {
@@ -342,14 +342,15 @@ async fn create_video_thumbnail(
     Ok(output_thumbnail_file)
 }
 
-async fn save_video_info_to_database(
+// Returns the ID of the new video.
+pub async fn save_video_info_to_database(
     pool: &Pool<Postgres>,
     upload_directory: &String,
     video_title: &String,
     video_file: &String,
     video_thumbnail_file: &String,
     owner: &String
-) -> Result<bool, AppError> {
+) -> Result<i64, AppError> {
     let timestamp = current_unix_timestamp();
     let video_file_path = format!("{}/videos/{}", upload_directory, video_file);
     let video_resolution = get_video_resolution(&video_file, &upload_directory)?;
@@ -366,7 +367,7 @@ async fn save_video_info_to_database(
         }
     };
 
-    let insert = sqlx::query(
+    let insert: Result<i64, sqlx::Error> = sqlx::query_scalar(
         "INSERT INTO videos (
                     owner,
                     title,
@@ -381,7 +382,8 @@ async fn save_video_info_to_database(
                     uploaded_at
                 ) VALUES (
                     $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11
-                )"
+                )
+                RETURNING id"
     )
         .bind(owner)
         .bind(video_title)
@@ -394,11 +396,11 @@ async fn save_video_info_to_database(
         .bind(video_length)
         .bind(video_size as i64)
         .bind(timestamp as i64)
-        .execute(pool)
+        .fetch_one(pool)
         .await;
 
     match insert {
-        Ok(_) => Ok(true),
+        Ok(val) => Ok(val),
         Err(err) => {
             error!("Failed to store video data to the database.");
             debug!("{}", err);
@@ -17,13 +17,27 @@ pub async fn search_history(
         SELECT
                 live_stream_history.id,
                 live_streams.title AS live_stream_title,
-                videos.thumbnail AS video_thumbnail,
+                COALESCE(videos.thumbnail, '') AS video_thumbnail,
                 live_stream_history.start_time,
-                live_stream_history.end_time
+                live_stream_history.end_time,
+                live_stream_history.avg_cpu,
+                live_stream_history.peak_cpu,
+                live_stream_history.peak_memory,
+                live_stream_history.net_tx_bytes,
+                live_stream_history.loop_iteration,
+                live_stream_history.loop_position,
+                live_stream_history.dry_run,
+                live_stream_history.events,
+                ARRAY(
+                    SELECT live_stream_recordings.video
+                    FROM live_stream_recordings
+                    WHERE live_stream_recordings.history = live_stream_history.id
+                    ORDER BY live_stream_recordings.position ASC
+                ) AS recordings
         FROM live_stream_history
         INNER JOIN live_streams
             ON live_stream_history.live_stream = live_streams.id
-        INNER JOIN videos
+        LEFT JOIN videos
             ON live_streams.video = videos.id
         WHERE live_stream_history.owner = $1
             AND live_stream_history.end_status IN ('Stopped', 'Done')
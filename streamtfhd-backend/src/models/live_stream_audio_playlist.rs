@@ -0,0 +1,107 @@
+use sqlx::{Pool, Postgres, Transaction};
+use tracing::{error, debug};
+
+use crate::{
+    dto::live_stream_edit_stream_get::Audio,
+    errors::AppError
+};
+
+// Number of distinct audios among `ids` owned by `owner`.
+pub async fn count_owned_audios(
+    ids: &[i64],
+    owner: &String,
+    pool: &Pool<Postgres>
+) -> Result<i64, AppError> {
+    let res: Result<i64, sqlx::Error> = sqlx::query_scalar(
+        "SELECT COUNT(id) FROM audios WHERE id = ANY($1) AND owner = $2"
+    )
+        .bind(ids)
+        .bind(owner)
+        .fetch_one(pool)
+        .await;
+
+    match res {
+        Ok(val) => Ok(val),
+        Err(err) => {
+            error!("Failed to count owned audios.");
+            debug!("{}", err);
+
+            Err(AppError::Database(err))
+        }
+    }
+}
+
+// Replaces the audio playlist of a live stream, keeping the given order. It
+// runs in the transaction that writes the live stream itself.
+pub async fn replace_playlist(
+    live_stream_id: i64,
+    audios: &[i64],
+    tx: &mut Transaction<'_, Postgres>
+) -> Result<(), AppError> {
+    let res = sqlx::query("DELETE FROM live_stream_audios WHERE live_stream = $1")
+        .bind(live_stream_id)
+        .execute(&mut **tx)
+        .await;
+
+    if let Err(err) = res {
+        error!("Failed to delete live stream audio playlist.");
+        debug!("{}", err);
+
+        return Err(AppError::Database(err));
+    }
+
+    if audios.is_empty() {
+        return Ok(());
+    }
+
+    let res = sqlx::query(
+        r#"
+        INSERT INTO live_stream_audios (live_stream, position, audio)
+        SELECT $1, playlist.position, playlist.audio
+        FROM UNNEST($2::BIGINT[]) WITH ORDINALITY AS playlist(audio, position)
+        "#
+    )
+        .bind(live_stream_id)
+        .bind(audios)
+        .execute(&mut **tx)
+        .await;
+
+    match res {
+        Ok(_) => Ok(()),
+        Err(err) => {
+            error!("Failed to insert live stream audio playlist.");
+            debug!("{}", err);
+
+            Err(AppError::Database(err))
+        }
+    }
+}
+
+pub async fn get_playlist(
+    live_stream_id: i64,
+    pool: &Pool<Postgres>
+) -> Result<Vec<Audio>, AppError> {
+    let res = sqlx::query_as::<_, Audio>(
+        r#"
+        SELECT audios.id, audios.title, audios.file, audios.length
+        FROM live_stream_audios
+        INNER JOIN audios
+            ON live_stream_audios.audio = audios.id
+        WHERE live_stream_audios.live_stream = $1
+        ORDER BY live_stream_audios.position ASC
+        "#
+    )
+        .bind(live_stream_id)
+        .fetch_all(pool)
+        .await;
+
+    match res {
+        Ok(val) => Ok(val),
+        Err(err) => {
+            error!("Failed to get live stream audio playlist.");
+            debug!("{}", err);
+
+            Err(AppError::Database(err))
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use sqlx::{Pool, Postgres};
+use tracing::{error, debug};
+
+use crate::{
+    dto::live_stream_bulk::StreamGroup,
+    dto::live_stream_schedule::ScheduleCandidate,
+    errors::AppError
+};
+
+pub async fn get_live_stream_owners(
+    ids: &[i64],
+    pool: &Pool<Postgres>
+) -> Result<Vec<(i64, String)>, AppError> {
+    let res: Result<Vec<(i64, String)>, sqlx::Error> = sqlx::query_as(
+        "SELECT id, owner FROM live_streams WHERE id = ANY($1)"
+    )
+        .bind(ids)
+        .fetch_all(pool)
+        .await;
+
+    let ret = match res {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to get live stream owners from database.");
+            debug!("{}", err);
+
+            return Err(AppError::Database(err));
+        }
+    };
+
+    Ok(ret)
+}
+
+pub async fn get_group_ids(
+    owner: &String,
+    tag: &String,
+    pool: &Pool<Postgres>
+) -> Result<Vec<i64>, AppError> {
+    let res: Result<Vec<i64>, sqlx::Error> = sqlx::query_scalar(
+        "SELECT id FROM live_streams WHERE owner = $1 AND $2 = ANY(tags) ORDER BY id ASC"
+    )
+        .bind(owner)
+        .bind(tag)
+        .fetch_all(pool)
+        .await;
+
+    let ret = match res {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to get live stream group from database.");
+            debug!("{}", err);
+
+            return Err(AppError::Database(err));
+        }
+    };
+
+    Ok(ret)
+}
+
+pub async fn get_groups(
+    owner: &String,
+    pool: &Pool<Postgres>
+) -> Result<Vec<StreamGroup>, AppError> {
+    let res: Result<Vec<(String, i64)>, sqlx::Error> = sqlx::query_as(
+        r#"
+        SELECT tag, COUNT(*) AS live_streams
+        FROM live_streams, UNNEST(tags) AS tag
+        WHERE owner = $1
+        GROUP BY tag
+        ORDER BY tag ASC
+        "#
+    )
+        .bind(owner)
+        .fetch_all(pool)
+        .await;
+
+    let rows = match res {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to get live stream groups from database.");
+            debug!("{}", err);
+
+            return Err(AppError::Database(err));
+        }
+    };
+
+    Ok(rows.into_iter().map(|(tag, live_streams)| StreamGroup { tag, live_streams }).collect())
+}
+
+pub async fn get_schedule_candidate(
+    id: i64,
+    pool: &Pool<Postgres>
+) -> Result<Option<ScheduleCandidate>, AppError> {
+    let res = sqlx::query_as::<_, ScheduleCandidate>(
+        r#"
+        SELECT id, owner, mode, video,
+            ARRAY(
+                SELECT jsonb_array_elements_text(jsonb_build_array(rtmp_url) || COALESCE(destination->'backup_urls', '[]'::JSONB))
+            ) AS endpoints,
+            stream_key, schedule_start, schedule_end, dry_run
+        FROM live_streams
+        WHERE id = $1
+        "#
+    )
+        .bind(id)
+        .fetch_optional(pool)
+        .await;
+
+    let ret = match res {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to get live stream schedule from database.");
+            debug!("{}", err);
+
+            return Err(AppError::Database(err));
+        }
+    };
+
+    Ok(ret)
+}
+
+pub async fn update_schedule(
+    id: i64,
+    schedule_start: Option<i64>,
+    schedule_end: Option<i64>,
+    pool: &Pool<Postgres>
+) -> Result<bool, AppError> {
+    let res = sqlx::query(
+        "UPDATE live_streams SET schedule_start = $1, schedule_end = $2 WHERE id = $3"
+    )
+        .bind(schedule_start)
+        .bind(schedule_end)
+        .bind(id)
+        .execute(pool)
+        .await;
+
+    let result = match res {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to update live stream schedule.");
+            debug!("{}", err);
+
+            return Err(AppError::Database(err));
+        }
+    };
+
+    Ok(result.rows_affected() > 0)
+}
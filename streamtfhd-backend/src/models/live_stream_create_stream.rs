@@ -1,18 +1,36 @@
-use sqlx::{Pool, Postgres};
+use sqlx::{Pool, Postgres, types::Json};
 use tracing::{error, debug};
 
 use crate::{
     dto::live_stream_create_stream::CreateLiveStreamData,
+    dto::live_stream_source::LiveStreamSource,
+    dto::live_stream_destination::Destination,
     errors::AppError,
+    models::live_stream_audio_playlist::replace_playlist,
+    utils::live_stream_ingest::generate_ingest_key,
     utils::time::current_unix_timestamp
 };
 
 pub async fn create_live_stream(
     owner: &String,
     data: &CreateLiveStreamData,
+    source: &LiveStreamSource,
+    destination: &Destination,
+    ingest_port: Option<i32>,
+    tags: &[String],
     pool: &Pool<Postgres>,
 ) -> Result<i64, AppError> {
     let timestamp = current_unix_timestamp();
+    let (rtmp_url, stream_key) = destination.address();
+    let mut tx = match pool.begin().await {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to begin live stream transaction.");
+            debug!("{}", err);
+
+            return Err(AppError::Database(err));
+        }
+    };
     let insert: Result<i64, sqlx::Error> = sqlx::query_scalar(
         "INSERT INTO live_streams (
                 owner,
@@ -23,33 +41,75 @@ pub async fn create_live_stream(
                 stream_loop,
                 schedule_start,
                 schedule_end,
-                created_at
+                created_at,
+                slate_video,
+                mode,
+                background_image,
+                slate_image,
+                slate_audio,
+                overlays,
+                trim_start,
+                trim_end,
+                resume,
+                record,
+                destination,
+                ingest_protocol,
+                ingest_port,
+                ingest_key,
+                preview,
+                dry_run,
+                tags
             ) VALUES (
-                $1, $2, $3, $4, $5, $6, $7, $8, $9
+                $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24, $25, $26
             )
             RETURNING id"
     )
         .bind(owner)
         .bind(&data.title)
-        .bind(data.video)
-        .bind(&data.rtmp_url)
-        .bind(&data.stream_key)
+        .bind(source.video)
+        .bind(&rtmp_url)
+        .bind(&stream_key)
         .bind(data.stream_loop)
         .bind(data.schedule_start)
         .bind(data.schedule_end)
         .bind(timestamp as i64)
-        .fetch_one(pool)
+        .bind(source.slate_video)
+        .bind(&source.mode)
+        .bind(source.background_image)
+        .bind(source.slate_image)
+        .bind(source.slate_audio)
+        .bind(Json(data.overlays.clone().unwrap_or_default()))
+        .bind(source.trim_start)
+        .bind(source.trim_end)
+        .bind(data.resume.unwrap_or(false))
+        .bind(data.record.unwrap_or(false))
+        .bind(Json(destination))
+        .bind(&source.ingest_protocol)
+        .bind(ingest_port)
+        .bind(source.ingest_protocol.as_ref().map(|_| generate_ingest_key()))
+        .bind(data.preview.unwrap_or(false))
+        .bind(data.dry_run.unwrap_or(false))
+        .bind(tags)
+        .fetch_one(&mut *tx)
         .await;
-
-    match insert {
-        Ok(val) => {
-            return Ok(val)
-        }
+    let id = match insert {
+        Ok(val) => val,
         Err(err) => {
             error!("Failed to insert live stream data to database.");
             debug!("{}", err);
 
             return Err(AppError::Database(err));
         }
+    };
+
+    replace_playlist(id, &source.audios, &mut tx).await?;
+
+    if let Err(err) = tx.commit().await {
+        error!("Failed to commit live stream data.");
+        debug!("{}", err);
+
+        return Err(AppError::Database(err));
     }
+
+    Ok(id)
 }
\ No newline at end of file
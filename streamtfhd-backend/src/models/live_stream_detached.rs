@@ -0,0 +1,83 @@
+use sqlx::{Pool, Postgres};
+use tracing::{error, debug};
+
+use crate::dto::live_stream_detached::DetachedStream;
+
+pub async fn insert_detached(
+    data: &DetachedStream,
+    pool: &Pool<Postgres>
+) -> bool {
+    let insert = sqlx::query(
+        "INSERT INTO live_stream_detached (
+                    live_stream,
+                    owner,
+                    pid,
+                    actual_start,
+                    schedule_end,
+                    bit_rate,
+                    detached_at,
+                    dry_run
+                ) VALUES (
+                    $1, $2, $3, $4, $5, $6, $7, $8
+                )
+                ON CONFLICT (live_stream) DO UPDATE
+                SET
+                    owner = EXCLUDED.owner,
+                    pid = EXCLUDED.pid,
+                    actual_start = EXCLUDED.actual_start,
+                    schedule_end = EXCLUDED.schedule_end,
+                    bit_rate = EXCLUDED.bit_rate,
+                    detached_at = EXCLUDED.detached_at,
+                    dry_run = EXCLUDED.dry_run"
+    )
+        .bind(data.live_stream)
+        .bind(&data.owner)
+        .bind(data.pid)
+        .bind(data.actual_start)
+        .bind(data.schedule_end)
+        .bind(data.bit_rate)
+        .bind(data.detached_at)
+        .bind(data.dry_run)
+        .execute(pool)
+        .await;
+
+    match insert {
+        Ok(_) => true,
+        Err(err) => {
+            error!("Failed to store detached live stream.");
+            debug!("{}", err);
+
+            false
+        }
+    }
+}
+
+// Returns and removes every detached live stream.
+pub async fn take_detached(
+    pool: &Pool<Postgres>
+) -> Vec<DetachedStream> {
+    let res = sqlx::query_as::<_, DetachedStream>(
+        "DELETE FROM live_stream_detached
+                RETURNING
+                    live_stream,
+                    owner,
+                    pid,
+                    actual_start,
+                    schedule_end,
+                    bit_rate,
+                    detached_at,
+                    dry_run"
+    )
+        .fetch_all(pool)
+        .await;
+
+    match res {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to get detached live streams.");
+            debug!("{}", err);
+
+            Vec::new()
+        }
+    }
+}
@@ -1,21 +1,45 @@
-use sqlx::{Pool, Postgres, prelude::FromRow};
+use sqlx::{Pool, Postgres, prelude::FromRow, types::Json};
 use tracing::{error, debug};
 
 use crate::{
     errors::AppError,
-    dto::live_stream_edit_stream_get
+    dto::live_stream_edit_stream_get,
+    dto::live_stream_overlay::Overlay,
+    dto::live_stream_destination::Destination,
+    dto::live_stream_test_destination::DestinationTest,
+    models::live_stream_audio_playlist::get_playlist,
+    utils::live_stream_ingest::{IngestConfig, ingest_address}
 };
 
 #[derive(Debug, FromRow)]
 struct LiveStream {
     id: i64,
     title: String,
-    video: i64,
+    mode: String,
+    video: Option<i64>,
+    background_image: Option<i64>,
     rtmp_url: String,
     stream_key: String,
     stream_loop: i32,
     schedule_start: Option<i64>,
-    schedule_end: Option<i64>
+    schedule_end: Option<i64>,
+    slate_video: Option<i64>,
+    slate_image: Option<i64>,
+    slate_audio: Option<i64>,
+    overlays: Json<Vec<Overlay>>,
+    trim_start: Option<i64>,
+    trim_end: Option<i64>,
+    resume: bool,
+    resume_position: Option<i64>,
+    record: bool,
+    preview: bool,
+    dry_run: bool,
+    destination: Json<Destination>,
+    ingest_protocol: Option<String>,
+    ingest_port: Option<i32>,
+    ingest_key: Option<String>,
+    destination_test: Option<Json<DestinationTest>>,
+    tags: Vec<String>
 }
 
 pub async fn get_live_stream_owner(
@@ -48,7 +72,7 @@ async fn get_live_stream_data(
 ) -> Result<Option<LiveStream>, AppError> {
     let live_stream_result = sqlx::query_as::<_, LiveStream>(
         r#"
-        SELECT id, title, video, rtmp_url, stream_key, stream_loop, schedule_start, schedule_end
+        SELECT id, title, mode, video, background_image, rtmp_url, stream_key, stream_loop, schedule_start, schedule_end, slate_video, slate_image, slate_audio, overlays, trim_start, trim_end, resume, resume_position, record, preview, dry_run, destination, ingest_protocol, ingest_port, ingest_key, destination_test, tags
         FROM live_streams
         WHERE id = $1
         "#
@@ -98,27 +122,125 @@ async fn get_video_data(
     Ok(video_option)
 }
 
+async fn get_image_data(
+    id: i64,
+    pool: &Pool<Postgres>
+) -> Result<Option<live_stream_edit_stream_get::Image>, AppError> {
+    let image_result = sqlx::query_as::<_, live_stream_edit_stream_get::Image>(
+        r#"
+        SELECT id, title, file
+        FROM images
+        WHERE id = $1
+        "#
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await;
+
+    let image_option = match image_result {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to get image data from database.");
+            debug!("{}", err);
+
+            return Err(AppError::Database(err));
+        }
+    };
+
+    Ok(image_option)
+}
+
+async fn get_audio_data(
+    id: i64,
+    pool: &Pool<Postgres>
+) -> Result<Option<live_stream_edit_stream_get::Audio>, AppError> {
+    let audio_result = sqlx::query_as::<_, live_stream_edit_stream_get::Audio>(
+        r#"
+        SELECT id, title, file, length
+        FROM audios
+        WHERE id = $1
+        "#
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await;
+
+    let audio_option = match audio_result {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to get audio data from database.");
+            debug!("{}", err);
+
+            return Err(AppError::Database(err));
+        }
+    };
+
+    Ok(audio_option)
+}
+
 pub async fn get_live_stream(
     id: i64,
     pool: &Pool<Postgres>
 ) -> Result<Option<live_stream_edit_stream_get::LiveStream>, AppError> {
-    let live_stream_option = get_live_stream_data(id, &pool).await?;
+    let live_stream_option = get_live_stream_data(id, pool).await?;
     let live_stream = match live_stream_option {
         Some(val) => val,
         None => {
             return Ok(None);
         }
     };
-    let video_option = get_video_data(live_stream.video, &pool).await?;
+    let video_option = match live_stream.video {
+        Some(val) => get_video_data(val, pool).await?,
+        None => None
+    };
+    let background_image_option = match live_stream.background_image {
+        Some(val) => get_image_data(val, pool).await?,
+        None => None
+    };
+    let audios = get_playlist(live_stream.id, pool).await?;
+    let slate_video_option = match live_stream.slate_video {
+        Some(val) => get_video_data(val, pool).await?,
+        None => None
+    };
+    let slate_image_option = match live_stream.slate_image {
+        Some(val) => get_image_data(val, pool).await?,
+        None => None
+    };
+    let slate_audio_option = match live_stream.slate_audio {
+        Some(val) => get_audio_data(val, pool).await?,
+        None => None
+    };
+    let ingest = match (&live_stream.ingest_protocol, live_stream.ingest_port, &live_stream.ingest_key) {
+        (Some(protocol), Some(port), Some(key)) => Some(ingest_address(protocol, &IngestConfig::from_env().public_host, port, key)),
+        _ => None
+    };
     let ret = live_stream_edit_stream_get::LiveStream {
         id: live_stream.id,
         title: live_stream.title,
+        mode: live_stream.mode,
         video: video_option,
+        background_image: background_image_option,
+        audios,
         rtmp_url: live_stream.rtmp_url,
         stream_key: live_stream.stream_key,
         stream_loop: live_stream.stream_loop,
         schedule_start: live_stream.schedule_start,
-        schedule_end: live_stream.schedule_end
+        schedule_end: live_stream.schedule_end,
+        slate_video: slate_video_option,
+        slate_image: slate_image_option,
+        slate_audio: slate_audio_option,
+        overlays: live_stream.overlays.0,
+        trim_start: live_stream.trim_start,
+        trim_end: live_stream.trim_end,
+        resume: live_stream.resume,
+        resume_position: live_stream.resume_position,
+        record: live_stream.record,
+        preview: live_stream.preview,
+        dry_run: live_stream.dry_run,
+        destination: live_stream.destination.0,
+        ingest,
+        destination_test: live_stream.destination_test.map(|val| val.0),
+        tags: live_stream.tags
     };
 
     Ok(Some(ret))
@@ -1,9 +1,13 @@
-use sqlx::{Pool, Postgres};
+use sqlx::{Pool, Postgres, types::Json};
 use tracing::{error, debug};
 
 use crate::{
     errors::AppError,
-    dto::live_stream_edit_stream_post::LiveStream
+    dto::live_stream_edit_stream_post::{LiveStream, StreamSettings},
+    dto::live_stream_source::LiveStreamSource,
+    dto::live_stream_destination::Destination,
+    models::live_stream_audio_playlist::replace_playlist,
+    utils::live_stream_ingest::generate_ingest_key
 };
 
 pub async fn get_live_stream_owner(
@@ -30,32 +34,138 @@ pub async fn get_live_stream_owner(
     Ok(ret)
 }
 
+pub async fn get_stream_settings(
+    id: i64,
+    pool: &Pool<Postgres>
+) -> Result<Option<StreamSettings>, AppError> {
+    let res = sqlx::query_as::<_, StreamSettings>(
+        r#"
+        SELECT
+                mode,
+                video,
+                background_image,
+                ARRAY(
+                    SELECT live_stream_audios.audio
+                    FROM live_stream_audios
+                    WHERE live_stream_audios.live_stream = live_streams.id
+                    ORDER BY live_stream_audios.position ASC
+                ) AS audios,
+                slate_video,
+                slate_image,
+                slate_audio,
+                stream_loop,
+                trim_start,
+                trim_end,
+                record,
+                preview,
+                dry_run,
+                destination,
+                ingest_protocol
+        FROM live_streams
+        WHERE id = $1
+        "#
+    )
+        .bind(id)
+        .fetch_optional(pool)
+        .await;
+
+    let ret = match res {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to get live stream settings from database.");
+            debug!("{}", err);
+
+            return Err(AppError::Database(err));
+        }
+    };
+
+    Ok(ret)
+}
+
 pub async fn update_live_stream_data(
     data: &LiveStream,
+    source: &LiveStreamSource,
+    destination: &Destination,
+    ingest_port: Option<i32>,
+    tags: Option<&[String]>,
     pool: &Pool<Postgres>
 ) -> Result<bool, AppError> {
+    let (rtmp_url, stream_key) = destination.address();
+    let mut tx = match pool.begin().await {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to begin live stream transaction.");
+            debug!("{}", err);
+
+            return Err(AppError::Database(err));
+        }
+    };
     let res = sqlx::query(
         r#"
         UPDATE live_streams
         SET title = $1,
+            -- The position of the previous run belongs to the old video.
+            resume_position = CASE WHEN video IS DISTINCT FROM $2 THEN NULL ELSE resume_position END,
             video = $2,
             rtmp_url = $3,
             stream_key = $4,
             stream_loop = $5,
             schedule_start = $6,
-            schedule_end = $7
+            schedule_end = $7,
+            slate_video = $9,
+            mode = $10,
+            background_image = $11,
+            slate_image = $12,
+            slate_audio = $13,
+            overlays = COALESCE($14, overlays),
+            trim_start = $15,
+            trim_end = $16,
+            resume = $17,
+            record = $18,
+            preview = $24,
+            dry_run = $25,
+            tags = COALESCE($26, tags),
+            -- The last test result belongs to the old destination.
+            destination_test = CASE WHEN destination IS DISTINCT FROM $19 THEN NULL ELSE destination_test END,
+            destination = $19,
+            ingest_protocol = $20,
+            ingest_port = $21,
+            -- Leaving ingest mode drops the key, a new one is only made on request.
+            ingest_key = CASE
+                WHEN $20::TEXT IS NULL THEN NULL
+                WHEN $23 THEN $22
+                ELSE COALESCE(ingest_key, $22)
+            END
         WHERE id = $8
         "#
     )
         .bind(&data.title)
-        .bind(data.video)
-        .bind(&data.rtmp_url)
-        .bind(&data.stream_key)
+        .bind(source.video)
+        .bind(&rtmp_url)
+        .bind(&stream_key)
         .bind(data.stream_loop)
         .bind(data.schedule_start)
         .bind(data.schedule_end)
         .bind(data.id)
-        .execute(pool)
+        .bind(source.slate_video)
+        .bind(&source.mode)
+        .bind(source.background_image)
+        .bind(source.slate_image)
+        .bind(source.slate_audio)
+        .bind(data.overlays.clone().map(Json))
+        .bind(source.trim_start)
+        .bind(source.trim_end)
+        .bind(data.resume.unwrap_or(false))
+        .bind(data.record.unwrap_or(false))
+        .bind(Json(destination))
+        .bind(&source.ingest_protocol)
+        .bind(ingest_port)
+        .bind(generate_ingest_key())
+        .bind(data.reset_ingest_key.unwrap_or(false))
+        .bind(data.preview.unwrap_or(false))
+        .bind(data.dry_run.unwrap_or(false))
+        .bind(tags)
+        .execute(&mut *tx)
         .await;
     let result = match res {
         Ok(val) => val,
@@ -67,5 +177,18 @@ pub async fn update_live_stream_data(
         }
     };
 
-    Ok(result.rows_affected() > 0)
+    if result.rows_affected() == 0 {
+        return Ok(false);
+    }
+
+    replace_playlist(data.id, &source.audios, &mut tx).await?;
+
+    if let Err(err) = tx.commit().await {
+        error!("Failed to commit live stream data.");
+        debug!("{}", err);
+
+        return Err(AppError::Database(err));
+    }
+
+    Ok(true)
 }
\ No newline at end of file
@@ -18,14 +18,16 @@ pub async  fn get_live_streams_new(
                 live_streams.schedule_start AS live_stream_schedule_start,
                 live_streams.schedule_end AS live_stream_schedule_end,
                 live_streams.started_at AS live_stream_started_at,
+                live_streams.mode AS live_stream_mode,
+                live_streams.tags AS live_stream_tags,
                 videos.id AS video_id,
-                videos.thumbnail AS video_thumbnail,
-                videos.width AS video_width,
-                videos.height AS video_height,
-                videos.bit_rate AS video_bit_rate,
-                videos.frame_rate AS video_frame_rate
+                COALESCE(videos.thumbnail, '') AS video_thumbnail,
+                COALESCE(videos.width, 0) AS video_width,
+                COALESCE(videos.height, 0) AS video_height,
+                COALESCE(videos.bit_rate, 0) AS video_bit_rate,
+                COALESCE(videos.frame_rate, 0) AS video_frame_rate
         FROM live_streams
-        INNER JOIN videos
+        LEFT JOIN videos
             ON live_streams.video = videos.id
         WHERE live_streams.owner = $1
         ORDER BY live_streams.id DESC
@@ -0,0 +1,56 @@
+use sqlx::{Pool, Postgres};
+use tracing::{error, debug};
+
+use crate::{
+    errors::AppError,
+    utils::live_stream_ingest::IngestConfig
+};
+
+// Ingest port of a live stream, the one it already has when it is still in
+// range, or else the lowest one no other live stream listens on.
+pub async fn get_free_ingest_port(
+    id: Option<i64>,
+    config: &IngestConfig,
+    pool: &Pool<Postgres>
+) -> Result<Option<i32>, AppError> {
+    let res: Result<Option<i32>, sqlx::Error> = sqlx::query_scalar(
+        r#"
+        SELECT COALESCE(
+            (
+                SELECT ingest_port
+                FROM live_streams
+                WHERE id = $1
+                    AND ingest_port BETWEEN $2 AND $3
+            ),
+            (
+                SELECT port
+                FROM generate_series($2::INTEGER, $3::INTEGER) AS port
+                WHERE NOT EXISTS (
+                    SELECT 1
+                    FROM live_streams
+                    WHERE live_streams.ingest_port = port
+                )
+                ORDER BY port ASC
+                LIMIT 1
+            )
+        )
+        "#
+    )
+        .bind(id)
+        .bind(config.port_start)
+        .bind(config.port_end)
+        .fetch_one(pool)
+        .await;
+
+    let port = match res {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to get a free ingest port.");
+            debug!("{}", err);
+
+            return Err(AppError::Database(err));
+        }
+    };
+
+    Ok(port)
+}
@@ -0,0 +1,27 @@
+use sqlx::{Pool, Postgres};
+use tracing::{error, debug};
+
+use crate::errors::AppError;
+
+// (id, file) of the given images, used as watermarks.
+pub async fn get_image_files(
+    ids: &[i64],
+    pool: &Pool<Postgres>
+) -> Result<Vec<(i64, String)>, AppError> {
+    let res = sqlx::query_as::<_, (i64, String)>(
+        "SELECT id, file FROM images WHERE id = ANY($1)"
+    )
+        .bind(ids)
+        .fetch_all(pool)
+        .await;
+
+    match res {
+        Ok(val) => Ok(val),
+        Err(err) => {
+            error!("Failed to get watermark image files.");
+            debug!("{}", err);
+
+            Err(AppError::Database(err))
+        }
+    }
+}
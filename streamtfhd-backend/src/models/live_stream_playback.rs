@@ -0,0 +1,54 @@
+use sqlx::{Pool, Postgres};
+use tracing::{error, debug};
+
+use crate::errors::AppError;
+
+// Length of a video in seconds, trim points must fall inside it.
+pub async fn get_video_length(
+    video_id: i64,
+    pool: &Pool<Postgres>
+) -> Result<Option<i32>, AppError> {
+    let res: Result<Option<i32>, sqlx::Error> = sqlx::query_scalar(
+        "SELECT length FROM videos WHERE id = $1"
+    )
+        .bind(video_id)
+        .fetch_optional(pool)
+        .await;
+
+    match res {
+        Ok(val) => Ok(val),
+        Err(err) => {
+            error!("Failed to get video length from database.");
+            debug!("{}", err);
+
+            Err(AppError::Database(err))
+        }
+    }
+}
+
+// None once the video has played to its end, the next run starts over.
+pub async fn save_resume_position(
+    stream_id: i64,
+    position_ms: Option<i64>,
+    pool: &Pool<Postgres>
+) -> bool {
+    let update = sqlx::query(
+        "UPDATE live_streams
+                SET resume_position = $1
+                WHERE id = $2"
+    )
+        .bind(position_ms)
+        .bind(stream_id)
+        .execute(pool)
+        .await;
+
+    match update {
+        Ok(_) => true,
+        Err(err) => {
+            error!("Failed to update resume position data.");
+            debug!("{}", err);
+
+            false
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use sqlx::{Pool, Postgres};
+use tracing::{error, debug};
+
+// Links a registered recording segment to the history row of its run.
+pub async fn link_recording(
+    history: i64,
+    position: i32,
+    video: i64,
+    pool: &Pool<Postgres>
+) -> bool {
+    let insert = sqlx::query(
+        "INSERT INTO live_stream_recordings (
+                    history,
+                    position,
+                    video
+                ) VALUES (
+                    $1, $2, $3
+                )"
+    )
+        .bind(history)
+        .bind(position)
+        .bind(video)
+        .execute(pool)
+        .await;
+
+    match insert {
+        Ok(_) => true,
+        Err(err) => {
+            error!("Failed to store live stream recording data to the database.");
+            debug!("{}", err);
+
+            false
+        }
+    }
+}
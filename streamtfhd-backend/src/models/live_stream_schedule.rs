@@ -0,0 +1,250 @@
+use sqlx::{Pool, Postgres};
+use tracing::{error, debug};
+
+use crate::{
+    dto::live_stream_schedule::{CalendarEntry, ScheduleWindow},
+    dto::live_stream_source::{MODE_IMAGE_AUDIO, MODE_INGEST},
+    errors::AppError,
+    utils::live_stream_pipeline::{IMAGE_AUDIO_BIT_RATE, INGEST_BIT_RATE}
+};
+
+// Scheduled windows of other live streams that overlap [start, end).
+// An empty end means the window has no end.
+pub async fn get_overlapping_windows(
+    exclude_id: Option<i64>,
+    start: i64,
+    end: Option<i64>,
+    pool: &Pool<Postgres>
+) -> Result<Vec<ScheduleWindow>, AppError> {
+    let result = sqlx::query_as::<_, ScheduleWindow>(
+        r#"
+        SELECT
+                live_streams.id,
+                live_streams.owner,
+                live_streams.title,
+                live_streams.schedule_start,
+                live_streams.schedule_end,
+                CASE
+                    WHEN live_streams.dry_run THEN 0
+                    WHEN live_streams.mode = $4 THEN $5
+                    WHEN live_streams.mode = $6 THEN $7
+                    ELSE COALESCE(videos.bit_rate, 0)
+                END AS bit_rate
+        FROM live_streams
+        LEFT JOIN videos
+            ON live_streams.video = videos.id
+        WHERE live_streams.schedule_start IS NOT NULL
+            AND ($1::BIGINT IS NULL OR live_streams.id <> $1)
+            AND ($3::BIGINT IS NULL OR live_streams.schedule_start < $3)
+            AND (live_streams.schedule_end IS NULL OR live_streams.schedule_end > $2)
+        ORDER BY live_streams.schedule_start ASC
+        "#
+    )
+        .bind(exclude_id)
+        .bind(start)
+        .bind(end)
+        .bind(MODE_IMAGE_AUDIO)
+        .bind(IMAGE_AUDIO_BIT_RATE as i32)
+        .bind(MODE_INGEST)
+        .bind(INGEST_BIT_RATE as i32)
+        .fetch_all(pool)
+        .await;
+
+    let windows = match result {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to get overlapping live stream schedules.");
+            debug!("{}", err);
+
+            return Err(AppError::Database(err));
+        }
+    };
+
+    Ok(windows)
+}
+
+// Ids of the given windows that stream to the same destination, dry runs
+// never reach it. Backup URLs take the stream key of the primary one, so any
+// shared endpoint with the same key is the same destination.
+pub async fn get_same_destination_ids(
+    ids: &[i64],
+    endpoints: &[String],
+    stream_key: &String,
+    pool: &Pool<Postgres>
+) -> Result<Vec<i64>, AppError> {
+    let result: Result<Vec<i64>, sqlx::Error> = sqlx::query_scalar(
+        r#"
+        SELECT id
+        FROM live_streams
+        WHERE id = ANY($1)
+            AND stream_key = $3
+            AND NOT dry_run
+            AND EXISTS (
+                SELECT 1
+                FROM jsonb_array_elements_text(jsonb_build_array(rtmp_url) || COALESCE(destination->'backup_urls', '[]'::JSONB)) AS endpoint
+                WHERE RTRIM(endpoint, '/') IN (SELECT RTRIM(candidate, '/') FROM UNNEST($2::TEXT[]) AS candidate)
+            )
+        "#
+    )
+        .bind(ids)
+        .bind(endpoints)
+        .bind(stream_key)
+        .fetch_all(pool)
+        .await;
+
+    let same_destination = match result {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to get live streams with the same destination.");
+            debug!("{}", err);
+
+            return Err(AppError::Database(err));
+        }
+    };
+
+    Ok(same_destination)
+}
+
+pub async fn get_video_bit_rate(
+    video_id: i64,
+    pool: &Pool<Postgres>
+) -> Result<Option<i32>, AppError> {
+    let res: Result<Option<i32>, sqlx::Error> = sqlx::query_scalar(
+        "SELECT bit_rate FROM videos WHERE id = $1"
+    )
+        .bind(video_id)
+        .fetch_optional(pool)
+        .await;
+
+    let bit_rate = match res {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to get video bit rate.");
+            debug!("{}", err);
+
+            return Err(AppError::Database(err));
+        }
+    };
+
+    Ok(bit_rate)
+}
+
+pub async fn get_calendar(
+    owner: &String,
+    from: i64,
+    to: i64,
+    pool: &Pool<Postgres>
+) -> Result<Vec<CalendarEntry>, AppError> {
+    let result = sqlx::query_as::<_, CalendarEntry>(
+        r#"
+        SELECT
+                live_streams.id AS live_stream_id,
+                live_streams.title AS live_stream_title,
+                live_streams.schedule_start,
+                live_streams.schedule_end,
+                COALESCE(videos.title, images.title, '') AS video_title,
+                COALESCE(videos.thumbnail, '') AS video_thumbnail
+        FROM live_streams
+        LEFT JOIN videos
+            ON live_streams.video = videos.id
+        LEFT JOIN images
+            ON live_streams.background_image = images.id
+        WHERE live_streams.owner = $1
+            AND live_streams.schedule_start IS NOT NULL
+            AND live_streams.schedule_start < $3
+            AND (live_streams.schedule_end IS NULL OR live_streams.schedule_end > $2)
+        ORDER BY live_streams.schedule_start ASC
+        "#
+    )
+        .bind(owner)
+        .bind(from)
+        .bind(to)
+        .fetch_all(pool)
+        .await;
+
+    let entries = match result {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to get live stream calendar.");
+            debug!("{}", err);
+
+            return Err(AppError::Database(err));
+        }
+    };
+
+    Ok(entries)
+}
+
+pub async fn get_calendar_token(
+    user_id: &String,
+    pool: &Pool<Postgres>
+) -> Result<Option<String>, AppError> {
+    let res: Result<Option<String>, sqlx::Error> = sqlx::query_scalar(
+        "SELECT calendar_token FROM users WHERE id = $1"
+    )
+        .bind(user_id)
+        .fetch_one(pool)
+        .await;
+
+    let token = match res {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to get calendar token.");
+            debug!("{}", err);
+
+            return Err(AppError::Database(err));
+        }
+    };
+
+    Ok(token)
+}
+
+pub async fn set_calendar_token(
+    user_id: &String,
+    token: &String,
+    pool: &Pool<Postgres>
+) -> Result<bool, AppError> {
+    let res = sqlx::query(
+        "UPDATE users SET calendar_token = $1 WHERE id = $2"
+    )
+        .bind(token)
+        .bind(user_id)
+        .execute(pool)
+        .await;
+
+    let result = match res {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to store calendar token.");
+            debug!("{}", err);
+
+            return Err(AppError::Database(err));
+        }
+    };
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn get_user_id_from_calendar_token(
+    token: &String,
+    pool: &Pool<Postgres>
+) -> Result<Option<String>, AppError> {
+    let res: Result<Option<String>, sqlx::Error> = sqlx::query_scalar(
+        "SELECT id FROM users WHERE calendar_token = $1"
+    )
+        .bind(token)
+        .fetch_optional(pool)
+        .await;
+
+    let user_id = match res {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to get user id from calendar token.");
+            debug!("{}", err);
+
+            return Err(AppError::Database(err));
+        }
+    };
+
+    Ok(user_id)
+}
@@ -15,15 +15,52 @@ pub async fn get_live_stream_data(
         SELECT
                 live_streams.id,
                 live_streams.owner,
+                live_streams.title,
+                live_streams.mode,
                 videos.file as video_file,
-                live_streams.rtmp_url,
-                live_streams.stream_key,
+                COALESCE(videos.bit_rate, 0) as video_bit_rate,
+                COALESCE(videos.width, 0) as video_width,
+                COALESCE(videos.height, 0) as video_height,
+                COALESCE(videos.frame_rate, 0) as video_frame_rate,
+                COALESCE(videos.length, 0) as video_length,
+                background_images.file as background_image_file,
+                ARRAY(
+                    SELECT audios.file
+                    FROM live_stream_audios
+                    INNER JOIN audios
+                        ON live_stream_audios.audio = audios.id
+                    WHERE live_stream_audios.live_stream = live_streams.id
+                    ORDER BY live_stream_audios.position ASC
+                ) as audio_files,
+                slate_videos.file as slate_file,
+                slate_images.file as slate_image_file,
+                slate_audios.file as slate_audio_file,
                 live_streams.stream_loop,
                 live_streams.schedule_start,
-                live_streams.schedule_end
+                live_streams.schedule_end,
+                live_streams.overlays,
+                live_streams.trim_start,
+                live_streams.trim_end,
+                live_streams.resume,
+                live_streams.resume_position,
+                live_streams.record,
+                live_streams.preview,
+                live_streams.dry_run,
+                live_streams.destination,
+                live_streams.ingest_protocol,
+                live_streams.ingest_port,
+                live_streams.ingest_key
         FROM live_streams
-        INNER JOIN videos
+        LEFT JOIN videos
             ON live_streams.video = videos.id
+        LEFT JOIN images background_images
+            ON live_streams.background_image = background_images.id
+        LEFT JOIN videos slate_videos
+            ON live_streams.slate_video = slate_videos.id
+        LEFT JOIN images slate_images
+            ON live_streams.slate_image = slate_images.id
+        LEFT JOIN audios slate_audios
+            ON live_streams.slate_audio = slate_audios.id
         WHERE live_streams.id = $1
         "#
     )
@@ -0,0 +1,31 @@
+use sqlx::{Pool, Postgres};
+use tracing::{error, debug};
+
+use crate::errors::AppError;
+
+// Files of the given videos owned by `owner`, as (id, file) pairs.
+pub async fn get_video_files(
+    ids: &[i64],
+    owner: &String,
+    pool: &Pool<Postgres>
+) -> Result<Vec<(i64, String)>, AppError> {
+    let res: Result<Vec<(i64, String)>, sqlx::Error> = sqlx::query_as(
+        "SELECT id, file FROM videos WHERE id = ANY($1) AND owner = $2"
+    )
+        .bind(ids)
+        .bind(owner)
+        .fetch_all(pool)
+        .await;
+
+    let files = match res {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to get video files from database.");
+            debug!("{}", err);
+
+            return Err(AppError::Database(err));
+        }
+    };
+
+    Ok(files)
+}
@@ -0,0 +1,247 @@
+use sqlx::{Pool, Postgres, prelude::FromRow, types::Json};
+use tracing::{error, debug};
+
+use crate::{
+    dto::live_stream_destination::Destination,
+    dto::live_stream_overlay::Overlay,
+    dto::live_stream_template::{Template, TemplateSettings},
+    errors::AppError,
+    utils::time::current_unix_timestamp
+};
+
+#[derive(Debug, FromRow)]
+struct LiveStream {
+    owner: String,
+    title: String,
+    mode: String,
+    video: Option<i64>,
+    background_image: Option<i64>,
+    audios: Vec<i64>,
+    stream_loop: i32,
+    schedule_start: Option<i64>,
+    schedule_end: Option<i64>,
+    slate_video: Option<i64>,
+    slate_image: Option<i64>,
+    slate_audio: Option<i64>,
+    overlays: Json<Vec<Overlay>>,
+    trim_start: Option<i64>,
+    trim_end: Option<i64>,
+    resume: bool,
+    record: bool,
+    preview: bool,
+    dry_run: bool,
+    destination: Json<Destination>,
+    ingest_protocol: Option<String>,
+    tags: Vec<String>
+}
+
+pub async fn insert_template(
+    owner: &String,
+    name: &str,
+    settings: &TemplateSettings,
+    pool: &Pool<Postgres>
+) -> Result<i64, AppError> {
+    let timestamp = current_unix_timestamp() as i64;
+    let res: Result<i64, sqlx::Error> = sqlx::query_scalar(
+        r#"
+        INSERT INTO live_stream_templates (owner, name, settings, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $4)
+        RETURNING id
+        "#
+    )
+        .bind(owner)
+        .bind(name)
+        .bind(Json(settings))
+        .bind(timestamp)
+        .fetch_one(pool)
+        .await;
+
+    match res {
+        Ok(val) => Ok(val),
+        Err(err) => {
+            error!("Failed to insert live stream template to database.");
+            debug!("{}", err);
+
+            Err(AppError::Database(err))
+        }
+    }
+}
+
+pub async fn get_templates(
+    owner: &String,
+    pool: &Pool<Postgres>
+) -> Result<Vec<Template>, AppError> {
+    let res = sqlx::query_as::<_, Template>(
+        r#"
+        SELECT id, name, settings, created_at, updated_at
+        FROM live_stream_templates
+        WHERE owner = $1
+        ORDER BY name ASC, id ASC
+        "#
+    )
+        .bind(owner)
+        .fetch_all(pool)
+        .await;
+
+    match res {
+        Ok(val) => Ok(val),
+        Err(err) => {
+            error!("Failed to get live stream templates from database.");
+            debug!("{}", err);
+
+            Err(AppError::Database(err))
+        }
+    }
+}
+
+pub async fn get_template_owner(
+    id: i64,
+    pool: &Pool<Postgres>
+) -> Result<Option<String>, AppError> {
+    let res: Result<Option<String>, sqlx::Error> = sqlx::query_scalar(
+        "SELECT owner FROM live_stream_templates WHERE id = $1"
+    )
+        .bind(id)
+        .fetch_optional(pool)
+        .await;
+
+    match res {
+        Ok(val) => Ok(val),
+        Err(err) => {
+            error!("Failed to get live stream template owner from database.");
+            debug!("{}", err);
+
+            Err(AppError::Database(err))
+        }
+    }
+}
+
+pub async fn get_template(
+    id: i64,
+    pool: &Pool<Postgres>
+) -> Result<Option<Template>, AppError> {
+    let res = sqlx::query_as::<_, Template>(
+        "SELECT id, name, settings, created_at, updated_at FROM live_stream_templates WHERE id = $1"
+    )
+        .bind(id)
+        .fetch_optional(pool)
+        .await;
+
+    match res {
+        Ok(val) => Ok(val),
+        Err(err) => {
+            error!("Failed to get live stream template from database.");
+            debug!("{}", err);
+
+            Err(AppError::Database(err))
+        }
+    }
+}
+
+pub async fn update_template(
+    id: i64,
+    name: &str,
+    settings: &TemplateSettings,
+    pool: &Pool<Postgres>
+) -> Result<bool, AppError> {
+    let res = sqlx::query(
+        "UPDATE live_stream_templates SET name = $1, settings = $2, updated_at = $3 WHERE id = $4"
+    )
+        .bind(name)
+        .bind(Json(settings))
+        .bind(current_unix_timestamp() as i64)
+        .bind(id)
+        .execute(pool)
+        .await;
+
+    match res {
+        Ok(val) => Ok(val.rows_affected() > 0),
+        Err(err) => {
+            error!("Failed to update live stream template.");
+            debug!("{}", err);
+
+            Err(AppError::Database(err))
+        }
+    }
+}
+
+pub async fn delete_template(
+    id: i64,
+    pool: &Pool<Postgres>
+) -> Result<bool, AppError> {
+    let res = sqlx::query("DELETE FROM live_stream_templates WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await;
+
+    match res {
+        Ok(val) => Ok(val.rows_affected() > 0),
+        Err(err) => {
+            error!("Failed to delete live stream template.");
+            debug!("{}", err);
+
+            Err(AppError::Database(err))
+        }
+    }
+}
+
+// The settings of a live stream with its owner, as a template of itself. The
+// duration is the scheduled one, a recurrence is never guessed.
+pub async fn get_live_stream_settings(
+    id: i64,
+    pool: &Pool<Postgres>
+) -> Result<Option<(String, TemplateSettings)>, AppError> {
+    let res = sqlx::query_as::<_, LiveStream>(
+        r#"
+        SELECT owner, title, mode, video, background_image,
+            ARRAY(SELECT audio FROM live_stream_audios WHERE live_stream = live_streams.id ORDER BY position ASC) AS audios,
+            stream_loop, schedule_start, schedule_end, slate_video, slate_image, slate_audio, overlays, trim_start, trim_end,
+            resume, record, preview, dry_run, destination, ingest_protocol, tags
+        FROM live_streams
+        WHERE id = $1
+        "#
+    )
+        .bind(id)
+        .fetch_optional(pool)
+        .await;
+
+    let live_stream = match res {
+        Ok(Some(val)) => val,
+        Ok(None) => return Ok(None),
+        Err(err) => {
+            error!("Failed to get live stream settings from database.");
+            debug!("{}", err);
+
+            return Err(AppError::Database(err));
+        }
+    };
+    let duration = match (live_stream.schedule_start, live_stream.schedule_end) {
+        (Some(start), Some(end)) if end > start => Some(end - start),
+        _ => None
+    };
+    let settings = TemplateSettings {
+        title: Some(live_stream.title),
+        mode: Some(live_stream.mode),
+        video: live_stream.video.map(|video| video as i32),
+        background_image: live_stream.background_image,
+        audios: Some(live_stream.audios),
+        stream_loop: Some(live_stream.stream_loop),
+        slate_video: live_stream.slate_video,
+        slate_image: live_stream.slate_image,
+        slate_audio: live_stream.slate_audio,
+        overlays: Some(live_stream.overlays.0),
+        trim_start: live_stream.trim_start,
+        trim_end: live_stream.trim_end,
+        resume: Some(live_stream.resume),
+        record: Some(live_stream.record),
+        preview: Some(live_stream.preview),
+        dry_run: Some(live_stream.dry_run),
+        destination: Some(live_stream.destination.0),
+        ingest_protocol: live_stream.ingest_protocol,
+        tags: Some(live_stream.tags),
+        recurrence: None,
+        duration
+    };
+
+    Ok(Some((live_stream.owner, settings)))
+}
\ No newline at end of file
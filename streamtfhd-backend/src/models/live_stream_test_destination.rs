@@ -0,0 +1,55 @@
+use sqlx::{Pool, Postgres, types::Json};
+use tracing::{error, debug};
+
+use crate::{
+    dto::live_stream_destination::Destination,
+    dto::live_stream_test_destination::DestinationTest,
+    errors::AppError
+};
+
+pub async fn get_destination(
+    id: i64,
+    pool: &Pool<Postgres>
+) -> Result<Option<Destination>, AppError> {
+    let res: Result<Option<Json<Destination>>, sqlx::Error> = sqlx::query_scalar(
+        "SELECT destination FROM live_streams WHERE id = $1"
+    )
+        .bind(id)
+        .fetch_optional(pool)
+        .await;
+
+    let destination = match res {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to get live stream destination from database.");
+            debug!("{}", err);
+
+            return Err(AppError::Database(err));
+        }
+    };
+
+    Ok(destination.map(|val| val.0))
+}
+
+pub async fn save_destination_test(
+    id: i64,
+    test: &DestinationTest,
+    pool: &Pool<Postgres>
+) -> Result<(), AppError> {
+    let res = sqlx::query(
+        "UPDATE live_streams SET destination_test = $1 WHERE id = $2"
+    )
+        .bind(Json(test))
+        .bind(id)
+        .execute(pool)
+        .await;
+
+    if let Err(err) = res {
+        error!("Failed to save live stream destination test to database.");
+        debug!("{}", err);
+
+        return Err(AppError::Database(err));
+    }
+
+    Ok(())
+}
@@ -27,36 +27,54 @@ pub async fn get_live_stream_owner(
     ret
 }
 
+// Returns the ID of the history row, None when it could not be stored.
 pub async fn write_history(
     data: &History,
     pool: &Pool<Postgres>
-) -> bool {
-    let insert = sqlx::query(
+) -> Option<i64> {
+    let insert: Result<i64, sqlx::Error> = sqlx::query_scalar(
         "INSERT INTO live_stream_history (
                     owner,
                     live_stream,
                     start_time,
                     end_time,
-                    end_status
+                    end_status,
+                    avg_cpu,
+                    peak_cpu,
+                    peak_memory,
+                    net_tx_bytes,
+                    loop_iteration,
+                    loop_position,
+                    dry_run,
+                    events
                 ) VALUES (
-                    $1, $2, $3, $4, $5
-                )"
+                    $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13
+                )
+                RETURNING id"
     )
         .bind(&data.owner)
         .bind(data.live_stream)
         .bind(data.start_time)
         .bind(data.end_time)
         .bind(&data.end_status)
-        .execute(pool)
+        .bind(data.avg_cpu)
+        .bind(data.peak_cpu)
+        .bind(data.peak_memory)
+        .bind(data.net_tx_bytes)
+        .bind(data.loop_iteration)
+        .bind(data.loop_position)
+        .bind(data.dry_run)
+        .bind(&data.events)
+        .fetch_one(pool)
         .await;
 
     match insert {
-        Ok(_) => true,
+        Ok(val) => Some(val),
         Err(err) => {
             error!("Failed to store live stream history data to the database.");
             debug!("{}", err);
 
-            false
+            None
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,194 @@
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+
+use crate::{
+    dto::live_stream_state::{LiveStreamState, STREAM_STATES},
+    dto::metrics_state::{MetricsState, HTTP_LATENCY_BUCKETS},
+    utils::prometheus::{write_header, write_sample}
+};
+
+fn render_host_metrics(
+    out: &mut String,
+    metrics_state: &MetricsState
+) {
+    let host = match metrics_state.host.read() {
+        Ok(val) => val.clone(),
+        Err(_) => None
+    };
+    let host = match host {
+        Some(val) => val,
+        None => return
+    };
+
+    write_header(out, "streamtfhd_host_cpu_usage_percent", "Global CPU usage of the host.", "gauge");
+    write_sample(out, "streamtfhd_host_cpu_usage_percent", &[], host.cpu as f64);
+
+    write_header(out, "streamtfhd_host_memory_used_bytes", "Used memory of the host.", "gauge");
+    write_sample(out, "streamtfhd_host_memory_used_bytes", &[], (host.used_memory * 1024) as f64);
+
+    write_header(out, "streamtfhd_host_memory_total_bytes", "Total memory of the host.", "gauge");
+    write_sample(out, "streamtfhd_host_memory_total_bytes", &[], (host.total_memory * 1024) as f64);
+
+    write_header(out, "streamtfhd_host_disk_used_bytes", "Used disk space of the host.", "gauge");
+    write_sample(out, "streamtfhd_host_disk_used_bytes", &[], (host.disk_used * 1024) as f64);
+
+    // `disk_available` holds the total disk space, see `metrics_collector`.
+    write_header(out, "streamtfhd_host_disk_total_bytes", "Total disk space of the host.", "gauge");
+    write_sample(out, "streamtfhd_host_disk_total_bytes", &[], (host.disk_available * 1024) as f64);
+
+    write_header(out, "streamtfhd_host_network_receive_bytes_per_second", "Network download rate of the host.", "gauge");
+    write_sample(out, "streamtfhd_host_network_receive_bytes_per_second", &[], (host.download_kbps * 1024) as f64);
+
+    write_header(out, "streamtfhd_host_network_transmit_bytes_per_second", "Network upload rate of the host.", "gauge");
+    write_sample(out, "streamtfhd_host_network_transmit_bytes_per_second", &[], (host.upload_kbps * 1024) as f64);
+
+    write_header(out, "streamtfhd_host_network_bytes", "Total bytes received and transmitted by the host.", "gauge");
+    write_sample(out, "streamtfhd_host_network_bytes", &[], (host.bandwidth * 1024) as f64);
+}
+
+fn render_stream_metrics(
+    out: &mut String,
+    live_stream_state: &LiveStreamState
+) {
+    let mut states = String::new();
+    let mut bitrate = String::new();
+    let mut fps = String::new();
+    let mut speed = String::new();
+    let mut drop_frames = String::new();
+    let mut dup_frames = String::new();
+    let mut out_time = String::new();
+    let mut total_size = String::new();
+    let mut restarts = String::new();
+    let mut process_cpu = String::new();
+    let mut process_memory = String::new();
+    let mut counts = [0u64; STREAM_STATES.len()];
+
+    for entry in &live_stream_state.jobs {
+        let job = entry.value();
+        let id = job.id.to_string();
+        let labels = [("stream_id", id.as_str()), ("owner", job.owner.as_str())];
+        let current = job.status.as_str();
+
+        for (i, state) in STREAM_STATES.iter().enumerate() {
+            let is_current = *state == current;
+
+            if is_current {
+                counts[i] += 1;
+            }
+
+            write_sample(
+                &mut states,
+                "streamtfhd_stream_state",
+                &[("stream_id", id.as_str()), ("owner", job.owner.as_str()), ("state", state)],
+                if is_current { 1.0 } else { 0.0 }
+            );
+        }
+
+        write_sample(&mut bitrate, "streamtfhd_stream_ffmpeg_bitrate_kbps", &labels, job.progress.bitrate_kbps);
+        write_sample(&mut fps, "streamtfhd_stream_ffmpeg_fps", &labels, job.progress.fps);
+        write_sample(&mut speed, "streamtfhd_stream_ffmpeg_speed", &labels, job.progress.speed);
+        write_sample(&mut drop_frames, "streamtfhd_stream_ffmpeg_dropped_frames", &labels, job.progress.drop_frames as f64);
+        write_sample(&mut dup_frames, "streamtfhd_stream_ffmpeg_duplicated_frames", &labels, job.progress.dup_frames as f64);
+        write_sample(&mut out_time, "streamtfhd_stream_ffmpeg_out_time_seconds", &labels, job.progress.out_time_ms as f64 / 1_000_000.0);
+        write_sample(&mut total_size, "streamtfhd_stream_ffmpeg_output_bytes", &labels, job.progress.total_size as f64);
+        write_sample(&mut restarts, "streamtfhd_stream_restarts", &labels, job.ffmpeg_restarts as f64);
+        write_sample(&mut process_cpu, "streamtfhd_stream_process_cpu_percent", &labels, job.resources.cpu_percent as f64);
+        write_sample(&mut process_memory, "streamtfhd_stream_process_memory_bytes", &labels, (job.resources.memory_kb * 1024) as f64);
+    }
+
+    write_header(out, "streamtfhd_streams", "Number of live stream jobs by state.", "gauge");
+
+    for (i, state) in STREAM_STATES.iter().enumerate() {
+        write_sample(out, "streamtfhd_streams", &[("state", state)], counts[i] as f64);
+    }
+
+    write_header(out, "streamtfhd_stream_state", "Current state of a live stream job, 1 for the active state.", "gauge");
+    out.push_str(&states);
+
+    write_header(out, "streamtfhd_stream_ffmpeg_bitrate_kbps", "Output bitrate reported by ffmpeg.", "gauge");
+    out.push_str(&bitrate);
+
+    write_header(out, "streamtfhd_stream_ffmpeg_fps", "Output frame rate reported by ffmpeg.", "gauge");
+    out.push_str(&fps);
+
+    write_header(out, "streamtfhd_stream_ffmpeg_speed", "Encoding speed reported by ffmpeg, 1.0 means realtime.", "gauge");
+    out.push_str(&speed);
+
+    write_header(out, "streamtfhd_stream_ffmpeg_dropped_frames", "Frames dropped by ffmpeg in the current run.", "gauge");
+    out.push_str(&drop_frames);
+
+    write_header(out, "streamtfhd_stream_ffmpeg_duplicated_frames", "Frames duplicated by ffmpeg in the current run.", "gauge");
+    out.push_str(&dup_frames);
+
+    write_header(out, "streamtfhd_stream_ffmpeg_out_time_seconds", "Output timestamp reported by ffmpeg.", "gauge");
+    out.push_str(&out_time);
+
+    write_header(out, "streamtfhd_stream_ffmpeg_output_bytes", "Bytes written to the destination in the current run.", "gauge");
+    out.push_str(&total_size);
+
+    write_header(out, "streamtfhd_stream_restarts", "Number of times ffmpeg was restarted for a live stream job.", "gauge");
+    out.push_str(&restarts);
+
+    write_header(out, "streamtfhd_stream_process_cpu_percent", "CPU usage of the ffmpeg process group of a live stream job.", "gauge");
+    out.push_str(&process_cpu);
+
+    write_header(out, "streamtfhd_stream_process_memory_bytes", "Resident memory of the ffmpeg process group of a live stream job.", "gauge");
+    out.push_str(&process_memory);
+}
+
+fn render_http_metrics(
+    out: &mut String,
+    metrics_state: &MetricsState
+) {
+    write_header(out, "streamtfhd_http_request_duration_seconds", "HTTP request latencies.", "histogram");
+
+    for entry in &metrics_state.http_requests {
+        let key = entry.key();
+        let stats = entry.value();
+        let status = key.status.to_string();
+        let labels = [
+            ("method", key.method.as_str()),
+            ("route", key.route.as_str()),
+            ("status", status.as_str())
+        ];
+
+        for (i, bound) in HTTP_LATENCY_BUCKETS.iter().enumerate() {
+            let le = bound.to_string();
+
+            write_sample(
+                out,
+                "streamtfhd_http_request_duration_seconds_bucket",
+                &[labels[0], labels[1], labels[2], ("le", le.as_str())],
+                stats.buckets[i] as f64
+            );
+        }
+
+        write_sample(
+            out,
+            "streamtfhd_http_request_duration_seconds_bucket",
+            &[labels[0], labels[1], labels[2], ("le", "+Inf")],
+            stats.count as f64
+        );
+        write_sample(out, "streamtfhd_http_request_duration_seconds_sum", &labels, stats.sum);
+        write_sample(out, "streamtfhd_http_request_duration_seconds_count", &labels, stats.count as f64);
+    }
+}
+
+pub fn render_metrics(
+    metrics_state: &Arc<MetricsState>,
+    live_stream_state: &Arc<LiveStreamState>
+) -> String {
+    let mut out = String::new();
+
+    render_host_metrics(&mut out, metrics_state);
+    render_stream_metrics(&mut out, live_stream_state);
+    render_http_metrics(&mut out, metrics_state);
+
+    write_header(&mut out, "streamtfhd_ingest_queue_depth", "Gallery uploads and imports currently in progress.", "gauge");
+    write_sample(&mut out, "streamtfhd_ingest_queue_depth", &[], metrics_state.ingest_queue_depth.load(Ordering::Relaxed) as f64);
+
+    write_header(&mut out, "streamtfhd_ffmpeg_restarts_total", "Total number of ffmpeg restarts since the backend started.", "counter");
+    write_sample(&mut out, "streamtfhd_ffmpeg_restarts_total", &[], metrics_state.ffmpeg_restarts_total.load(Ordering::Relaxed) as f64);
+
+    out
+}
@@ -1,23 +1,53 @@
 use sysinfo::{System, Networks, Disks};
 use tokio::sync::broadcast;
 use std::time::Duration;
+use std::sync::Arc;
+use sqlx::{Pool, Postgres};
 
 use serde::Serialize;
 
+use crate::{
+    dto::metrics_state::MetricsState,
+    models::dashboard_metrics_history::{
+        self,
+        RAW_RESOLUTION,
+        MINUTE_RESOLUTION
+    },
+    utils::time::current_unix_timestamp
+};
+
 #[derive(Debug, Clone, Serialize)]
 pub struct Metrics {
-    cpu: f32,               // In percent
-    used_memory: u64,       // In Kilobytes
-    total_memory: u64,      // In Kilobytes
-    bandwidth: u64,         // In Kilobytes
-    disk_used: u64,         // In Kilobytes
-    disk_available: u64,    // In Kilobytes
-    download_kbps: u64,     // In Kilobytes
-    upload_kbps: u64,       // In Kilobytes
+    pub cpu: f32,               // In percent
+    pub used_memory: u64,       // In Kilobytes
+    pub total_memory: u64,      // In Kilobytes
+    pub bandwidth: u64,         // In Kilobytes
+    pub disk_used: u64,         // In Kilobytes
+    pub disk_available: u64,    // In Kilobytes
+    pub download_kbps: u64,     // In Kilobytes
+    pub upload_kbps: u64,       // In Kilobytes
 }
 
-pub async fn metrics_collector(tx: broadcast::Sender<Metrics>) {
+pub async fn metrics_collector(
+    tx: broadcast::Sender<Metrics>,
+    metrics_state: Arc<MetricsState>,
+    pool: Pool<Postgres>
+) {
     let mut sys = System::new_all();
+    let raw_retention = dashboard_metrics_history::raw_retention();
+    let minute_retention = dashboard_metrics_history::minute_retention();
+    let minute = MINUTE_RESOLUTION as i64;
+
+    // Continue rolling up from the last stored minute, so samples collected
+    // right before a restart are not left without a minute row.
+    let mut rollup_from = match dashboard_metrics_history::get_last_sample_time(MINUTE_RESOLUTION, &pool).await {
+        Some(val) => val + minute,
+        None => {
+            let now = current_unix_timestamp() as i64;
+
+            now - now % minute
+        }
+    };
 
     loop {
         sys.refresh_all();
@@ -80,6 +110,23 @@ pub async fn metrics_collector(tx: broadcast::Sender<Metrics>) {
             upload_kbps: upload_bps / 1024,
         };
 
+        if let Ok(mut host) = metrics_state.host.write() {
+            *host = Some(metrics.clone());
+        }
+
+        let now = current_unix_timestamp() as i64;
+        let current_minute = now - now % minute;
+
+        dashboard_metrics_history::insert_sample(&metrics, now, &pool).await;
+
+        if current_minute > rollup_from {
+            dashboard_metrics_history::downsample(rollup_from, current_minute, &pool).await;
+            dashboard_metrics_history::prune(RAW_RESOLUTION, now - raw_retention, &pool).await;
+            dashboard_metrics_history::prune(MINUTE_RESOLUTION, now - minute_retention, &pool).await;
+
+            rollup_from = current_minute;
+        }
+
         let _ = tx.send(metrics);
 
         tokio::time::sleep(Duration::from_secs(4)).await;
@@ -3,4 +3,27 @@ pub mod time;
 pub mod token;
 pub mod user;
 pub mod google_drive_video_downloader;
-pub mod live_stream;
\ No newline at end of file
+pub mod live_stream;
+pub mod prometheus;
+pub mod live_stream_resources;
+pub mod live_stream_admission;
+pub mod live_stream_schedule;
+pub mod icalendar;
+pub mod live_stream_shutdown;
+pub mod live_stream_watchdog;
+pub mod live_stream_pipeline;
+pub mod live_stream_source;
+pub mod live_stream_overlay;
+pub mod live_stream_recording;
+pub mod live_stream_destination;
+pub mod live_stream_ingest;
+pub mod live_stream_test_destination;
+pub mod live_stream_preview;
+pub mod live_stream_dry_run;
+pub mod live_stream_runner;
+pub mod live_stream_event;
+pub mod live_stream_edit;
+pub mod live_stream_group;
+pub mod live_stream_template;
+#[cfg(test)]
+pub mod live_stream_scripted_runner;
\ No newline at end of file
@@ -0,0 +1,119 @@
+use crate::dto::live_stream_schedule::CalendarEntry;
+
+pub const CONTENT_TYPE: &str = "text/calendar; charset=utf-8";
+
+// Longest content line allowed by RFC 5545, in octets, without the line break.
+const MAX_LINE_LENGTH: usize = 75;
+
+fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+// Folds a content line so no line is longer than 75 octets, without
+// splitting a multi byte character.
+fn fold_line(out: &mut String, line: &str) {
+    let mut length = 0;
+
+    for ch in line.chars() {
+        if length + ch.len_utf8() > MAX_LINE_LENGTH {
+            out.push_str("\r\n ");
+            length = 1;
+        }
+
+        out.push(ch);
+        length += ch.len_utf8();
+    }
+
+    out.push_str("\r\n");
+}
+
+// Unix timestamp to the UTC date time form, e.g. 20260216T102045Z.
+pub fn format_utc(timestamp: i64) -> String {
+    let days = timestamp.div_euclid(86400);
+    let seconds = timestamp.rem_euclid(86400);
+
+    // Civil from days, http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+pub fn build_calendar(
+    name: &str,
+    entries: &[CalendarEntry],
+    now: i64
+) -> String {
+    let mut out = String::new();
+
+    fold_line(&mut out, "BEGIN:VCALENDAR");
+    fold_line(&mut out, "VERSION:2.0");
+    fold_line(&mut out, "PRODID:-//StreamTFHD//Live Stream Schedule//EN");
+    fold_line(&mut out, "CALSCALE:GREGORIAN");
+    fold_line(&mut out, &format!("X-WR-CALNAME:{}", escape_text(name)));
+
+    for entry in entries {
+        fold_line(&mut out, "BEGIN:VEVENT");
+        fold_line(&mut out, &format!("UID:live-stream-{}@streamtfhd", entry.live_stream_id));
+        fold_line(&mut out, &format!("DTSTAMP:{}", format_utc(now)));
+        fold_line(&mut out, &format!("DTSTART:{}", format_utc(entry.schedule_start)));
+
+        if let Some(end) = entry.schedule_end {
+            fold_line(&mut out, &format!("DTEND:{}", format_utc(end)));
+        }
+
+        fold_line(&mut out, &format!("SUMMARY:{}", escape_text(&entry.live_stream_title)));
+        fold_line(&mut out, &format!("DESCRIPTION:Video: {}", escape_text(&entry.video_title)));
+        fold_line(&mut out, "END:VEVENT");
+    }
+
+    fold_line(&mut out, "END:VCALENDAR");
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_utc_converts_unix_timestamps() {
+        assert_eq!(format_utc(0), "19700101T000000Z");
+        assert_eq!(format_utc(951782400), "20000229T000000Z");
+        assert_eq!(format_utc(1771237245), "20260216T102045Z");
+    }
+
+    #[test]
+    fn long_lines_are_folded() {
+        let mut out = String::new();
+
+        fold_line(&mut out, &"a".repeat(100));
+
+        assert_eq!(out, format!("{}\r\n {}\r\n", "a".repeat(75), "a".repeat(25)));
+    }
+
+    #[test]
+    fn text_values_are_escaped() {
+        assert_eq!(escape_text("a,b;c\\d\ne"), "a\\,b\\;c\\\\d\\ne");
+    }
+}
@@ -1,146 +1,563 @@
 use anyhow::Result;
 use sqlx::{Pool, Postgres};
-use tokio::process::{Child, Command};
-use std::process::Stdio;
+use sqlx::types::Json;
 use std::sync::Arc;
-use tokio::{
-    io::{AsyncBufReadExt, BufReader},
-    process::ChildStderr,
-};
+use std::sync::atomic::Ordering;
+use tokio::sync::mpsc::{self, UnboundedReceiver};
 use tracing::{info, warn};
-use std::env::var;
 use tracing::{error, debug};
 use tokio::time::Duration;
-use nix::unistd::setsid;
-use nix::sys::signal::{killpg, Signal};
-use nix::unistd::Pid;
 
 use crate::{
     dto::live_stream_state::{
         LiveStreamState,
         StreamStatus,
-        StreamJob
+        StreamJob,
+        FfmpegProgress,
+        ProcessResources,
+        PipelineCommand,
+        SourceItem,
+        PlaybackPosition,
+        LoopProgress,
+        EndpointProgress,
+        Background,
+        FfmpegLaunch
     },
     dto::live_stream_start::LiveStreamData,
+    dto::live_stream_runner::{Playlist, RunnerEvent, RunnerOutput},
+    dto::live_stream_destination::DestinationOutput,
+    dto::live_stream_source::{INFINITE_LOOP, MODE_IMAGE_AUDIO, MODE_INGEST, MODE_VIDEO},
     errors::AppError,
     utils::time::current_unix_timestamp,
+    utils::live_stream_recording::{
+        RecordingConfig,
+        recording_output,
+        register_recordings
+    },
+    utils::live_stream_destination::destination_outputs,
+    utils::live_stream_ingest::listen_url,
+    utils::live_stream_overlay::{
+        create_overlay_runtime,
+        load_image_files,
+        start_text_pollers
+    },
+    utils::live_stream_admission::{
+        AdmissionConfig,
+        AdmissionPolicy,
+        is_running,
+        try_admit
+    },
+    utils::live_stream_pipeline::{
+        IMAGE_AUDIO_BIT_RATE,
+        IMAGE_AUDIO_FRAME_RATE,
+        IMAGE_AUDIO_HEIGHT,
+        IMAGE_AUDIO_VIDEO_BIT_RATE,
+        IMAGE_AUDIO_WIDTH,
+        INGEST_BIT_RATE,
+        INGEST_FRAME_RATE,
+        INGEST_HEIGHT,
+        INGEST_VIDEO_BIT_RATE,
+        INGEST_WIDTH
+    },
+    utils::live_stream_watchdog::{WatchdogConfig, watch_stream},
+    utils::live_stream_event::{transition, transition_stream},
+    utils::live_stream_dry_run::{DryRunConfig, dry_run_endpoint, dry_run_output, prune_dry_runs},
+    utils::live_stream_preview::{
+        PreviewConfig,
+        preview_output,
+        remove_preview
+    },
     models::{
         live_stream_write_history,
         live_stream_update_start_time,
+        live_stream_playback,
         live_stream_empty_schedule
     },
     dto::live_stream_write_history::History
 };
 
-fn spawn_ffmpeg(
-    video: &String,
-    rtmp_url: &String,
-    stream_key: &String,
-    stream_loop: i32
-) -> Result<Child, std::io::Error> {
-    info!("Spawning ffmpeg using {}", video);
-
-    let youtube_rtmp = format!("{}/{}", rtmp_url, stream_key);
-
-    let mut cmd = Command::new("ffmpeg");
-
-    if stream_loop > 1 {
-        info!("ffmpeg -stream_loop {} -re -i {} -progress pipe:2 -stats_period 1 -c:v copy -preset veryfast -tune zerolatency -c:a copy -f flv {}", stream_loop, video, youtube_rtmp);
-        cmd.args([
-                "-stream_loop", &stream_loop.to_string(),
-                "-re",
-                "-i", video,
-
-                // machine-readable progress
-                "-progress", "pipe:2",
-                "-stats_period", "1",
-                
-                "-c:v", "copy",
-                "-preset", "veryfast",
-                "-tune", "zerolatency",
-                "-c:a", "copy",
-                "-f", "flv",
-                &youtube_rtmp,
-            ])
-            .stderr(Stdio::piped())
-            .stdout(Stdio::null());
-    } else {
-        info!("ffmpeg -re -i {} -progress pipe:2 -stats_period 1 -c:v copy -preset veryfast -tune zerolatency -c:a copy -f flv {}", video, youtube_rtmp);
-        cmd.args([
-                "-re",
-                "-i", video,
-
-                // machine-readable progress
-                "-progress", "pipe:2",
-                "-stats_period", "1",
-
-                "-c:v", "copy",
-                "-preset", "veryfast",
-                "-tune", "zerolatency",
-                "-c:a", "copy",
-                "-f", "flv",
-                &youtube_rtmp,
-            ])
-            .stderr(Stdio::piped())
-            .stdout(Stdio::null());
-    }
-
-    unsafe {
-        cmd.pre_exec(|| {
-            setsid().map_err(|e| {
-                std::io::Error::new(std::io::ErrorKind::Other, e)
-            })?;
-            Ok(())
-        });
+// How long an endpoint has to hold before a failure of it starts a new
+// round of failovers.
+const FAILOVER_STABLE_PERIOD: Duration = Duration::from_secs(60);
+
+// Whether `generation` is still the running ffmpeg of the job. Output of an
+// ffmpeg replaced by a restart is ignored.
+fn is_current_generation(
+    state: &LiveStreamState,
+    stream_id: i64,
+    generation: u32
+) -> bool {
+    match state.jobs.get(&stream_id) {
+        Some(job) => job.generation == generation,
+        None => false
+    }
+}
+
+// Takes a failed tee output out of the redundant endpoints of a job. Returns
+// false once none of them is left.
+fn drop_tee_slave(
+    state: &LiveStreamState,
+    stream_id: i64,
+    slave: usize
+) -> bool {
+    let mut job = match state.jobs.get_mut(&stream_id) {
+        Some(val) => val,
+        None => return true
+    };
+
+    // The recording comes after the endpoints.
+    if !job.endpoints.redundant || slave >= job.endpoints.endpoints.len() {
+        warn!(%stream_id, "live stream recording failed, the broadcast goes on");
+
+        return true;
     }
 
-    Ok(cmd.spawn()?)
+    job.endpoints.active.retain(|index| *index != slave);
+    warn!(%stream_id, endpoint = slave, "redundant endpoint failed, {} left", job.endpoints.active.len());
+
+    !job.endpoints.active.is_empty()
+}
+
+// Moves a job that lost its endpoint on to the next one. Returns false when
+// there is no other endpoint, or every one of them failed in a row.
+async fn fail_over(
+    state: &Arc<LiveStreamState>,
+    stream_id: i64,
+    pool: &Pool<Postgres>
+) -> bool {
+    let next = match state.jobs.get_mut(&stream_id) {
+        Some(mut job) => {
+            let job = &mut *job;
+            let launch = match job.launch.as_mut() {
+                Some(val) if !val.redundant && val.outputs.len() > 1 => val,
+                _ => return false
+            };
+            let endpoints = &mut job.endpoints;
+
+            if endpoints.switched_at.is_some_and(|at| at.elapsed() >= FAILOVER_STABLE_PERIOD) {
+                endpoints.streak = 0;
+            }
+
+            if endpoints.streak as usize + 1 >= launch.outputs.len() {
+                return false;
+            }
+
+            launch.active = (launch.active + 1) % launch.outputs.len();
+            endpoints.active = vec![launch.active];
+            endpoints.failovers += 1;
+            endpoints.streak += 1;
+            endpoints.switched_at = Some(std::time::Instant::now());
+
+            launch.active
+        }
+        None => return false
+    };
+
+    warn!(%stream_id, endpoint = next, "destination endpoint failed, failing over to the next one");
+
+    respawn_ffmpeg(state, stream_id, pool).await
 }
 
-fn monitor_ffmpeg(
+// Applies what the output of a job reports to the job, until the output is
+// gone or replaced.
+fn monitor_output(
     stream_id: i64,
-    stderr: ChildStderr,
+    generation: u32,
+    mut events: UnboundedReceiver<RunnerEvent>,
     state: Arc<LiveStreamState>,
     pool: &Pool<Postgres>
 ) {
     let pool_clone = pool.clone();
     tokio::spawn(async move {
-        let mut lines = BufReader::new(stderr).lines();
+        while let Some(event) = events.recv().await {
+            if !is_current_generation(&state, stream_id, generation) {
+                return;
+            }
+
+            match event {
+                RunnerEvent::Progress(progress) => {
+                    if let Some(mut job) = state.jobs.get_mut(&stream_id) {
+                        job.playback.advance(progress.out_time_ms);
+                        job.progress = progress;
+
+                        if matches!(job.status, StreamStatus::Starting) {
+                            transition(&mut job, StreamStatus::Live, &state.events);
+                        }
+                    }
+                }
+                RunnerEvent::Finished => {
+                    info!(%stream_id, "ffmpeg finished");
+
+                    let status = match state.jobs.get(&stream_id).and_then(|job| job.pipeline_error.clone()) {
+                        Some(reason) => StreamStatus::Failed(reason),
+                        None => StreamStatus::Stopped
+                    };
 
-        while let Ok(Some(line)) = lines.next_line().await {
-            if line == "progress=continue" {
-                if let Some(mut job) = state.jobs.get_mut(&stream_id) {
-                    if matches!(job.status, StreamStatus::Starting) {
-                        job.status = StreamStatus::Live;
+                    stop_stream_internal(&state, stream_id, status, &pool_clone).await;
+                    return;
+                }
+                RunnerEvent::SlaveFailed(slave) => {
+                    if !drop_tee_slave(&state, stream_id, slave) {
+                        stop_stream_internal(
+                            &state,
+                            stream_id,
+                            StreamStatus::Failed(String::from("Every destination endpoint failed.")),
+                            &pool_clone
+                        )
+                        .await;
+                        return;
+                    }
+                }
+                RunnerEvent::EndpointFailed(_) => {
+                    if fail_over(&state, stream_id, &pool_clone).await {
+                        return;
+                    }
+                }
+                RunnerEvent::Error(line) => {
+                    if fail_over(&state, stream_id, &pool_clone).await {
+                        return;
                     }
+
+                    warn!(%stream_id, "ffmpeg error: {}", line);
+                    stop_stream_internal(
+                        &state,
+                        stream_id,
+                        StreamStatus::Failed(line),
+                        &pool_clone
+                    )
+                    .await;
+                    return;
                 }
             }
+        }
+    });
+}
 
-            if line == "progress=end" {
-                info!(%stream_id, "ffmpeg finished");
-                stop_stream_internal(
-                    &state,
-                    stream_id,
-                    StreamStatus::Stopped,
-                    &pool_clone
-                )
-                .await;
-                return;
+// Replaces the running output ffmpeg of a job with a new one, spawned from
+// the current launch of the job. Returns false when the job is gone or has
+// been failed because ffmpeg could not be spawned again.
+pub async fn respawn_ffmpeg(
+    state: &Arc<LiveStreamState>,
+    stream_id: i64,
+    pool: &Pool<Postgres>
+) -> bool {
+    let (old_output, launch, generation) = match state.jobs.get_mut(&stream_id) {
+        Some(mut job) => {
+            if job.status.is_final() {
+                return false;
             }
 
-            if line.contains("error") {
-                warn!(%stream_id, "ffmpeg error: {}", line);
-                stop_stream_internal(
-                    &state,
-                    stream_id,
-                    StreamStatus::Failed(line),
-                    &pool_clone
-                )
-                .await;
-                return;
+            let launch = match job.launch.clone() {
+                Some(val) => val,
+                None => return false
+            };
+
+            job.generation += 1;
+            job.ffmpeg_restarts += 1;
+            job.progress = FfmpegProgress::default();
+            // The new ffmpeg counts its output time from zero.
+            job.playback.last_out_time = None;
+
+            (job.output.take(), launch, job.generation)
+        }
+        None => return false
+    };
+
+    state.metrics.ffmpeg_restarts_total.fetch_add(1, Ordering::Relaxed);
+
+    if let Some(output) = old_output {
+        info!(%stream_id, "stopping replaced ffmpeg");
+        output.stop(false).await;
+    }
+
+    let output = match state.runner.spawn_output(&launch) {
+        Ok(val) => val,
+        Err(e) => {
+            error!("Error while respawning ffmpeg.");
+            debug!("{}.", e);
+
+            stop_stream_internal(state, stream_id, StreamStatus::Failed(e.to_string()), pool).await;
+
+            return false;
+        }
+    };
+
+    let RunnerOutput { process, events } = output;
+
+    // The job may have been stopped while the old ffmpeg was being killed.
+    let process = match state.jobs.get_mut(&stream_id) {
+        Some(mut job) if !job.status.is_final() => {
+            job.pid = process.pid();
+            job.output = Some(process);
+
+            None
+        }
+        _ => Some(process)
+    };
+
+    if let Some(process) = process {
+        process.stop(false).await;
+
+        return false;
+    }
+
+    info!(%stream_id, generation, "ffmpeg respawned");
+
+    monitor_output(stream_id, generation, events, state.clone(), pool);
+
+    true
+}
+
+// Restarts a stalled job, both the output ffmpeg and the current feeder.
+pub async fn restart_ffmpeg(
+    state: &Arc<LiveStreamState>,
+    stream_id: i64,
+    pool: &Pool<Postgres>
+) -> bool {
+    match state.jobs.get_mut(&stream_id) {
+        Some(mut job) => {
+            if job.status.is_final() {
+                return false;
+            }
+
+            job.restart_count += 1;
+            transition(&mut job, StreamStatus::Degraded, &state.events);
+
+            // A stuck demuxer is on the feeder side, so both ends start over.
+            if let Some(pipeline) = &job.pipeline {
+                let _ = pipeline.send(PipelineCommand::RestartFeeder);
             }
         }
-    });
+        None => return false
+    };
+
+    respawn_ffmpeg(state, stream_id, pool).await
+}
+
+const ADMISSION_RETRY_INTERVAL: Duration = Duration::from_secs(10);
+
+// Waits until the job is admitted. Returns false when the job has been
+// finalized instead, either rejected, cancelled or out of its schedule.
+async fn wait_for_admission(
+    state: &Arc<LiveStreamState>,
+    stream_id: i64,
+    config: &AdmissionConfig,
+    cancel_notify: &Arc<tokio::sync::Notify>,
+    pool: &Pool<Postgres>
+) -> bool {
+    loop {
+        let reason = match try_admit(state, config, stream_id) {
+            Ok(_) => return true,
+            Err(reason) => reason
+        };
+
+        if config.policy == AdmissionPolicy::Reject {
+            warn!(%stream_id, "live stream rejected by admission control: {}", reason);
+
+            stop_stream_internal(state, stream_id, StreamStatus::Failed(reason), pool).await;
+
+            return false;
+        }
+
+        let schedule_end = match state.jobs.get_mut(&stream_id) {
+            Some(mut job) => {
+                if job.status.is_final() {
+                    return false;
+                }
+
+                if !matches!(job.status, StreamStatus::Queued) {
+                    info!(%stream_id, "live stream queued by admission control: {}", reason);
+                }
+
+                transition(&mut job, StreamStatus::Queued, &state.events);
+                job.schedule_end
+            }
+            None => return false
+        };
+
+        if let Some(stop_at) = schedule_end && stop_at <= current_unix_timestamp() as i64 {
+            let reason = format!("Not admitted before the schedule end: {}", reason);
+
+            stop_stream_internal(state, stream_id, StreamStatus::Failed(reason), pool).await;
+
+            return false;
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(ADMISSION_RETRY_INTERVAL) => {}
+            // Whoever cancelled the job has finalized it already.
+            _ = cancel_notify.notified() => return false
+        }
+    }
+}
+
+// The playlist of a live stream and its outgoing bit rate, in kbps.
+fn build_source_items(
+    data: &LiveStreamData,
+    upload_dir: &str
+) -> Result<(Vec<SourceItem>, i64), AppError> {
+    let video_file = data.video_file.as_ref().map(|file| format!("{}/videos/{}", upload_dir, file));
+
+    if data.mode == MODE_INGEST {
+        let (Some(protocol), Some(port), Some(key)) = (&data.ingest_protocol, data.ingest_port, &data.ingest_key) else {
+            return Err(AppError::BadRequest("Live stream has no ingest address.".to_string()));
+        };
+        let item = SourceItem::Ingest {
+            url: listen_url(protocol, port, key),
+            fallback: video_file,
+            fallback_length: data.video_length as i64 * 1000
+        };
+
+        return Ok((vec![item], INGEST_BIT_RATE));
+    }
+
+    if data.mode != MODE_IMAGE_AUDIO {
+        return match video_file {
+            Some(file) => {
+                let item = SourceItem::Video {
+                    file,
+                    start: data.trim_start.unwrap_or(0),
+                    end: data.trim_end
+                };
+
+                Ok((vec![item], data.video_bit_rate as i64))
+            }
+            None => Err(AppError::BadRequest("Live stream has no video.".to_string()))
+        };
+    }
+
+    let background = match (&data.background_image_file, video_file) {
+        (Some(file), _) => Background::Image(format!("{}/gallery_images/{}", upload_dir, file)),
+        (None, Some(file)) => Background::Video(file),
+        (None, None) => return Err(AppError::BadRequest("Live stream has no background.".to_string()))
+    };
+    let items: Vec<SourceItem> = data.audio_files
+        .iter()
+        .map(|file| SourceItem::ImageAudio {
+            background: background.clone(),
+            audio: format!("{}/audios/{}", upload_dir, file)
+        })
+        .collect();
+
+    if items.is_empty() {
+        return Err(AppError::BadRequest("Live stream has no audios.".to_string()));
+    }
+
+    Ok((items, IMAGE_AUDIO_BIT_RATE))
+}
+
+// The slate is encoded to the size of the main content, the image audio or
+// ingest profile when there is no main video.
+fn build_slate(
+    data: &LiveStreamData,
+    upload_dir: &str
+) -> Option<SourceItem> {
+    let background = match (&data.slate_file, &data.slate_image_file) {
+        (Some(file), _) => Background::Video(format!("{}/videos/{}", upload_dir, file)),
+        (None, Some(file)) => Background::Image(format!("{}/gallery_images/{}", upload_dir, file)),
+        (None, None) => return None
+    };
+    let audio = data.slate_audio_file.as_ref().map(|file| format!("{}/audios/{}", upload_dir, file));
+
+    if data.mode == MODE_IMAGE_AUDIO {
+        return Some(SourceItem::Slate {
+            background,
+            audio,
+            width: IMAGE_AUDIO_WIDTH,
+            height: IMAGE_AUDIO_HEIGHT,
+            frame_rate: IMAGE_AUDIO_FRAME_RATE,
+            bit_rate: IMAGE_AUDIO_VIDEO_BIT_RATE
+        });
+    }
+
+    if data.mode == MODE_INGEST {
+        return Some(SourceItem::Slate {
+            background,
+            audio,
+            width: INGEST_WIDTH,
+            height: INGEST_HEIGHT,
+            frame_rate: INGEST_FRAME_RATE,
+            bit_rate: INGEST_VIDEO_BIT_RATE
+        });
+    }
+
+    Some(SourceItem::Slate {
+        background,
+        audio,
+        width: data.video_width,
+        height: data.video_height,
+        frame_rate: data.video_frame_rate,
+        bit_rate: data.video_bit_rate as i64
+    })
+}
+
+// Same number of plays as `-stream_loop` gave when ffmpeg read the video
+// directly, None loops forever.
+fn loop_passes(stream_loop: i32) -> Option<u32> {
+    match stream_loop {
+        INFINITE_LOOP => None,
+        val if val > 1 => Some(val as u32 + 1),
+        _ => Some(1)
+    }
+}
+
+// Length of one pass over the video between its trim points, in milliseconds.
+fn loop_length(data: &LiveStreamData) -> Option<i64> {
+    if data.mode != MODE_VIDEO || data.video_length <= 0 {
+        return None;
+    }
+
+    let end = data.trim_end.unwrap_or(data.video_length as i64 * 1000);
+
+    Some((end - data.trim_start.unwrap_or(0)).max(0))
+}
+
+// Where the video of a live stream that resumes starts, positions outside
+// of the trim points start it from the beginning.
+fn resume_position(data: &LiveStreamData) -> Option<i64> {
+    if !data.resume || data.mode != MODE_VIDEO {
+        return None;
+    }
+
+    let position = data.resume_position?;
+    let start = data.trim_start.unwrap_or(0);
+
+    match data.trim_end {
+        Some(end) if position >= end => None,
+        _ if position <= start => None,
+        _ => Some(position)
+    }
+}
+
+// Size and rate the output ffmpeg encodes to when it draws overlays.
+fn output_profile(data: &LiveStreamData) -> (i32, i32, i64) {
+    if data.mode == MODE_IMAGE_AUDIO {
+        return (IMAGE_AUDIO_WIDTH, IMAGE_AUDIO_FRAME_RATE, IMAGE_AUDIO_VIDEO_BIT_RATE);
+    }
+
+    if data.mode == MODE_INGEST {
+        return (INGEST_WIDTH, INGEST_FRAME_RATE, INGEST_VIDEO_BIT_RATE);
+    }
+
+    (data.video_width, data.video_frame_rate, data.video_bit_rate as i64)
+}
+
+fn endpoint_progress(data: &LiveStreamData, dry_run: Option<&DestinationOutput>) -> EndpointProgress {
+    let (endpoints, redundant) = match dry_run {
+        Some(output) => (vec![dry_run_endpoint(output)], false),
+        None => (data.destination.endpoints(), data.destination.is_redundant())
+    };
+    let active = match redundant {
+        true => (0..endpoints.len()).collect(),
+        false => vec![0]
+    };
+
+    EndpointProgress {
+        endpoints,
+        active,
+        redundant,
+        ..EndpointProgress::default()
+    }
 }
 
 pub async fn start_stream(
@@ -153,7 +570,7 @@ pub async fn start_stream(
         debug!("Live stream with id={} status is {:#?}", live_stream_data.id, job.status);
 
         match job.status {
-            StreamStatus::Live => return Err(AppError::LiveStreamAlreadyLive),
+            StreamStatus::Live | StreamStatus::Degraded => return Err(AppError::LiveStreamAlreadyLive),
             StreamStatus::Scheduled => return Err(AppError::LiveStreamAlreadyScheduled),
             _ => return Err(AppError::LiveStreamConflict)
         }
@@ -161,19 +578,60 @@ pub async fn start_stream(
 
     info!("Starting live stream with id {}.", live_stream_data.id);
 
+    if live_stream_data.dry_run {
+        info!(stream_id = live_stream_data.id, "dry run, the destination is left alone");
+    }
+
     let live_stream_data_clone = live_stream_data.clone();
-    let upload_dir = match var("UPLOAD_DIRECTORY") {
-        Ok(val) => val,
-        Err(err) => {
-            error!("Missing UPLOAD_DIRECTORY key and value in env file.");
-            debug!("{}", err.to_string());
+    let upload_dir = state.upload_dir.clone();
+    let (items, bit_rate) = build_source_items(live_stream_data, &upload_dir)?;
+    let (video_width, video_frame_rate, video_bit_rate) = output_profile(live_stream_data);
+    let overlay_image_files = load_image_files(&live_stream_data.overlays, pool).await?;
+    let overlays = create_overlay_runtime(
+        live_stream_data.id,
+        &live_stream_data.overlays,
+        &overlay_image_files,
+        &upload_dir
+    )?;
+    let overlay_dir = overlays.dir.clone();
+    let recording = live_stream_data.record.then(|| recording_output(
+        live_stream_data.id,
+        &live_stream_data.title,
+        &upload_dir,
+        &RecordingConfig::from_env()
+    ));
+    let preview = live_stream_data.preview.then(|| preview_output(
+        live_stream_data.id,
+        &upload_dir,
+        &PreviewConfig::from_env()
+    ));
+    // A dry run goes through every step of a real one, only its output
+    // stays on this machine.
+    let dry_run_config = DryRunConfig::from_env();
+    let dry_run = live_stream_data.dry_run.then(|| dry_run_output(
+        live_stream_data.id,
+        &upload_dir,
+        &dry_run_config
+    ));
 
-            return Err(AppError::EnvVarError(err));
-        }
+    if let Some(dir) = dry_run.as_ref().and_then(|output| output.local_dir.as_ref()) {
+        prune_dry_runs(dir, dry_run_config.retention);
+    }
+    let resume_at = resume_position(live_stream_data);
+    let playback = PlaybackPosition {
+        position_ms: resume_at.or(live_stream_data.resume_position),
+        ..PlaybackPosition::default()
     };
-    let video_file = format!("{}/videos/{}", upload_dir, live_stream_data_clone.video_file);
+
+    if let Some(position) = resume_at {
+        info!(stream_id = live_stream_data.id, position, "resuming live stream video");
+    }
     let stream_id = live_stream_data_clone.id;
     let cancel_notify = Arc::new(tokio::sync::Notify::new());
+    let schedule_notify = Arc::new(tokio::sync::Notify::new());
+    let slate = build_slate(live_stream_data, &upload_dir);
+    let admission = AdmissionConfig::from_env();
+    let watchdog = WatchdogConfig::from_env();
 
     let pool_clone = pool.clone();
     
@@ -182,47 +640,147 @@ pub async fn start_stream(
         owner: live_stream_data_clone.owner,
         schedule_start: live_stream_data_clone.schedule_start,
         schedule_end: live_stream_data.schedule_end,
+        bit_rate,
+        dry_run: live_stream_data.dry_run,
         actual_start: None,
         actual_stop: None,
         status: StreamStatus::Offline,
-        child: None,
+        output: None,
+        pid: None,
+        feeder_pid: None,
+        pipeline: None,
+        slate: slate.clone(),
         cancel_notify: cancel_notify.clone(),
-        is_finalized: false
+        schedule_notify: schedule_notify.clone(),
+        events: Vec::new(),
+        progress: FfmpegProgress::default(),
+        playback,
+        loops: LoopProgress {
+            count: loop_passes(live_stream_data.stream_loop),
+            length_ms: loop_length(live_stream_data),
+            ..LoopProgress::default()
+        },
+        endpoints: endpoint_progress(live_stream_data, dry_run.as_ref()),
+        restart_count: 0,
+        ffmpeg_restarts: 0,
+        pipeline_error: None,
+        generation: 0,
+        launch: None,
+        overlays: Some(overlays),
+        resources: ProcessResources::default()
     });
 
+    start_text_pollers(state, stream_id, &live_stream_data.overlays, &overlay_dir, 0);
+
+    let starts_now = match live_stream_data.schedule_start {
+        Some(start_at) => start_at <= current_unix_timestamp() as i64,
+        None => true
+    };
+
+    // Streams that start right away are checked here, so a rejection can be
+    // returned to the caller. Scheduled streams are checked when they fire.
+    if starts_now && let Err(reason) = try_admit(state, &admission, stream_id) {
+        if admission.policy == AdmissionPolicy::Reject {
+            warn!(%stream_id, "live stream rejected by admission control: {}", reason);
+
+            state.jobs.remove(&stream_id);
+
+            return Err(AppError::LiveStreamLimitExceeded(reason));
+        }
+
+        info!(%stream_id, "live stream queued by admission control: {}", reason);
+
+        transition_stream(state, stream_id, StreamStatus::Queued);
+    }
+
     let state_clone = state.clone();
 
     tokio::spawn(async move {
-        if let Some(start_at) = live_stream_data_clone.schedule_start {
+        let mut start_at = live_stream_data_clone.schedule_start;
+
+        // An edited start sets the timer again, a start taken away starts
+        // the live stream right away.
+        while let Some(at) = start_at {
             let now = current_unix_timestamp() as i64;
 
-            debug!("Started in {} secs.", (start_at - now));
+            debug!("Started in {} secs.", (at - now));
 
-            if start_at > now {
-                if let Some(mut job) = state_clone.jobs.get_mut(&stream_id) {
-                    job.status = StreamStatus::Scheduled;
-                }
+            if at <= now {
+                break;
+            }
 
-                tokio::select! {
-                    _ = tokio::time::sleep(Duration::from_secs((start_at - now) as u64)) => {}
-                    _ = cancel_notify.notified() => {
-                        stop_stream_internal(&state_clone, stream_id, StreamStatus::Cancelled, &pool_clone).await;
+            transition_stream(&state_clone, stream_id, StreamStatus::Scheduled);
 
-                        return;
-                    }
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs((at - now) as u64)) => break,
+                // Whoever cancelled the job has finalized it already, the
+                // live stream may even be running again from an edit.
+                _ = cancel_notify.notified() => return,
+                _ = schedule_notify.notified() => {
+                    start_at = match state_clone.jobs.get(&stream_id) {
+                        Some(job) => job.schedule_start,
+                        None => return
+                    };
                 }
             }
         }
 
+        let is_admitted = match state_clone.jobs.get(&stream_id) {
+            Some(job) => matches!(job.status, StreamStatus::Starting),
+            None => return
+        };
+
+        if !is_admitted && !wait_for_admission(&state_clone, stream_id, &admission, &cancel_notify, &pool_clone).await {
+            return;
+        }
+
         live_stream_update_start_time::update_start_time(stream_id, &pool_clone).await;
 
-        let mut child = match spawn_ffmpeg(
-                &video_file,
-                &live_stream_data_clone.rtmp_url,
-                &live_stream_data_clone.stream_key,
-                live_stream_data_clone.stream_loop
-        ) {
-            Ok(c) => c,
+        let (pipeline_tx, pipeline_rx) = mpsc::unbounded_channel();
+        let playlist = Playlist {
+            items,
+            passes: loop_passes(live_stream_data_clone.stream_loop),
+            resume_at,
+            commands: pipeline_rx
+        };
+        let input = match state_clone.runner.start_pipeline(&state_clone, stream_id, playlist) {
+            Ok(val) => val,
+            Err(e) => {
+                error!("Error while creating live stream pipeline.");
+                debug!("{}.", e);
+
+                stop_stream_internal(&state_clone, stream_id, StreamStatus::Failed(e.to_string()), &pool_clone).await;
+
+                return;
+            }
+        };
+        // Overlays may have been edited while the live stream was scheduled.
+        let overlays = state_clone.jobs
+            .get(&stream_id)
+            .and_then(|job| job.overlays.as_ref().map(|runtime| runtime.layers.clone()))
+            .unwrap_or_default();
+        let (outputs, redundant) = match dry_run {
+            Some(output) => (vec![output], false),
+            None => (
+                destination_outputs(&live_stream_data_clone.destination, stream_id, &upload_dir),
+                live_stream_data_clone.destination.is_redundant()
+            )
+        };
+        let launch = FfmpegLaunch {
+            input,
+            outputs,
+            active: 0,
+            redundant,
+            overlays,
+            width: video_width,
+            frame_rate: video_frame_rate,
+            video_bit_rate,
+            recording,
+            preview
+        };
+
+        let RunnerOutput { process, events } = match state_clone.runner.spawn_output(&launch) {
+            Ok(val) => val,
             Err(e) => {
                 error!("Error while spawning ffmpeg.");
                 debug!("{}.", e);
@@ -233,35 +791,135 @@ pub async fn start_stream(
             }
         };
 
-        let stderr = child.stderr.take().unwrap();
+        // The job may have been stopped while ffmpeg was being spawned, its
+        // process group would be left behind without anyone to stop it.
+        let process = match state_clone.jobs.get_mut(&stream_id) {
+            Some(mut job) if !job.status.is_final() => {
+                job.actual_start = Some(current_unix_timestamp() as i64);
+                transition(&mut job, StreamStatus::Starting, &state_clone.events);
+                job.pid = process.pid();
+                job.output = Some(process);
+                job.pipeline = Some(pipeline_tx);
+                job.launch = Some(launch);
 
-        if let Some(mut job) = state_clone.jobs.get_mut(&stream_id) {
-            job.actual_start = Some(current_unix_timestamp() as i64);
-            job.status = StreamStatus::Starting;
-            job.child = Some(child);
+                None
+            }
+            _ => Some(process)
+        };
+
+        if let Some(process) = process {
+            warn!(%stream_id, "live stream ended while ffmpeg was starting, stopping it");
+            process.stop(false).await;
+
+            return;
         }
 
-        monitor_ffmpeg(stream_id, stderr, state_clone.clone(), &pool_clone);
+        monitor_output(stream_id, 0, events, state_clone.clone(), &pool_clone);
 
-        if let Some(stop_at) = live_stream_data_clone.schedule_end {
-            let now = current_unix_timestamp() as i64;
+        tokio::spawn(watch_stream(
+            state_clone.clone(),
+            stream_id,
+            watchdog,
+            pool_clone.clone()
+        ));
 
-            debug!("Stop in {} secs.", (stop_at - now));
+        // The end may have been edited while the live stream was scheduled.
+        // One already past when it starts is left alone, one edited into the
+        // past later ends it right away.
+        let mut stop_at = state_clone.jobs
+            .get(&stream_id)
+            .and_then(|job| job.schedule_end)
+            .filter(|at| *at > current_unix_timestamp() as i64);
 
-            if stop_at > now {
-                tokio::select! {
-                    _ = tokio::time::sleep(Duration::from_secs((stop_at - now) as u64)) => {}
-                    _ = cancel_notify.notified() => return,
-                }
+        loop {
+            let stop_in = stop_at.map(|at| (at - current_unix_timestamp() as i64).max(0) as u64);
+
+            if let Some(secs) = stop_in {
+                debug!("Stop in {} secs.", secs);
+            }
 
-                stop_stream_internal(&state_clone, stream_id, StreamStatus::Done, &pool_clone).await;
+            tokio::select! {
+                _ = async {
+                    match stop_in {
+                        Some(secs) => tokio::time::sleep(Duration::from_secs(secs)).await,
+                        None => std::future::pending().await
+                    }
+                } => break,
+                _ = cancel_notify.notified() => return,
+                _ = schedule_notify.notified() => {
+                    stop_at = match state_clone.jobs.get(&stream_id) {
+                        Some(job) if !job.status.is_final() => job.schedule_end,
+                        _ => return
+                    };
+                }
             }
         }
+
+        stop_stream_internal(&state_clone, stream_id, StreamStatus::Done, &pool_clone).await;
     });
 
     Ok(stream_id)
 }
 
+// Moves the timers of a job to an edited schedule. A job that has started
+// keeps its start, the end applies to every job. Returns false when there is
+// no job to reschedule.
+pub fn reschedule_stream(
+    state: &Arc<LiveStreamState>,
+    stream_id: i64,
+    schedule_start: Option<i64>,
+    schedule_end: Option<i64>
+) -> bool {
+    let mut job = match state.jobs.get_mut(&stream_id) {
+        Some(val) if !val.status.is_final() => val,
+        _ => return false
+    };
+
+    if job.actual_start.is_none() {
+        job.schedule_start = schedule_start;
+    }
+
+    job.schedule_end = schedule_end;
+    // Kept until the timers wait again when they are busy.
+    job.schedule_notify.notify_one();
+
+    info!(%stream_id, ?schedule_start, ?schedule_end, "live stream rescheduled");
+
+    true
+}
+
+// Replaces the source of a running live stream, the output ffmpeg and its
+// connection to the destination stay up. No items switches to the slate.
+pub fn swap_source(
+    state: &Arc<LiveStreamState>,
+    stream_id: i64,
+    items: Vec<SourceItem>
+) -> Result<(), AppError> {
+    let job = match state.jobs.get(&stream_id) {
+        Some(val) => val,
+        None => return Err(AppError::Conflict("Live stream is not running.".to_string()))
+    };
+
+    if job.status.is_final() || !is_running(&job.status) {
+        return Err(AppError::Conflict("Live stream is not running.".to_string()));
+    }
+
+    if items.is_empty() && job.slate.is_none() {
+        return Err(AppError::Conflict("Live stream has no standby slate.".to_string()));
+    }
+
+    let pipeline = match &job.pipeline {
+        Some(val) => val,
+        None => return Err(AppError::Conflict("Live stream source can not be swapped.".to_string()))
+    };
+
+    if pipeline.send(PipelineCommand::Swap(items)).is_err() {
+        return Err(AppError::Conflict("Live stream is not running.".to_string()));
+    }
+
+    Ok(())
+}
+
 pub async fn write_history(
     state: &Arc<LiveStreamState>,
     stream_id: i64,
@@ -271,7 +929,10 @@ pub async fn write_history(
     if let Some((_, job)) = state.jobs.remove(&stream_id) {
         info!(%stream_id, "writing stream history");
 
-        let owner = match live_stream_write_history::get_live_stream_owner(stream_id, &pool).await {
+        // Nothing writes to the preview once the job is gone.
+        remove_preview(&state.upload_dir, stream_id);
+
+        let owner = match live_stream_write_history::get_live_stream_owner(stream_id, pool).await {
             Some(val) => val,
             None => String::from("None")
         };
@@ -285,58 +946,498 @@ pub async fn write_history(
             StreamStatus::Done => String::from("Done"),
             StreamStatus::Stopped => String::from("Stopped"),
             StreamStatus::Cancelled => String::from("Canceled"),
+            StreamStatus::Interrupted => String::from("Interrupted"),
             StreamStatus::Failed(_) => String::from("Failed"),
             _ => String::from("Unknown")
         };
 
+        let resources = &job.resources;
+        let has_samples = resources.samples > 0;
+
         let data = History {
             owner: owner,
             live_stream: stream_id,
             start_time: start_time,
             end_time: current_unix_timestamp() as i64,
-            end_status: end_status
+            end_status: end_status,
+            avg_cpu: has_samples.then(|| (resources.cpu_percent_sum / resources.samples as f64) as f32),
+            peak_cpu: has_samples.then_some(resources.peak_cpu_percent),
+            peak_memory: has_samples.then_some(resources.peak_memory_kb as i64),
+            net_tx_bytes: job.actual_start.map(|_| job.progress.total_size as i64),
+            loop_iteration: (job.loops.iteration > 0).then_some(job.loops.iteration as i32),
+            loop_position: job.playback.position_ms.filter(|_| job.loops.iteration > 0),
+            dry_run: job.dry_run,
+            events: Json(job.events.clone())
         };
 
-        live_stream_write_history::write_history(&data, &pool).await;
+        let history = live_stream_write_history::write_history(&data, pool).await;
+        let recording = job.launch.as_ref().and_then(|launch| launch.recording.clone());
+
+        // ffmpeg has closed its last segment by now.
+        if let (Some(history), Some(recording)) = (history, recording) {
+            tokio::spawn(register_recordings(history, recording, job.owner.clone(), pool.clone()));
+        }
+
+        // A run that never started leaves the position of the previous one,
+        // re-adopted jobs do not know where their video is.
+        if job.actual_start.is_some() && job.launch.is_some() {
+            live_stream_playback::save_resume_position(stream_id, job.playback.position_ms, pool).await;
+        }
     }
 }
 
+// Ends the job of a live stream so it can be started again from its edited
+// settings. The schedule in the database already holds the edit and is left
+// alone, and only a job that went out is recorded in the history. Returns
+// false when there was no job left to end.
+pub async fn end_job_for_restart(
+    state: &Arc<LiveStreamState>,
+    stream_id: i64,
+    pool: &Pool<Postgres>
+) -> bool {
+    let (output, has_run) = match state.jobs.get_mut(&stream_id) {
+        Some(mut job) => {
+            let has_run = is_running(&job.status);
+            let final_status = match has_run {
+                true => StreamStatus::Stopped,
+                false => StreamStatus::Cancelled
+            };
+
+            if !transition(&mut job, final_status, &state.events) {
+                return false;
+            }
+
+            job.cancel_notify.notify_waiters();
+
+            (job.output.take(), has_run)
+        }
+        None => return false
+    };
+
+    if let Some(output) = output {
+        info!(%stream_id, "stopping ffmpeg");
+        output.stop(true).await;
+    }
+
+    match has_run {
+        true => write_history(state, stream_id, pool, StreamStatus::Stopped).await,
+        false => {
+            state.jobs.remove(&stream_id);
+        }
+    }
+
+    true
+}
+
+// Finalizes the job with `final_status`. False when there is no job or it
+// can not get there, nothing is touched then.
 pub async fn stop_stream_internal(
     state: &Arc<LiveStreamState>,
     stream_id: i64,
     final_status: StreamStatus,
     pool: &Pool<Postgres>
-) {
-    live_stream_empty_schedule::empty_schedule(stream_id, &pool).await;
-
-    let child = {
+) -> bool {
+    let output = {
         if let Some(mut job) = state.jobs.get_mut(&stream_id) {
-            if job.is_finalized {
-                return;
+            if !transition(&mut job, final_status.clone(), &state.events) {
+                return false;
             }
-            job.is_finalized = true;
             job.cancel_notify.notify_waiters();
-            job.child.take()
+            job.output.take()
         } else {
-            return;
+            return false;
         }
     };
 
-    if let Some(mut child) = child {
-        if let Some(pid) = child.id() {
-            let pgid = Pid::from_raw(pid as i32);
+    live_stream_empty_schedule::empty_schedule(stream_id, pool).await;
 
-            info!(%stream_id, pid, "SIGTERM ffmpeg group");
-            let _ = killpg(pgid, Signal::SIGTERM);
+    if let Some(output) = output {
+        info!(%stream_id, "stopping ffmpeg");
+        output.stop(true).await;
+    }
 
-            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    write_history(state, stream_id, pool, final_status).await;
 
-            info!(%stream_id, pid, "SIGKILL ffmpeg group");
-            let _ = killpg(pgid, Signal::SIGKILL);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dashmap::DashMap;
+    use sqlx::postgres::PgPoolOptions;
+    use std::sync::Mutex;
+    use tokio::sync::broadcast;
+    use crate::{
+        dto::live_stream_destination::Destination,
+        dto::metrics_state::MetricsState,
+        utils::live_stream_pipeline::{create_pipeline, run_pipeline},
+        utils::live_stream_shutdown::{ShutdownConfig, ShutdownPolicy, shutdown_streams},
+        utils::live_stream_scripted_runner::{Script, ScriptedRunner, progress}
+    };
+
+    // Nothing listens there, every query fails right away and the state
+    // machine goes on without the database.
+    fn pool() -> Pool<Postgres> {
+        PgPoolOptions::new()
+            .acquire_timeout(Duration::from_millis(1))
+            .connect_lazy("postgres://streamtfhd@127.0.0.1:1/streamtfhd")
+            .unwrap()
+    }
+
+    fn scripted_state(runner: Arc<ScriptedRunner>) -> Arc<LiveStreamState> {
+        Arc::new(LiveStreamState {
+            jobs: DashMap::new(),
+            metrics: Arc::new(MetricsState::new()),
+            admission_lock: Mutex::new(()),
+            runner,
+            events: broadcast::channel(256).0,
+            upload_dir: std::env::temp_dir().to_string_lossy().into_owned()
+        })
+    }
+
+    fn live_stream(id: i64, schedule_start: Option<i64>) -> LiveStreamData {
+        LiveStreamData {
+            id,
+            owner: String::from("septian"),
+            title: String::from("Scripted"),
+            mode: String::from(MODE_VIDEO),
+            video_file: Some(String::from("a.mp4")),
+            video_bit_rate: 2500,
+            video_width: 1280,
+            video_height: 720,
+            video_frame_rate: 30,
+            video_length: 600,
+            background_image_file: None,
+            audio_files: Vec::new(),
+            slate_file: None,
+            slate_image_file: None,
+            slate_audio_file: None,
+            stream_loop: 1,
+            schedule_start,
+            schedule_end: None,
+            overlays: Json(Vec::new()),
+            trim_start: None,
+            trim_end: None,
+            resume: false,
+            resume_position: None,
+            record: false,
+            preview: false,
+            dry_run: false,
+            destination: Json(Destination::Rtmp {
+                url: String::from("rtmp://127.0.0.1:1935/live"),
+                stream_key: String::from("key"),
+                backup_urls: Vec::new(),
+                redundant: false
+            }),
+            ingest_protocol: None,
+            ingest_port: None,
+            ingest_key: None
         }
+    }
 
-        let _ = child.wait().await;
+    fn status(state: &LiveStreamState, stream_id: i64) -> Option<StreamStatus> {
+        state.jobs.get(&stream_id).map(|job| job.status.clone())
     }
 
-    write_history(state, stream_id, &pool, final_status).await;
-}
\ No newline at end of file
+    fn every_second(seconds: i64) -> Vec<(Duration, RunnerEvent)> {
+        (1..=seconds).map(|second| (Duration::from_secs(1), progress(second))).collect()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn scheduled_stream_goes_live_when_it_fires() {
+        let runner = Arc::new(ScriptedRunner::new(vec![Script::Run(every_second(60))]));
+        let state = scripted_state(runner.clone());
+        let mut events = state.events.subscribe();
+        let start_at = current_unix_timestamp() as i64 + 60;
+
+        start_stream(&live_stream(101, Some(start_at)), &state, &pool()).await.unwrap();
+        tokio::time::sleep(Duration::from_secs(30)).await;
+
+        assert!(matches!(status(&state, 101), Some(StreamStatus::Scheduled)));
+        assert_eq!(runner.log().spawns, 0);
+
+        tokio::time::sleep(Duration::from_secs(35)).await;
+
+        assert!(matches!(status(&state, 101), Some(StreamStatus::Live)));
+        assert_eq!((runner.log().pipelines, runner.log().spawns), (1, 1));
+        assert!(state.jobs.get(&101).unwrap().progress.out_time_ms > 0);
+
+        let mut transitions = Vec::new();
+
+        while let Ok(event) = events.try_recv() {
+            transitions.push(format!("{}:{}>{}", event.sequence, event.from, event.to));
+        }
+
+        assert_eq!(transitions, ["0:offline>scheduled", "1:scheduled>starting", "2:starting>live"]);
+        assert_eq!(state.jobs.get(&101).unwrap().events.len(), 3);
+        assert!(!transition_stream(&state, 101, StreamStatus::Queued));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn edited_schedule_moves_the_timers() {
+        let runner = Arc::new(ScriptedRunner::new(vec![Script::Run(every_second(600))]));
+        let state = scripted_state(runner.clone());
+        let now = current_unix_timestamp() as i64;
+
+        start_stream(&live_stream(107, Some(now + 60)), &state, &pool()).await.unwrap();
+        tokio::time::sleep(Duration::from_secs(30)).await;
+
+        assert!(reschedule_stream(&state, 107, Some(now + 200), None));
+
+        tokio::time::sleep(Duration::from_secs(60)).await;
+
+        assert!(matches!(status(&state, 107), Some(StreamStatus::Scheduled)));
+        assert_eq!(runner.log().spawns, 0);
+
+        tokio::time::sleep(Duration::from_secs(150)).await;
+
+        assert!(matches!(status(&state, 107), Some(StreamStatus::Live)));
+
+        // An end edited into the past ends the live stream right away.
+        assert!(reschedule_stream(&state, 107, Some(now + 200), Some(now - 1)));
+
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        assert!(state.jobs.get(&107).is_none());
+        assert_eq!(runner.log().stops, [true]);
+        assert!(!reschedule_stream(&state, 107, None, None));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cancelled_schedule_never_spawns() {
+        let runner = Arc::new(ScriptedRunner::default());
+        let state = scripted_state(runner.clone());
+        let pool = pool();
+        let start_at = current_unix_timestamp() as i64 + 60;
+
+        start_stream(&live_stream(102, Some(start_at)), &state, &pool).await.unwrap();
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert!(stop_stream_internal(&state, 102, StreamStatus::Cancelled, &pool).await);
+        tokio::time::sleep(Duration::from_secs(120)).await;
+
+        assert!(state.jobs.get(&102).is_none());
+        assert!(!stop_stream_internal(&state, 102, StreamStatus::Cancelled, &pool).await);
+        assert_eq!((runner.log().pipelines, runner.log().spawns), (0, 0));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn output_errors_fail_the_stream() {
+        let mut events = every_second(3);
+
+        events.push((Duration::from_secs(1), RunnerEvent::Error(String::from("Connection reset by peer"))));
+
+        let runner = Arc::new(ScriptedRunner::new(vec![Script::Run(events)]));
+        let state = scripted_state(runner.clone());
+
+        start_stream(&live_stream(103, None), &state, &pool()).await.unwrap();
+        tokio::time::sleep(Duration::from_secs(2)).await;
+
+        assert!(matches!(status(&state, 103), Some(StreamStatus::Live)));
+
+        tokio::time::sleep(Duration::from_secs(5)).await;
+
+        assert!(state.jobs.get(&103).is_none());
+        assert_eq!(runner.log().stops, [true]);
+
+        let runner = Arc::new(ScriptedRunner::new(vec![Script::Refuse]));
+        let state = scripted_state(runner.clone());
+
+        start_stream(&live_stream(104, None), &state, &pool()).await.unwrap();
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        assert!(state.jobs.get(&104).is_none());
+        assert_eq!((runner.log().pipelines, runner.log().spawns), (1, 1));
+        assert!(runner.log().stops.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stalled_output_is_restarted_until_it_recovers() {
+        let runner = Arc::new(ScriptedRunner::new(vec![
+            Script::Run(Vec::new()),
+            Script::Run(every_second(120))
+        ]));
+        let state = scripted_state(runner.clone());
+
+        start_stream(&live_stream(105, None), &state, &pool()).await.unwrap();
+        tokio::time::sleep(Duration::from_secs(40)).await;
+
+        let log = runner.log();
+
+        assert_eq!(state.jobs.get(&105).unwrap().restart_count, 1);
+        assert_eq!(state.jobs.get(&105).unwrap().ffmpeg_restarts, 1);
+        assert_eq!(state.metrics.ffmpeg_restarts_total.load(Ordering::Relaxed), 1);
+        assert_eq!((log.spawns, log.feeder_restarts), (2, 1));
+        assert_eq!(log.stops, [false]);
+
+        tokio::time::sleep(Duration::from_secs(20)).await;
+
+        assert!(matches!(status(&state, 105), Some(StreamStatus::Live)));
+        assert_eq!(runner.log().spawns, 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stream_fails_after_the_last_restart() {
+        let runner = Arc::new(ScriptedRunner::default());
+        let state = scripted_state(runner.clone());
+
+        start_stream(&live_stream(106, None), &state, &pool()).await.unwrap();
+        tokio::time::sleep(Duration::from_secs(40)).await;
+
+        assert!(matches!(status(&state, 106), Some(StreamStatus::Degraded)));
+
+        tokio::time::sleep(Duration::from_secs(300)).await;
+
+        assert!(state.jobs.get(&106).is_none());
+        assert_eq!(runner.log().spawns, 4);
+        assert_eq!(runner.log().stops, [false, false, false, true]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn restarted_schedule_keeps_the_new_job() {
+        let runner = Arc::new(ScriptedRunner::new(vec![Script::Run(every_second(120))]));
+        let state = scripted_state(runner.clone());
+        let start_at = current_unix_timestamp() as i64 + 60;
+
+        start_stream(&live_stream(110, Some(start_at)), &state, &pool()).await.unwrap();
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        assert!(end_job_for_restart(&state, 110, &pool()).await);
+        assert!(state.jobs.get(&110).is_none());
+
+        start_stream(&live_stream(110, Some(start_at)), &state, &pool()).await.unwrap();
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        assert!(matches!(status(&state, 110), Some(StreamStatus::Scheduled)));
+
+        tokio::time::sleep(Duration::from_secs(65)).await;
+
+        assert!(matches!(status(&state, 110), Some(StreamStatus::Live)));
+        assert_eq!(runner.log().spawns, 1);
+        assert!(!end_job_for_restart(&state, 111, &pool()).await);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn dry_runs_leave_the_bit_rate_to_real_streams() {
+        let state = scripted_state(Arc::new(ScriptedRunner::default()));
+        let config = AdmissionConfig {
+            max_concurrent_streams: 2,
+            max_streams_per_user: 0,
+            max_total_bit_rate: 3000,
+            min_free_cpu_percent: 0,
+            min_free_memory_mb: 0,
+            policy: AdmissionPolicy::Reject
+        };
+
+        for id in [112, 113, 114] {
+            let start_at = current_unix_timestamp() as i64 + 600;
+
+            start_stream(&live_stream(id, Some(start_at)), &state, &pool()).await.unwrap();
+            state.jobs.get_mut(&id).unwrap().bit_rate = 2000;
+        }
+
+        state.jobs.get_mut(&112).unwrap().dry_run = true;
+
+        assert!(try_admit(&state, &config, 112).is_ok());
+        assert!(try_admit(&state, &config, 113).is_ok());
+        // Still a slot of its own.
+        assert!(try_admit(&state, &config, 114).is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn shutdown_leaves_upcoming_streams_alone() {
+        let runner = Arc::new(ScriptedRunner::new(vec![Script::Run(every_second(600))]));
+        let state = scripted_state(runner.clone());
+        let config = ShutdownConfig { policy: ShutdownPolicy::Stop, drain_timeout: Duration::from_secs(30) };
+        let start_at = current_unix_timestamp() as i64 + 600;
+
+        start_stream(&live_stream(115, None), &state, &pool()).await.unwrap();
+        start_stream(&live_stream(116, Some(start_at)), &state, &pool()).await.unwrap();
+        tokio::time::sleep(Duration::from_secs(5)).await;
+
+        assert!(matches!(status(&state, 115), Some(StreamStatus::Live)));
+        assert!(matches!(status(&state, 116), Some(StreamStatus::Scheduled)));
+
+        let mut events = state.events.subscribe();
+
+        shutdown_streams(&state, &config, &pool()).await;
+
+        let mut transitions = Vec::new();
+
+        while let Ok(event) = events.try_recv() {
+            transitions.push(format!("{}:{}>{}", event.stream_id, event.from, event.to));
+        }
+
+        // Only a transition empties the schedule or writes history.
+        assert_eq!(transitions, ["115:live>interrupted"]);
+        assert!(state.jobs.is_empty());
+
+        tokio::time::sleep(Duration::from_secs(700)).await;
+
+        assert_eq!(runner.log().spawns, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn only_waiting_jobs_are_admitted() {
+        let state = scripted_state(Arc::new(ScriptedRunner::default()));
+        let config = AdmissionConfig::from_env();
+
+        assert!(try_admit(&state, &config, 109).is_err());
+
+        start_stream(&live_stream(109, None), &state, &pool()).await.unwrap();
+
+        assert!(try_admit(&state, &config, 109).is_ok());
+
+        transition_stream(&state, 109, StreamStatus::Failed(String::from("gone")));
+
+        assert!(try_admit(&state, &config, 109).is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn pipeline_gives_up_when_every_item_fails() {
+        let runner = Arc::new(ScriptedRunner::new(vec![
+            Script::Run(vec![(Duration::from_secs(10), RunnerEvent::Finished)])
+        ]));
+        let state = scripted_state(runner.clone());
+        let mut events = state.events.subscribe();
+
+        start_stream(&live_stream(108, None), &state, &pool()).await.unwrap();
+
+        // Missing files fail whether or not ffmpeg is installed.
+        let missing = SourceItem::Video { file: String::from("/nonexistent/streamtfhd.mp4"), start: 0, end: None };
+        let (_commands, receiver) = mpsc::unbounded_channel();
+        let pipeline = run_pipeline(
+            state.clone(),
+            108,
+            create_pipeline(108).unwrap(),
+            vec![missing.clone(), missing],
+            None,
+            None,
+            receiver
+        );
+
+        tokio::time::timeout(Duration::from_secs(5), pipeline).await.unwrap();
+        tokio::time::sleep(Duration::from_secs(10)).await;
+
+        let failure = std::iter::from_fn(|| events.try_recv().ok()).find(|event| event.to == "failed");
+
+        assert!(state.jobs.get(&108).is_none());
+        assert_eq!(failure.and_then(|event| event.reason).as_deref(), Some("Every item of the playlist failed to play."));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn healthy_output_earns_its_restarts_back() {
+        // Every output stalls after a healthy stretch longer than the reset.
+        let runner = Arc::new(ScriptedRunner::new(
+            (0..6).map(|_| Script::Run(every_second(400))).collect()
+        ));
+        let state = scripted_state(runner.clone());
+
+        start_stream(&live_stream(107, None), &state, &pool()).await.unwrap();
+        tokio::time::sleep(Duration::from_secs(5 * 440)).await;
+
+        assert!(matches!(status(&state, 107), Some(StreamStatus::Live | StreamStatus::Degraded)));
+        assert!(runner.log().spawns >= 5);
+        assert!(state.jobs.get(&107).unwrap().restart_count <= 1);
+    }
+}
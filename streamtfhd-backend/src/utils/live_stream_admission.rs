@@ -0,0 +1,157 @@
+use std::env::var;
+use tracing::warn;
+
+use crate::dto::live_stream_state::{LiveStreamState, StreamStatus};
+use crate::utils::live_stream_event::transition_stream;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdmissionPolicy {
+    Reject,
+    Queue
+}
+
+// Limits applied before a live stream spawns ffmpeg. A zero value means no limit.
+#[derive(Debug, Clone)]
+pub struct AdmissionConfig {
+    pub max_concurrent_streams: u64,
+    pub max_streams_per_user: u64,
+    pub max_total_bit_rate: u64,       // In kbps
+    pub min_free_cpu_percent: u64,
+    pub min_free_memory_mb: u64,
+    pub policy: AdmissionPolicy
+}
+
+fn limit_from_env(key: &str) -> u64 {
+    match var(key) {
+        Ok(val) if !val.trim().is_empty() => match val.trim().parse::<u64>() {
+            Ok(val) => val,
+            Err(_) => {
+                warn!("Invalid {} value in env file, the limit is disabled.", key);
+
+                0
+            }
+        },
+        _ => 0
+    }
+}
+
+impl AdmissionConfig {
+    pub fn from_env() -> Self {
+        let policy = match var("ADMISSION_POLICY") {
+            Ok(val) if val.trim().eq_ignore_ascii_case("queue") => AdmissionPolicy::Queue,
+            _ => AdmissionPolicy::Reject
+        };
+
+        AdmissionConfig {
+            max_concurrent_streams: limit_from_env("MAX_CONCURRENT_STREAMS"),
+            max_streams_per_user: limit_from_env("MAX_STREAMS_PER_USER"),
+            max_total_bit_rate: limit_from_env("MAX_TOTAL_BIT_RATE"),
+            min_free_cpu_percent: limit_from_env("MIN_FREE_CPU_PERCENT"),
+            min_free_memory_mb: limit_from_env("MIN_FREE_MEMORY_MB"),
+            policy
+        }
+    }
+}
+
+// A job holds an admission slot from the moment it is admitted until it is finalized.
+pub fn is_running(status: &StreamStatus) -> bool {
+    matches!(status, StreamStatus::Starting | StreamStatus::Live | StreamStatus::Degraded)
+}
+
+fn check_limits(
+    state: &LiveStreamState,
+    config: &AdmissionConfig,
+    stream_id: i64
+) -> Result<(), String> {
+    // Dry runs encode like any other job and hold a slot, but their output
+    // stays on this machine and takes nothing from the outgoing bit rate.
+    let outgoing = |bit_rate: i64, dry_run: bool| match dry_run {
+        true => 0,
+        false => bit_rate.max(0) as u64
+    };
+    let (owner, bit_rate) = match state.jobs.get(&stream_id) {
+        Some(job) => (job.owner.clone(), outgoing(job.bit_rate, job.dry_run)),
+        None => return Err(String::from("Live stream job no longer exists"))
+    };
+    let mut running: u64 = 0;
+    let mut running_for_owner: u64 = 0;
+    let mut total_bit_rate: u64 = bit_rate;
+
+    for entry in &state.jobs {
+        let job = entry.value();
+
+        if job.id == stream_id || !is_running(&job.status) {
+            continue;
+        }
+
+        running += 1;
+        total_bit_rate += outgoing(job.bit_rate, job.dry_run);
+
+        if job.owner == owner {
+            running_for_owner += 1;
+        }
+    }
+
+    if config.max_concurrent_streams > 0 && running >= config.max_concurrent_streams {
+        return Err(format!("Maximum of {} concurrent live streams reached", config.max_concurrent_streams));
+    }
+
+    if config.max_streams_per_user > 0 && running_for_owner >= config.max_streams_per_user {
+        return Err(format!("Maximum of {} concurrent live streams per user reached", config.max_streams_per_user));
+    }
+
+    if config.max_total_bit_rate > 0 && total_bit_rate > config.max_total_bit_rate {
+        return Err(format!("Total outgoing bit rate would exceed {} kbps", config.max_total_bit_rate));
+    }
+
+    if config.min_free_cpu_percent == 0 && config.min_free_memory_mb == 0 {
+        return Ok(());
+    }
+
+    let host = match state.metrics.host.read() {
+        Ok(val) => val.clone(),
+        Err(_) => None
+    };
+
+    // No sample collected yet, nothing to compare against.
+    let host = match host {
+        Some(val) => val,
+        None => return Ok(())
+    };
+
+    let free_cpu = (100.0 - host.cpu).max(0.0);
+
+    if config.min_free_cpu_percent > 0 && free_cpu < config.min_free_cpu_percent as f32 {
+        return Err(format!("Not enough CPU headroom, {:.0}% free but {}% required", free_cpu, config.min_free_cpu_percent));
+    }
+
+    let free_memory_mb = host.total_memory.saturating_sub(host.used_memory) / 1024;
+
+    if config.min_free_memory_mb > 0 && free_memory_mb < config.min_free_memory_mb {
+        return Err(format!("Not enough free memory, {} MB free but {} MB required", free_memory_mb, config.min_free_memory_mb));
+    }
+
+    Ok(())
+}
+
+// Checks the limits and marks the job as starting while holding the admission
+// lock, so two jobs can not be admitted into the same free slot. A job that is
+// gone or already finalized is never admitted.
+pub fn try_admit(
+    state: &LiveStreamState,
+    config: &AdmissionConfig,
+    stream_id: i64
+) -> Result<(), String> {
+    let _guard = match state.admission_lock.lock() {
+        Ok(val) => val,
+        Err(poisoned) => poisoned.into_inner()
+    };
+
+    check_limits(state, config, stream_id)?;
+
+    if !transition_stream(state, stream_id, StreamStatus::Starting) {
+        return Err(String::from("The live stream is no longer waiting to start"));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,459 @@
+use crate::{
+    dto::live_stream_destination::{Destination, DestinationOutput, PushTarget},
+    errors::AppError
+};
+
+const MAX_BACKUP_URLS: usize = 3;
+
+// Characters a passphrase can use without being escaped in the SRT URL.
+fn is_url_safe(value: &str) -> bool {
+    value.chars().all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.' | '~'))
+}
+
+fn is_valid_host(host: &str) -> bool {
+    !host.is_empty() && host.len() <= 253 && host.chars().all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '.'))
+}
+
+fn check_url(url: &str, schemes: &[&str]) -> Result<(), String> {
+    let rest = schemes.iter().find_map(|scheme| url.strip_prefix(scheme));
+
+    match rest {
+        Some(rest) if !rest.is_empty() && !rest.chars().any(|ch| ch.is_whitespace()) => Ok(()),
+        _ => Err(format!("Destination URL must start with {}.", schemes.join(" or ")))
+    }
+}
+
+fn check_target(target: &PushTarget, extension: &str) -> Result<(), String> {
+    match target {
+        PushTarget::Http { url } => {
+            check_url(url, &["http://", "https://"])?;
+
+            if !url.ends_with(extension) {
+                return Err(format!("Destination URL must end with {}.", extension));
+            }
+        }
+        PushTarget::Directory { name } => {
+            if name.is_empty() || name.len() > 64 || !name.chars().all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_')) {
+                return Err("Directory name must be 1 to 64 letters, digits, - or _.".to_string());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn check_segments(segment_seconds: u32, playlist_size: u32) -> Result<(), String> {
+    if !(1..=30).contains(&segment_seconds) {
+        return Err("Segment length must be between 1 and 30 secs.".to_string());
+    }
+
+    if !(2..=60).contains(&playlist_size) {
+        return Err("Playlist size must be between 2 and 60 segments.".to_string());
+    }
+
+    Ok(())
+}
+
+fn check_rtmp(
+    url: &str,
+    stream_key: &str,
+    backup_urls: &[String],
+    redundant: bool,
+    scheme: &str
+) -> Result<(), String> {
+    check_url(url, &[scheme])?;
+
+    if stream_key.chars().any(|ch| ch.is_whitespace()) {
+        return Err("Stream key can not contain spaces.".to_string());
+    }
+
+    if backup_urls.len() > MAX_BACKUP_URLS {
+        return Err(format!("A destination can have at most {} backup URLs.", MAX_BACKUP_URLS));
+    }
+
+    for (index, backup) in backup_urls.iter().enumerate() {
+        check_url(backup, &[scheme])?;
+
+        if backup == url || backup_urls[..index].contains(backup) {
+            return Err("Backup URLs must differ from each other and from the URL.".to_string());
+        }
+    }
+
+    if redundant && backup_urls.is_empty() {
+        return Err("Redundant mode needs at least one backup URL.".to_string());
+    }
+
+    Ok(())
+}
+
+// Checks the fields of a destination, without connecting to it.
+pub fn check_destination(destination: &Destination) -> Result<(), String> {
+    match destination {
+        Destination::Rtmp { url, stream_key, backup_urls, redundant } => {
+            check_rtmp(url, stream_key, backup_urls, *redundant, "rtmp://")?;
+        }
+        Destination::Rtmps { url, stream_key, backup_urls, redundant } => {
+            check_rtmp(url, stream_key, backup_urls, *redundant, "rtmps://")?;
+        }
+        Destination::Srt { host, port, stream_id, passphrase, latency_ms } => {
+            if !is_valid_host(host) {
+                return Err("Invalid SRT host.".to_string());
+            }
+
+            if *port == 0 {
+                return Err("Invalid SRT port.".to_string());
+            }
+
+            if let Some(stream_id) = stream_id && stream_id.len() > 512 {
+                return Err("SRT stream ID must be at most 512 characters.".to_string());
+            }
+
+            if let Some(passphrase) = passphrase && (!(10..=79).contains(&passphrase.len()) || !is_url_safe(passphrase)) {
+                return Err("SRT passphrase must be 10 to 79 letters, digits, -, _, . or ~.".to_string());
+            }
+
+            if !(20..=60000).contains(latency_ms) {
+                return Err("SRT latency must be between 20 and 60000 ms.".to_string());
+            }
+        }
+        Destination::Hls { target, segment_seconds, playlist_size } => {
+            check_target(target, ".m3u8")?;
+            check_segments(*segment_seconds, *playlist_size)?;
+        }
+        Destination::Dash { target, segment_seconds, window_size } => {
+            check_target(target, ".mpd")?;
+            check_segments(*segment_seconds, *window_size)?;
+        }
+    }
+
+    Ok(())
+}
+
+// The destination of live streams made before there were kinds.
+pub fn rtmp_destination(url: &str, stream_key: &str) -> Destination {
+    Destination::Rtmp {
+        url: url.to_string(),
+        stream_key: stream_key.to_string(),
+        backup_urls: Vec::new(),
+        redundant: false
+    }
+}
+
+// The destination of a live stream being created or edited, built from
+// `rtmp_url` and `stream_key` when the request has no `destination`.
+pub fn resolve_destination(
+    destination: &Option<Destination>,
+    rtmp_url: &Option<String>,
+    stream_key: &Option<String>
+) -> Result<Destination, AppError> {
+    let destination = match destination {
+        Some(val) => val.clone(),
+        None => rtmp_destination(
+            rtmp_url.as_deref().unwrap_or_default(),
+            stream_key.as_deref().unwrap_or_default()
+        )
+    };
+
+    if let Err(err) = check_destination(&destination) {
+        return Err(AppError::ValidationError(err));
+    }
+
+    Ok(destination)
+}
+
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::new();
+
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+
+    encoded
+}
+
+fn option(key: &str, value: impl ToString) -> (String, String) {
+    (key.to_string(), value.to_string())
+}
+
+fn rtmp_output(url: &str, stream_key: &str) -> DestinationOutput {
+    let target = match stream_key.is_empty() {
+        true => url.to_string(),
+        false => format!("{}/{}", url.trim_end_matches('/'), stream_key)
+    };
+
+    DestinationOutput {
+        format: String::from("flv"),
+        options: Vec::new(),
+        target,
+        local_dir: None
+    }
+}
+
+pub fn destination_output(destination: &Destination, stream_id: i64, upload_dir: &str) -> DestinationOutput {
+    let push_target = |target: &PushTarget, file: &str, options: &mut Vec<(String, String)>| match target {
+        PushTarget::Http { url } => {
+            options.push(option("method", "PUT"));
+            options.push(option("http_persistent", 1));
+
+            (url.clone(), None)
+        }
+        PushTarget::Directory { name } => {
+            // Names are picked by users, the live stream keeps them apart.
+            let dir = format!("{}/live/{}/{}", upload_dir, stream_id, name);
+
+            (format!("{}/{}", dir, file), Some(dir))
+        }
+    };
+
+    match destination {
+        Destination::Rtmp { url, stream_key, .. } | Destination::Rtmps { url, stream_key, .. } => {
+            rtmp_output(url, stream_key)
+        }
+        Destination::Srt { host, port, stream_id, passphrase, latency_ms } => {
+            // ffmpeg takes the SRT latency in microseconds.
+            let mut target = format!("srt://{}:{}?mode=caller&latency={}", host, port, *latency_ms as u64 * 1000);
+
+            if let Some(passphrase) = passphrase {
+                target.push_str(&format!("&passphrase={}", passphrase));
+            }
+
+            if let Some(stream_id) = stream_id {
+                target.push_str(&format!("&streamid={}", percent_encode(stream_id)));
+            }
+
+            DestinationOutput {
+                format: String::from("mpegts"),
+                options: Vec::new(),
+                target,
+                local_dir: None
+            }
+        }
+        Destination::Hls { target, segment_seconds, playlist_size } => {
+            let mut options = vec![
+                option("hls_time", segment_seconds),
+                option("hls_list_size", playlist_size),
+                option("hls_flags", "delete_segments+independent_segments")
+            ];
+            let (target, local_dir) = push_target(target, "index.m3u8", &mut options);
+
+            if let Some(dir) = &local_dir {
+                options.push(option("hls_segment_filename", format!("{}/segment_%05d.ts", dir)));
+            }
+
+            DestinationOutput {
+                format: String::from("hls"),
+                options,
+                target,
+                local_dir
+            }
+        }
+        Destination::Dash { target, segment_seconds, window_size } => {
+            let mut options = vec![
+                option("seg_duration", segment_seconds),
+                option("window_size", window_size),
+                option("use_template", 1),
+                option("use_timeline", 1)
+            ];
+            let (target, local_dir) = push_target(target, "manifest.mpd", &mut options);
+
+            DestinationOutput {
+                format: String::from("dash"),
+                options,
+                target,
+                local_dir
+            }
+        }
+    }
+}
+
+// One output per endpoint of the destination, primary first.
+pub fn destination_outputs(destination: &Destination, stream_id: i64, upload_dir: &str) -> Vec<DestinationOutput> {
+    match destination {
+        Destination::Rtmp { url, stream_key, backup_urls, .. } | Destination::Rtmps { url, stream_key, backup_urls, .. } => {
+            std::iter::once(url)
+                .chain(backup_urls)
+                .map(|url| rtmp_output(url, stream_key))
+                .collect()
+        }
+        _ => vec![destination_output(destination, stream_id, upload_dir)]
+    }
+}
+
+// `-opt value` arguments and target of an output that is not teed.
+pub fn output_args(output: &DestinationOutput) -> Vec<String> {
+    let mut args = Vec::new();
+
+    for (key, value) in &output.options {
+        args.push(format!("-{}", key));
+        args.push(value.clone());
+    }
+
+    args.extend(["-f".to_string(), output.format.clone(), output.target.clone()]);
+    args
+}
+
+// The tee muxer splits its outputs on `|` and then the options of each on
+// `:`, both taking backslash escapes.
+fn escape_tee(value: &str, special: &[char]) -> String {
+    let mut escaped = String::new();
+
+    for ch in value.chars() {
+        if ch == '\\' || ch == '\'' || special.contains(&ch) {
+            escaped.push('\\');
+        }
+
+        escaped.push(ch);
+    }
+
+    escaped
+}
+
+// One output of the tee muxer. `onfail=ignore` keeps the other outputs going
+// when this one fails.
+pub fn tee_slave(output: &DestinationOutput, ignore_failure: bool) -> String {
+    let mut options = vec![format!("f={}", output.format)];
+
+    for (key, value) in &output.options {
+        options.push(format!("{}={}", key, escape_tee(value, &[':'])));
+    }
+
+    // Taken care of by the muxer when ffmpeg writes straight to it.
+    if matches!(output.format.as_str(), "flv" | "dash" | "segment") {
+        options.push("bsfs/a=aac_adtstoasc".to_string());
+    }
+
+    options.push(format!("onfail={}", if ignore_failure { "ignore" } else { "abort" }));
+
+    escape_tee(&format!("[{}]{}", options.join(":"), output.target), &['|'])
+}
+
+// Index of the tee output that failed, from the line ffmpeg logs when it
+// carries on without it. Outputs that abort end ffmpeg instead.
+pub fn failed_tee_slave(log_line: &str) -> Option<usize> {
+    let rest = log_line.split_once("Slave muxer #")?.1;
+    let (index, rest) = rest.split_once(' ')?;
+
+    if !rest.starts_with("failed") || !rest.contains("continuing with") {
+        return None;
+    }
+
+    index.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn destinations_are_validated() {
+        assert!(check_destination(&rtmp_destination("rtmp://a.rtmp.youtube.com/live2", "abcd-1234")).is_ok());
+        assert!(check_destination(&rtmp_destination("http://example.com", "abcd")).is_err());
+        assert!(check_destination(&Destination::Srt {
+            host: String::from("ingest.example.com"),
+            port: 9000,
+            stream_id: Some(String::from("#!::r=live/abc,m=publish")),
+            passphrase: Some(String::from("short")),
+            latency_ms: 2000
+        }).is_err());
+        assert!(check_destination(&Destination::Hls {
+            target: PushTarget::Directory { name: String::from("../etc") },
+            segment_seconds: 4,
+            playlist_size: 6
+        }).is_err());
+        assert!(check_destination(&Destination::Dash {
+            target: PushTarget::Http { url: String::from("https://cdn.example.com/live/manifest.mpd") },
+            segment_seconds: 4,
+            window_size: 6
+        }).is_ok());
+    }
+
+    #[test]
+    fn backup_urls_share_the_scheme_and_key() {
+        let destination = Destination::Rtmp {
+            url: String::from("rtmp://a.rtmp.youtube.com/live2"),
+            stream_key: String::from("abcd-1234"),
+            backup_urls: vec![String::from("rtmp://b.rtmp.youtube.com/live2?backup=1")],
+            redundant: true
+        };
+        let outputs = destination_outputs(&destination, 7, "/uploads");
+
+        assert!(check_destination(&destination).is_ok());
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(outputs[1].target, "rtmp://b.rtmp.youtube.com/live2?backup=1/abcd-1234");
+        assert_eq!(destination.endpoints()[1], "rtmp://b.rtmp.youtube.com/live2?backup=1");
+        assert!(check_destination(&Destination::Rtmp {
+            url: String::from("rtmp://a.rtmp.youtube.com/live2"),
+            stream_key: String::from("abcd-1234"),
+            backup_urls: vec![String::from("rtmps://b.rtmp.youtube.com/live2")],
+            redundant: false
+        }).is_err());
+        assert!(check_destination(&Destination::Rtmp {
+            url: String::from("rtmp://a.rtmp.youtube.com/live2"),
+            stream_key: String::from("abcd-1234"),
+            backup_urls: Vec::new(),
+            redundant: true
+        }).is_err());
+    }
+
+    #[test]
+    fn failed_tee_slaves_are_read_from_the_log() {
+        assert_eq!(
+            failed_tee_slave("[tee @ 0x55] Slave muxer #1 failed: Broken pipe, continuing with 1/2 slaves."),
+            Some(1)
+        );
+        assert_eq!(failed_tee_slave("[tee @ 0x55] Slave muxer #0 failed, aborting."), None);
+        assert_eq!(failed_tee_slave("[tee @ 0x55] Slave '[f=flv]rtmp://a/b': error writing frame"), None);
+    }
+
+    #[test]
+    fn srt_output_uses_caller_mode() {
+        let output = destination_output(&Destination::Srt {
+            host: String::from("ingest.example.com"),
+            port: 9000,
+            stream_id: Some(String::from("live/abc")),
+            passphrase: Some(String::from("0123456789")),
+            latency_ms: 2000
+        }, 7, "/uploads");
+
+        assert_eq!(output.format, "mpegts");
+        assert_eq!(
+            output.target,
+            "srt://ingest.example.com:9000?mode=caller&latency=2000000&passphrase=0123456789&streamid=live%2Fabc"
+        );
+    }
+
+    #[test]
+    fn local_hls_is_written_under_the_upload_directory() {
+        let output = destination_output(&Destination::Hls {
+            target: PushTarget::Directory { name: String::from("main") },
+            segment_seconds: 4,
+            playlist_size: 6
+        }, 7, "/uploads");
+
+        assert_eq!(output.target, "/uploads/live/7/main/index.m3u8");
+        assert_eq!(output.local_dir.as_deref(), Some("/uploads/live/7/main"));
+        assert_eq!(
+            output_args(&output)[..4],
+            ["-hls_time", "4", "-hls_list_size", "6"]
+        );
+    }
+
+    #[test]
+    fn tee_slave_escapes_options() {
+        let output = DestinationOutput {
+            format: String::from("hls"),
+            options: vec![option("hls_segment_filename", "C:/live/segment_%05d.ts")],
+            target: String::from("http://cdn|x/index.m3u8"),
+            local_dir: None
+        };
+
+        assert_eq!(
+            tee_slave(&output, false),
+            "[f=hls:hls_segment_filename=C\\\\:/live/segment_%05d.ts:onfail=abort]http://cdn\\|x/index.m3u8"
+        );
+    }
+}
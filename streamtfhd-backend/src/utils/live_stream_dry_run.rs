@@ -0,0 +1,144 @@
+use std::env::var;
+use std::time::{Duration, SystemTime};
+use tracing::{info, warn};
+
+use crate::{
+    dto::live_stream_destination::DestinationOutput,
+    utils::time::current_unix_timestamp
+};
+
+pub const DRY_RUN_NULL: &str = "null";
+pub const DRY_RUN_FILE: &str = "file";
+const DRY_RUN_SEGMENT_SECONDS: u64 = 60;
+
+#[derive(Debug, Clone)]
+pub struct DryRunConfig {
+    pub sink: String,
+    pub file_minutes: u64,      // Most recent minutes the file sink keeps of a run
+    pub retention: Duration     // Files of older runs are deleted after it
+}
+
+fn number_from_env(key: &str, default: u64) -> u64 {
+    match var(key) {
+        Ok(val) => match val.trim().parse::<u64>() {
+            Ok(val) if val > 0 => val,
+            _ => {
+                warn!("Invalid {} value in env file, using {}.", key, default);
+
+                default
+            }
+        },
+        Err(_) => default
+    }
+}
+
+impl DryRunConfig {
+    pub fn from_env() -> Self {
+        let sink = match var("DRY_RUN_SINK") {
+            Ok(val) if val.trim().eq_ignore_ascii_case(DRY_RUN_FILE) => String::from(DRY_RUN_FILE),
+            _ => String::from(DRY_RUN_NULL)
+        };
+
+        DryRunConfig {
+            sink,
+            file_minutes: number_from_env("DRY_RUN_FILE_MINUTES", 10),
+            retention: Duration::from_secs(number_from_env("DRY_RUN_RETENTION_HOURS", 24) * 3600)
+        }
+    }
+}
+
+// Output a dry run sends to instead of its destination. The file sink writes
+// one minute MPEG-TS segments under the upload directory and wraps around
+// after `file_minutes` of them, so a looped rehearsal can not fill the disk.
+pub fn dry_run_output(
+    stream_id: i64,
+    upload_dir: &str,
+    config: &DryRunConfig
+) -> DestinationOutput {
+    match config.sink.as_str() {
+        DRY_RUN_FILE => {
+            let dir = format!("{}/dry_runs", upload_dir);
+
+            DestinationOutput {
+                format: String::from("segment"),
+                options: vec![
+                    (String::from("segment_time"), DRY_RUN_SEGMENT_SECONDS.to_string()),
+                    (String::from("segment_wrap"), config.file_minutes.to_string()),
+                    (String::from("segment_format"), String::from("mpegts")),
+                    (String::from("reset_timestamps"), String::from("1"))
+                ],
+                target: format!("{}/{}-{}-%03d.ts", dir, stream_id, current_unix_timestamp()),
+                local_dir: Some(dir)
+            }
+        }
+        _ => DestinationOutput {
+            format: String::from("null"),
+            options: Vec::new(),
+            target: String::from("-"),
+            local_dir: None
+        }
+    }
+}
+
+// Deletes the files of dry runs last written longer than `retention` ago.
+pub fn prune_dry_runs(dir: &str, retention: Duration) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(val) => val,
+        Err(_) => return
+    };
+    let cutoff = match SystemTime::now().checked_sub(retention) {
+        Some(val) => val,
+        None => return
+    };
+
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let is_expired = entry
+            .metadata()
+            .and_then(|meta| meta.modified())
+            .is_ok_and(|modified| modified < cutoff);
+
+        if !is_expired {
+            continue;
+        }
+
+        match std::fs::remove_file(entry.path()) {
+            Ok(_) => info!("deleted expired dry run file {}", entry.path().display()),
+            Err(err) => warn!("Failed to delete dry run file {}: {}", entry.path().display(), err)
+        }
+    }
+}
+
+// How the sink is shown in place of the destination endpoints.
+pub fn dry_run_endpoint(output: &DestinationOutput) -> String {
+    match output.format.as_str() {
+        "null" => String::from(DRY_RUN_NULL),
+        _ => output.target.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::live_stream_destination::output_args;
+
+    #[test]
+    fn dry_runs_never_leave_the_machine() {
+        let config = |sink: &str| DryRunConfig {
+            sink: String::from(sink),
+            file_minutes: 10,
+            retention: Duration::from_secs(3600)
+        };
+        let null = dry_run_output(7, "/uploads", &config(DRY_RUN_NULL));
+
+        assert_eq!(output_args(&null), ["-f", "null", "-"]);
+        assert_eq!(dry_run_endpoint(&null), "null");
+
+        let file = dry_run_output(7, "/uploads", &config(DRY_RUN_FILE));
+        let args = output_args(&file);
+
+        assert_eq!(file.local_dir.as_deref(), Some("/uploads/dry_runs"));
+        assert!(file.target.starts_with("/uploads/dry_runs/7-"));
+        assert!(args.windows(2).any(|pair| pair == ["-segment_wrap", "10"]));
+        assert_eq!(dry_run_endpoint(&file), file.target);
+    }
+}
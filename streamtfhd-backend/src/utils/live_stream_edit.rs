@@ -0,0 +1,133 @@
+use sqlx::types::Json;
+
+use crate::{
+    dto::live_stream_destination::Destination,
+    dto::live_stream_edit_stream_post::{LiveStream, StreamSettings},
+    dto::live_stream_source::{LiveStreamSource, MODE_INGEST}
+};
+
+// Settings of the live stream once the edit is stored.
+pub fn edited_settings(
+    data: &LiveStream,
+    source: &LiveStreamSource,
+    destination: &Destination
+) -> StreamSettings {
+    StreamSettings {
+        mode: source.mode.clone(),
+        video: source.video,
+        background_image: source.background_image,
+        audios: source.audios.clone(),
+        slate_video: source.slate_video,
+        slate_image: source.slate_image,
+        slate_audio: source.slate_audio,
+        stream_loop: source.stream_loop,
+        trim_start: source.trim_start,
+        trim_end: source.trim_end,
+        record: data.record.unwrap_or(false),
+        preview: data.preview.unwrap_or(false),
+        dry_run: data.dry_run.unwrap_or(false),
+        destination: Json(destination.clone()),
+        ingest_protocol: source.ingest_protocol.clone()
+    }
+}
+
+// Names of the changed settings a job can not pick up while it exists. The
+// title, schedule and overlays are applied to the job as it is.
+pub fn restart_changes(
+    current: &StreamSettings,
+    edited: &StreamSettings,
+    reset_ingest_key: bool
+) -> Vec<&'static str> {
+    let mut changes = Vec::new();
+
+    if current.mode != edited.mode {
+        changes.push("mode");
+    }
+
+    if current.video != edited.video
+        || current.background_image != edited.background_image
+        || current.audios != edited.audios {
+        changes.push("source");
+    }
+
+    if current.slate_video != edited.slate_video
+        || current.slate_image != edited.slate_image
+        || current.slate_audio != edited.slate_audio {
+        changes.push("slate");
+    }
+
+    if current.stream_loop != edited.stream_loop {
+        changes.push("loop");
+    }
+
+    if current.trim_start != edited.trim_start || current.trim_end != edited.trim_end {
+        changes.push("trim");
+    }
+
+    if current.destination != edited.destination {
+        changes.push("destination");
+    }
+
+    if current.ingest_protocol != edited.ingest_protocol || (reset_ingest_key && edited.mode == MODE_INGEST) {
+        changes.push("ingest");
+    }
+
+    if current.record != edited.record {
+        changes.push("recording");
+    }
+
+    if current.preview != edited.preview {
+        changes.push("preview");
+    }
+
+    if current.dry_run != edited.dry_run {
+        changes.push("dry run");
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dto::live_stream_source::MODE_VIDEO;
+
+    fn settings(video: i64, stream_key: &str) -> StreamSettings {
+        StreamSettings {
+            mode: String::from(MODE_VIDEO),
+            video: Some(video),
+            background_image: None,
+            audios: Vec::new(),
+            slate_video: None,
+            slate_image: None,
+            slate_audio: None,
+            stream_loop: 1,
+            trim_start: None,
+            trim_end: None,
+            record: false,
+            preview: false,
+            dry_run: false,
+            destination: Json(Destination::Rtmp {
+                url: String::from("rtmp://a.rtmp.youtube.com/live2"),
+                stream_key: stream_key.to_string(),
+                backup_urls: Vec::new(),
+                redundant: false
+            }),
+            ingest_protocol: None
+        }
+    }
+
+    #[test]
+    fn only_what_the_job_was_built_from_needs_a_restart() {
+        assert!(restart_changes(&settings(1, "key"), &settings(1, "key"), false).is_empty());
+        assert!(restart_changes(&settings(1, "key"), &settings(1, "key"), true).is_empty());
+        assert_eq!(restart_changes(&settings(1, "key"), &settings(2, "other"), false), ["source", "destination"]);
+
+        let mut edited = settings(1, "key");
+
+        edited.preview = true;
+        edited.trim_start = Some(5000);
+
+        assert_eq!(restart_changes(&settings(1, "key"), &edited, false), ["trim", "preview"]);
+    }
+}
@@ -0,0 +1,94 @@
+use tokio::sync::broadcast;
+use tracing::{debug, warn};
+
+use crate::{
+    dto::live_stream_event::StreamEvent,
+    dto::live_stream_state::{LiveStreamState, StreamJob, StreamStatus},
+    utils::time::current_unix_timestamp
+};
+
+// Transitions kept per run. The first one tells how the run began, the rest
+// are the latest ones.
+const MAX_RUN_EVENTS: usize = 200;
+
+// Moves the job to `next`, the only place a job changes status. The
+// transition is added to the log of the run and sent to every subscriber.
+// Returns false when the job may not go there, a job already in `next` is
+// left as it is.
+pub fn transition(
+    job: &mut StreamJob,
+    next: StreamStatus,
+    events: &broadcast::Sender<StreamEvent>
+) -> bool {
+    if !job.status.is_final() && job.status.as_str() == next.as_str() {
+        return true;
+    }
+
+    if !job.status.can_become(&next) {
+        warn!(
+            stream_id = job.id,
+            from = job.status.as_str(),
+            to = next.as_str(),
+            "invalid live stream transition refused"
+        );
+
+        return false;
+    }
+
+    let event = StreamEvent {
+        stream_id: job.id,
+        owner: job.owner.clone(),
+        sequence: job.events.last().map_or(0, |event| event.sequence + 1),
+        from: job.status.as_str().to_string(),
+        to: next.as_str().to_string(),
+        reason: match &next {
+            StreamStatus::Failed(reason) => Some(reason.clone()),
+            _ => None
+        },
+        at: current_unix_timestamp() as i64
+    };
+
+    debug!(stream_id = job.id, from = %event.from, to = %event.to, "live stream transition");
+
+    if job.events.len() >= MAX_RUN_EVENTS {
+        job.events.remove(1);
+    }
+
+    job.status = next;
+    job.events.push(event.clone());
+
+    // Nobody listening is fine.
+    let _ = events.send(event);
+
+    true
+}
+
+// `transition` for callers not holding the job.
+pub fn transition_stream(
+    state: &LiveStreamState,
+    stream_id: i64,
+    next: StreamStatus
+) -> bool {
+    match state.jobs.get_mut(&stream_id) {
+        Some(mut job) => transition(&mut job, next, &state.events),
+        None => false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finished_jobs_never_change() {
+        let failed = StreamStatus::Failed(String::from("Connection reset by peer"));
+
+        assert!(!failed.can_become(&StreamStatus::Live));
+        assert!(!failed.can_become(&StreamStatus::Stopped));
+        assert!(!StreamStatus::Done.can_become(&StreamStatus::Starting));
+        assert!(StreamStatus::Scheduled.can_become(&StreamStatus::Cancelled));
+        assert!(StreamStatus::Degraded.can_become(&StreamStatus::Live));
+        assert!(!StreamStatus::Live.can_become(&StreamStatus::Scheduled));
+        assert!(!StreamStatus::Queued.can_become(&StreamStatus::Live));
+    }
+}
@@ -0,0 +1,56 @@
+use crate::errors::AppError;
+
+pub const MAX_TAGS: usize = 10;
+pub const MAX_TAG_LENGTH: usize = 32;
+
+// Tags group live streams for bulk operations. They are compared in lower
+// case, so "News" and "news" are the same group.
+pub fn normalize_tags(tags: &[String]) -> Result<Vec<String>, AppError> {
+    let mut normalized: Vec<String> = Vec::new();
+
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+
+        if tag.is_empty() {
+            continue;
+        }
+
+        if tag.chars().count() > MAX_TAG_LENGTH {
+            return Err(AppError::ValidationError(format!("Tags can not be longer than {} characters.", MAX_TAG_LENGTH)));
+        }
+
+        if !tag.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == ' ') {
+            return Err(AppError::ValidationError("Tags may only contain letters, digits, spaces, '-' and '_'.".to_string()));
+        }
+
+        if !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+
+    if normalized.len() > MAX_TAGS {
+        return Err(AppError::ValidationError(format!("A live stream can have at most {} tags.", MAX_TAGS)));
+    }
+
+    Ok(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn tags_are_compared_in_lower_case() {
+        assert_eq!(
+            normalize_tags(&tags(&[" News ", "news", "", "sports_2"])).unwrap(),
+            ["news", "sports_2"]
+        );
+        assert!(normalize_tags(&tags(&["a,b"])).is_err());
+        assert!(normalize_tags(&tags(&[&"x".repeat(MAX_TAG_LENGTH + 1)])).is_err());
+        assert!(normalize_tags(&(0..=MAX_TAGS).map(|n| n.to_string()).collect::<Vec<_>>()).is_err());
+    }
+}
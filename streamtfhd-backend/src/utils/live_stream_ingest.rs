@@ -0,0 +1,398 @@
+use nix::sys::signal::{kill, Signal};
+use nix::unistd::Pid;
+use sqlx::{Pool, Postgres};
+use std::env::var;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::fs::OpenOptions;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::process::Child;
+use tokio::sync::Notify;
+use tokio::time::Instant;
+use tracing::{info, warn, error, debug};
+use uuid::Uuid;
+
+use crate::{
+    dto::live_stream_ingest::IngestAddress,
+    dto::live_stream_source::{LiveStreamSource, INGEST_SRT, MODE_INGEST},
+    dto::live_stream_state::{LiveStreamState, SourceItem},
+    errors::AppError,
+    models::live_stream_ingest::get_free_ingest_port,
+    utils::live_stream_pipeline::{
+        get_slate,
+        set_feeder_pid,
+        spawn_feeder,
+        spawn_feeder_to,
+        stop_feeder,
+        FEEDER_TS_GAP,
+        SLATE_RETRY_DELAY
+    }
+};
+
+// Stream name of the RTMP ingest address, the key is the application.
+const INGEST_STREAM_NAME: &str = "live";
+const TS_PACKET_SIZE: usize = 188;
+const RELAY_BUFFER_SIZE: usize = TS_PACKET_SIZE * 348;
+
+#[derive(Debug, Clone)]
+pub struct IngestConfig {
+    pub port_start: i32,
+    pub port_end: i32,
+    pub public_host: String     // Host encoders push to, as they see the server
+}
+
+impl IngestConfig {
+    pub fn from_env() -> Self {
+        let port = |key: &str, default: i32| match var(key) {
+            Ok(val) => match val.trim().parse::<u16>() {
+                Ok(val) if val > 0 => val as i32,
+                _ => {
+                    warn!("Invalid {} value in env file, using {}.", key, default);
+
+                    default
+                }
+            },
+            Err(_) => default
+        };
+        let port_start = port("INGEST_PORT_START", 1936);
+        let port_end = port("INGEST_PORT_END", 1999).max(port_start);
+        let public_host = match var("INGEST_PUBLIC_HOST") {
+            Ok(val) if !val.trim().is_empty() => val.trim().to_string(),
+            _ => String::from("localhost")
+        };
+
+        IngestConfig {
+            port_start,
+            port_end,
+            public_host
+        }
+    }
+}
+
+// 32 characters, also a valid SRT passphrase.
+pub fn generate_ingest_key() -> String {
+    Uuid::new_v4().simple().to_string()
+}
+
+// Address the relay listens on. SRT turns away callers without the
+// passphrase, RTMP publishers are checked by `is_foreign_publisher`.
+pub fn listen_url(protocol: &str, port: i32, key: &str) -> String {
+    if protocol == INGEST_SRT {
+        return format!("srt://0.0.0.0:{}?mode=listener&passphrase={}", port, key);
+    }
+
+    format!("rtmp://0.0.0.0:{}/{}/{}", port, key, INGEST_STREAM_NAME)
+}
+
+pub fn ingest_address(protocol: &str, host: &str, port: i32, key: &str) -> IngestAddress {
+    if protocol == INGEST_SRT {
+        return IngestAddress {
+            protocol: protocol.to_string(),
+            url: format!("srt://{}:{}?passphrase={}", host, port, key),
+            stream_key: String::new()
+        };
+    }
+
+    IngestAddress {
+        protocol: protocol.to_string(),
+        url: format!("rtmp://{}:{}/{}", host, port, key),
+        stream_key: INGEST_STREAM_NAME.to_string()
+    }
+}
+
+// Ingest port of a live stream being created or edited, None outside of
+// ingest mode.
+pub async fn assign_ingest_port(
+    id: Option<i64>,
+    source: &LiveStreamSource,
+    pool: &Pool<Postgres>
+) -> Result<Option<i32>, AppError> {
+    if source.mode != MODE_INGEST {
+        return Ok(None);
+    }
+
+    match get_free_ingest_port(id, &IngestConfig::from_env(), pool).await? {
+        Some(port) => Ok(Some(port)),
+        None => Err(AppError::Conflict("Every ingest port is taken by another live stream.".to_string()))
+    }
+}
+
+// ffmpeg only warns when an RTMP publisher connects to another application or
+// stream than the one it listens on.
+fn is_foreign_publisher(log_line: &str) -> bool {
+    log_line.contains("App field don't match up") || log_line.contains("Unexpected stream")
+}
+
+// Everything the relay of an ingest live stream needs.
+pub struct IngestRelay {
+    pub url: String,
+    pub fallback: Option<(String, i64)>,    // File and its length in milliseconds
+    pub pipeline_path: PathBuf,
+    pub started_at: Instant                 // Of the pipeline, feeder timestamps count from it
+}
+
+// Plays while nothing is pushed: the fallback video from where it was left
+// off, the slate, or black frames.
+struct Filler {
+    child: Option<Child>,
+    started_at: Instant,
+    position: i64
+}
+
+impl Filler {
+    fn start(
+        &mut self,
+        state: &LiveStreamState,
+        stream_id: i64,
+        relay: &IngestRelay
+    ) {
+        if self.child.is_some() {
+            return;
+        }
+
+        let item = match &relay.fallback {
+            Some((file, _)) => SourceItem::Filler { file: Some(file.clone()), start: self.position },
+            None => get_slate(state, stream_id).unwrap_or(SourceItem::Filler { file: None, start: 0 })
+        };
+        let ts_offset = relay.started_at.elapsed().as_secs_f64() + FEEDER_TS_GAP;
+
+        match spawn_feeder(&item, &relay.pipeline_path, ts_offset) {
+            Ok(child) => {
+                set_feeder_pid(state, stream_id, child.id());
+
+                self.child = Some(child);
+                self.started_at = Instant::now();
+            }
+            Err(err) => {
+                error!("Error while spawning ingest filler ffmpeg.");
+                debug!("{}.", err);
+            }
+        }
+    }
+
+    // Returns once the filler exited, it loops forever and only exits on
+    // errors. Without a filler it waits before one is spawned again.
+    async fn wait(&mut self, relay: &IngestRelay) {
+        if let Some(child) = self.child.as_mut() {
+            let _ = child.wait().await;
+
+            self.finish(relay);
+        }
+
+        tokio::time::sleep(SLATE_RETRY_DELAY).await;
+    }
+
+    async fn stop(&mut self, relay: &IngestRelay) {
+        if let Some(child) = self.child.as_mut() {
+            stop_feeder(child).await;
+
+            self.finish(relay);
+        }
+    }
+
+    fn finish(&mut self, relay: &IngestRelay) {
+        self.child = None;
+
+        if let Some((_, length)) = &relay.fallback && *length > 0 {
+            self.position = (self.position + self.started_at.elapsed().as_millis() as i64) % length;
+        }
+    }
+}
+
+enum ListenerEnd {
+    Stopped,
+    Disconnected,
+    Broken
+}
+
+fn unix_time_secs() -> f64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(val) => val.as_secs_f64(),
+        Err(_) => 0.0
+    }
+}
+
+// Listens for the publisher of an ingest live stream and relays it into the
+// pipeline, the filler plays whenever nothing is pushed. Returns once
+// stopped, or when the pipeline can not be written to.
+pub async fn relay_ingest(
+    state: Arc<LiveStreamState>,
+    stream_id: i64,
+    relay: IngestRelay,
+    stop: Arc<Notify>
+) {
+    let mut sink = match OpenOptions::new().write(true).open(&relay.pipeline_path).await {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Error while opening live stream pipeline for the ingest relay.");
+            debug!("{}.", err);
+
+            return;
+        }
+    };
+    // The listener stamps packets with the wall clock, shifted back by the
+    // time the pipeline started they follow on from the filler.
+    let ts_offset = relay.started_at.elapsed().as_secs_f64() - unix_time_secs();
+    let item = SourceItem::Ingest {
+        url: relay.url.clone(),
+        fallback: None,
+        fallback_length: 0
+    };
+    let mut filler = Filler {
+        child: None,
+        started_at: Instant::now(),
+        position: 0
+    };
+    let mut buffer = vec![0u8; RELAY_BUFFER_SIZE];
+    let mut pending: Vec<u8> = Vec::with_capacity(RELAY_BUFFER_SIZE + TS_PACKET_SIZE);
+
+    loop {
+        filler.start(&state, stream_id, &relay);
+
+        let mut listener = match spawn_feeder_to(&item, "pipe:1", ts_offset, true) {
+            Ok(val) => val,
+            Err(err) => {
+                error!("Error while spawning ingest listener ffmpeg.");
+                debug!("{}.", err);
+
+                tokio::select! {
+                    _ = tokio::time::sleep(SLATE_RETRY_DELAY) => continue,
+                    _ = stop.notified() => {
+                        filler.stop(&relay).await;
+
+                        return;
+                    }
+                }
+            }
+        };
+        let (Some(mut stdout), Some(stderr)) = (listener.stdout.take(), listener.stderr.take()) else {
+            stop_feeder(&mut listener).await;
+            filler.stop(&relay).await;
+
+            return;
+        };
+        let mut log = BufReader::new(stderr).lines();
+        let mut is_log_open = true;
+        let mut is_relaying = false;
+
+        pending.clear();
+        info!(%stream_id, "ingest relay waiting for the publisher");
+
+        // Log lines are read first, a publisher is turned away before any
+        // of its packets reach the pipeline.
+        let end = loop {
+            tokio::select! {
+                biased;
+                _ = stop.notified() => break ListenerEnd::Stopped,
+                line = log.next_line(), if is_log_open => match line {
+                    Ok(Some(line)) if is_foreign_publisher(&line) => {
+                        warn!(%stream_id, "ingest publisher turned away: {}", line);
+
+                        break ListenerEnd::Disconnected;
+                    }
+                    Ok(Some(_)) => (),
+                    _ => is_log_open = false
+                },
+                read = stdout.read(&mut buffer) => {
+                    let count = match read {
+                        Ok(val) if val > 0 => val,
+                        _ => break ListenerEnd::Disconnected
+                    };
+
+                    if !is_relaying {
+                        filler.stop(&relay).await;
+                        set_feeder_pid(&state, stream_id, listener.id());
+                        info!(%stream_id, "ingest publisher connected, relaying");
+
+                        is_relaying = true;
+                    }
+
+                    // Only whole packets are written, the pipeline may be
+                    // handed to the filler after any of them.
+                    pending.extend_from_slice(&buffer[..count]);
+
+                    let whole = pending.len() - pending.len() % TS_PACKET_SIZE;
+
+                    if let Err(err) = sink.write_all(&pending[..whole]).await {
+                        error!("Error while writing ingest relay to live stream pipeline.");
+                        debug!("{}.", err);
+
+                        break ListenerEnd::Broken;
+                    }
+
+                    pending.drain(..whole);
+                }
+                _ = filler.wait(&relay), if !is_relaying => {
+                    warn!(%stream_id, "ingest filler ffmpeg exited, starting it again");
+                    filler.start(&state, stream_id, &relay);
+                }
+            }
+        };
+
+        if let Some(pid) = listener.id() {
+            let _ = kill(Pid::from_raw(pid as i32), Signal::SIGTERM);
+        }
+
+        drop(stdout);
+        stop_feeder(&mut listener).await;
+        set_feeder_pid(&state, stream_id, None);
+
+        let _ = sink.flush().await;
+
+        match end {
+            ListenerEnd::Stopped | ListenerEnd::Broken => {
+                filler.stop(&relay).await;
+
+                return;
+            }
+            ListenerEnd::Disconnected if is_relaying => {
+                info!(%stream_id, "ingest publisher disconnected, playing the fallback");
+            }
+            ListenerEnd::Disconnected => {
+                // The listener did not get as far as a publisher, it may be
+                // failing to bind its port.
+                tokio::select! {
+                    _ = tokio::time::sleep(SLATE_RETRY_DELAY) => {}
+                    _ = stop.notified() => {
+                        filler.stop(&relay).await;
+
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dto::live_stream_source::INGEST_RTMP;
+
+    #[test]
+    fn rtmp_key_is_the_application() {
+        let address = ingest_address(INGEST_RTMP, "stream.example.com", 1936, "abc");
+
+        assert_eq!(listen_url(INGEST_RTMP, 1936, "abc"), "rtmp://0.0.0.0:1936/abc/live");
+        assert_eq!(address.url, "rtmp://stream.example.com:1936/abc");
+        assert_eq!(address.stream_key, "live");
+    }
+
+    #[test]
+    fn srt_key_is_the_passphrase() {
+        let address = ingest_address(INGEST_SRT, "stream.example.com", 1937, "abc");
+
+        assert_eq!(listen_url(INGEST_SRT, 1937, "abc"), "srt://0.0.0.0:1937?mode=listener&passphrase=abc");
+        assert_eq!(address.url, "srt://stream.example.com:1937?passphrase=abc");
+        assert!(address.stream_key.is_empty());
+        assert_eq!(generate_ingest_key().len(), 32);
+    }
+
+    #[test]
+    fn foreign_publishers_are_spotted_in_the_log() {
+        assert!(is_foreign_publisher("[rtmp @ 0x55] Unexpected stream wrong, expecting live"));
+        assert!(is_foreign_publisher("[rtmp @ 0x55] App field don't match up: other <-> abc"));
+        assert!(!is_foreign_publisher("[flv @ 0x55] Non-monotonous DTS in output stream"));
+    }
+}
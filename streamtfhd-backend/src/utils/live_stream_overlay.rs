@@ -0,0 +1,839 @@
+use futures_util::StreamExt;
+use reqwest::{Client, Url, redirect::Policy};
+use sqlx::{Pool, Postgres};
+use std::collections::HashMap;
+use std::env::var;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::net::lookup_host;
+use tokio::time::Duration;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    dto::live_stream_overlay::{
+        Overlay,
+        OverlayLayer,
+        OverlayPosition,
+        OverlayRuntime,
+        TextContent,
+        TextStyle,
+        TickerEdge
+    },
+    dto::live_stream_state::LiveStreamState,
+    errors::AppError,
+    models::gallery_delete_image::get_image_owner,
+    models::live_stream_overlay::get_image_files,
+    utils::live_stream::respawn_ffmpeg
+};
+
+pub const MAX_OVERLAYS: usize = 8;
+pub const MAX_TEXT_LENGTH: usize = 500;
+const MIN_URL_INTERVAL: u64 = 5;
+const MAX_URL_INTERVAL: u64 = 86400;
+const URL_TIMEOUT: Duration = Duration::from_secs(10);
+// Largest overlay text URL response read, the text itself is cut much shorter.
+const MAX_URL_BYTES: usize = 64 * 1024;
+// Distance between an overlay and the edge of the video, in pixels.
+const OVERLAY_MARGIN: i32 = 20;
+// Used to size watermarks when the width of the video is unknown.
+const DEFAULT_VIDEO_WIDTH: i32 = 1280;
+
+// A color name or hex value ffmpeg understands, with an optional @alpha.
+fn is_valid_color(color: &str) -> bool {
+    let (name, alpha) = match color.split_once('@') {
+        Some((name, alpha)) => (name, Some(alpha)),
+        None => (color, None)
+    };
+    let is_valid_name = match name.strip_prefix('#').or_else(|| name.strip_prefix("0x")) {
+        Some(hex) => hex.len() == 6 && hex.chars().all(|ch| ch.is_ascii_hexdigit()),
+        None => !name.is_empty() && name.chars().all(|ch| ch.is_ascii_alphabetic())
+    };
+    let is_valid_alpha = match alpha {
+        Some(val) => matches!(val.parse::<f32>(), Ok(val) if (0.0..=1.0).contains(&val)),
+        None => true
+    };
+
+    is_valid_name && is_valid_alpha
+}
+
+fn check_style(style: &TextStyle) -> Result<(), String> {
+    if !(8..=200).contains(&style.font_size) {
+        return Err("Font size must be between 8 and 200.".to_string());
+    }
+
+    if !is_valid_color(&style.color) {
+        return Err(format!("Invalid color \"{}\".", style.color));
+    }
+
+    if let Some(background) = &style.background && !is_valid_color(background) {
+        return Err(format!("Invalid background color \"{}\".", background));
+    }
+
+    Ok(())
+}
+
+// Whether a user may make the backend fetch from the address. Its own host,
+// the private network and cloud metadata endpoints are off limits.
+fn is_public_address(addr: IpAddr) -> bool {
+    match addr {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+
+            !(v4.is_loopback()
+                || v4.is_private()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || v4.is_multicast()
+                || v4.is_documentation()
+                || a == 0
+                || (a == 100 && (64..128).contains(&b)))    // Carrier grade NAT
+        }
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => is_public_address(IpAddr::V4(v4)),
+            None => !(v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_multicast()
+                || v6.is_unique_local()
+                || v6.is_unicast_link_local())
+        }
+    }
+}
+
+// Hosts in OVERLAY_URL_ALLOWED_HOSTS may be on the private network.
+fn is_allowed_host(host: &str) -> bool {
+    match var("OVERLAY_URL_ALLOWED_HOSTS") {
+        Ok(val) => val.split(',').any(|allowed| allowed.trim().eq_ignore_ascii_case(host)),
+        Err(_) => false
+    }
+}
+
+fn url_host(url: &Url) -> Option<&str> {
+    url.host_str().map(|host| host.trim_start_matches('[').trim_end_matches(']'))
+}
+
+fn check_content(content: &TextContent) -> Result<(), String> {
+    match content {
+        TextContent::Static { text } => {
+            if text.chars().count() > MAX_TEXT_LENGTH {
+                return Err(format!("Overlay text must be at most {} characters.", MAX_TEXT_LENGTH));
+            }
+        }
+        TextContent::Url { url, interval } => {
+            let host = match Url::parse(url) {
+                Ok(val) if matches!(val.scheme(), "http" | "https") => url_host(&val).unwrap_or_default().to_string(),
+                _ => return Err("Overlay text URL must be an http or https URL.".to_string())
+            };
+
+            // Names are checked again on every fetch, they may resolve elsewhere.
+            if let Ok(ip) = host.parse::<IpAddr>() && !is_public_address(ip) && !is_allowed_host(&host) {
+                return Err("Overlay text URL can not point to a private address.".to_string());
+            }
+
+            if !(MIN_URL_INTERVAL..=MAX_URL_INTERVAL).contains(interval) {
+                return Err(format!("Overlay text URL interval must be between {} and {} secs.", MIN_URL_INTERVAL, MAX_URL_INTERVAL));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Checks the overlays of a live stream, without looking at the database.
+pub fn check_overlays(overlays: &[Overlay]) -> Result<(), String> {
+    if overlays.len() > MAX_OVERLAYS {
+        return Err(format!("A live stream has at most {} overlays.", MAX_OVERLAYS));
+    }
+
+    for overlay in overlays {
+        match overlay {
+            Overlay::Watermark { opacity, scale, .. } => {
+                if !(0.0..=1.0).contains(opacity) {
+                    return Err("Watermark opacity must be between 0 and 1.".to_string());
+                }
+
+                if !(*scale > 0.0 && *scale <= 1.0) {
+                    return Err("Watermark scale must be greater than 0 and at most 1.".to_string());
+                }
+            }
+            Overlay::Text { content, style, .. } => {
+                check_content(content)?;
+                check_style(style)?;
+            }
+            Overlay::Clock { format, style, .. } => {
+                if format.trim().is_empty() || format.chars().count() > 64 {
+                    return Err("Clock format must be between 1 and 64 characters.".to_string());
+                }
+
+                check_style(style)?;
+            }
+            Overlay::Ticker { content, speed, style, .. } => {
+                if !(10..=1000).contains(speed) {
+                    return Err("Ticker speed must be between 10 and 1000 pixels per second.".to_string());
+                }
+
+                check_content(content)?;
+                check_style(style)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Validates the overlays of a live stream being created or edited.
+// Watermark images must belong to the user.
+pub async fn validate_overlays(
+    overlays: &[Overlay],
+    user_id: &String,
+    pool: &Pool<Postgres>
+) -> Result<(), AppError> {
+    if let Err(err) = check_overlays(overlays) {
+        return Err(AppError::ValidationError(err));
+    }
+
+    for overlay in overlays {
+        if let Overlay::Watermark { image, .. } = overlay {
+            match get_image_owner(*image, pool).await? {
+                Some(owner) if owner.eq(user_id) => {},
+                _ => return Err(AppError::BadRequest("Invalid watermark image ID".to_string()))
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Files of the watermark images used by the overlays, by image ID.
+pub async fn load_image_files(
+    overlays: &[Overlay],
+    pool: &Pool<Postgres>
+) -> Result<HashMap<i64, String>, AppError> {
+    let ids: Vec<i64> = overlays
+        .iter()
+        .filter_map(|overlay| match overlay {
+            Overlay::Watermark { image, .. } => Some(*image),
+            _ => None
+        })
+        .collect();
+
+    if ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    Ok(get_image_files(&ids, pool).await?.into_iter().collect())
+}
+
+fn text_file(dir: &Path, index: usize) -> PathBuf {
+    dir.join(format!("overlay-{}.txt", index))
+}
+
+// Replaces the text at once, ffmpeg reloads the file on every frame and must
+// never see it half written.
+fn write_text(path: &Path, text: &str) -> std::io::Result<()> {
+    let temp_path = path.with_extension("tmp");
+
+    std::fs::write(&temp_path, text)?;
+    std::fs::rename(&temp_path, path)
+}
+
+// A ticker scrolls one line.
+fn display_text(text: &str, single_line: bool) -> String {
+    let text: String = text.trim().chars().take(MAX_TEXT_LENGTH).collect();
+
+    if single_line {
+        return text.split_whitespace().collect::<Vec<&str>>().join(" ");
+    }
+
+    text
+}
+
+// drawtext expands `%{localtime:format}` read from the text file, the format
+// is an argument of that function, so `:` and `}` in it are escaped.
+fn clock_text(format: &str) -> String {
+    let mut escaped = String::new();
+
+    for ch in format.chars() {
+        if matches!(ch, '\\' | ':' | '}') {
+            escaped.push('\\');
+        }
+
+        escaped.push(ch);
+    }
+
+    format!("%{{localtime:{}}}", escaped)
+}
+
+// Writes the text files of the overlays into `dir` and resolves their files.
+// Text fetched from an URL keeps what was fetched last until the next poll.
+pub fn prepare_layers(
+    overlays: &[Overlay],
+    image_files: &HashMap<i64, String>,
+    dir: &Path,
+    upload_dir: &str
+) -> std::io::Result<Vec<OverlayLayer>> {
+    let mut layers = Vec::new();
+
+    for (index, overlay) in overlays.iter().enumerate() {
+        let path = text_file(dir, index);
+        let content = match overlay {
+            Overlay::Text { content, .. } | Overlay::Ticker { content, .. } => Some(content),
+            _ => None
+        };
+        let single_line = matches!(overlay, Overlay::Ticker { .. });
+
+        match content {
+            Some(TextContent::Static { text }) => write_text(&path, &display_text(text, single_line))?,
+            Some(TextContent::Url { .. }) if !path.exists() => write_text(&path, "")?,
+            _ => ()
+        }
+
+        match overlay {
+            Overlay::Watermark { image, position, opacity, scale } => {
+                let file = match image_files.get(image) {
+                    Some(val) => val,
+                    None => {
+                        warn!(image, "watermark image is gone, skipping the overlay");
+
+                        continue;
+                    }
+                };
+
+                layers.push(OverlayLayer::Watermark {
+                    file: format!("{}/gallery_images/{}", upload_dir, file),
+                    position: *position,
+                    opacity: *opacity,
+                    scale: *scale
+                });
+            }
+            Overlay::Text { position, style, .. } => layers.push(OverlayLayer::Text {
+                text_file: path,
+                expand: false,
+                position: *position,
+                style: style.clone()
+            }),
+            Overlay::Clock { format, position, style } => {
+                write_text(&path, &clock_text(format))?;
+
+                layers.push(OverlayLayer::Text {
+                    text_file: path,
+                    expand: true,
+                    position: *position,
+                    style: style.clone()
+                });
+            }
+            Overlay::Ticker { edge, speed, style, .. } => layers.push(OverlayLayer::Ticker {
+                text_file: path,
+                edge: *edge,
+                speed: *speed,
+                style: style.clone()
+            })
+        }
+    }
+
+    Ok(layers)
+}
+
+pub fn create_overlay_runtime(
+    stream_id: i64,
+    overlays: &[Overlay],
+    image_files: &HashMap<i64, String>,
+    upload_dir: &str
+) -> std::io::Result<OverlayRuntime> {
+    let dir = std::env::temp_dir().join(format!("streamtfhd-{}-{}-overlays", stream_id, Uuid::new_v4()));
+
+    std::fs::create_dir_all(&dir)?;
+
+    let mut runtime = OverlayRuntime {
+        dir,
+        config: overlays.to_vec(),
+        layers: Vec::new(),
+        generation: 0
+    };
+
+    // The directory is removed with the runtime if a text file fails.
+    runtime.layers = prepare_layers(overlays, image_files, &runtime.dir, upload_dir)?;
+
+    Ok(runtime)
+}
+
+// Escapes a value for an option of a filter inside a filter graph, first for
+// the option list of the filter and then for the graph.
+fn escape_filter_value(value: &str) -> String {
+    let mut option = String::new();
+    let mut graph = String::new();
+
+    for ch in value.chars() {
+        if matches!(ch, '\\' | '\'' | ':') {
+            option.push('\\');
+        }
+
+        option.push(ch);
+    }
+
+    for ch in option.chars() {
+        if matches!(ch, '\\' | '\'' | '[' | ']' | ',' | ';') {
+            graph.push('\\');
+        }
+
+        graph.push(ch);
+    }
+
+    graph
+}
+
+// x and y of a layer of `inner_w` by `inner_h` inside `outer_w` by `outer_h`,
+// named the way the filter calls them.
+fn position_xy(
+    position: OverlayPosition,
+    outer_w: &str,
+    inner_w: &str,
+    outer_h: &str,
+    inner_h: &str
+) -> (String, String) {
+    let left = OVERLAY_MARGIN.to_string();
+    let center_x = format!("({}-{})/2", outer_w, inner_w);
+    let right = format!("{}-{}-{}", outer_w, inner_w, OVERLAY_MARGIN);
+    let top = OVERLAY_MARGIN.to_string();
+    let center_y = format!("({}-{})/2", outer_h, inner_h);
+    let bottom = format!("{}-{}-{}", outer_h, inner_h, OVERLAY_MARGIN);
+
+    match position {
+        OverlayPosition::TopLeft => (left, top),
+        OverlayPosition::TopCenter => (center_x, top),
+        OverlayPosition::TopRight => (right, top),
+        OverlayPosition::Center => (center_x, center_y),
+        OverlayPosition::BottomLeft => (left, bottom),
+        OverlayPosition::BottomCenter => (center_x, bottom),
+        OverlayPosition::BottomRight => (right, bottom)
+    }
+}
+
+fn drawtext(
+    text_file: &Path,
+    expand: bool,
+    style: &TextStyle,
+    x: &str,
+    y: &str,
+    font_file: Option<&str>
+) -> String {
+    let mut options = vec![
+        format!("textfile={}", escape_filter_value(&text_file.to_string_lossy())),
+        "reload=1".to_string(),
+        format!("expansion={}", if expand { "normal" } else { "none" }),
+        format!("fontsize={}", style.font_size),
+        format!("fontcolor={}", escape_filter_value(&style.color))
+    ];
+
+    if let Some(font_file) = font_file {
+        options.push(format!("fontfile={}", escape_filter_value(font_file)));
+    }
+
+    if let Some(background) = &style.background {
+        options.push("box=1".to_string());
+        options.push(format!("boxcolor={}", escape_filter_value(background)));
+        options.push("boxborderw=10".to_string());
+    }
+
+    options.push(format!("x={}", escape_filter_value(x)));
+    options.push(format!("y={}", escape_filter_value(y)));
+
+    format!("drawtext={}", options.join(":"))
+}
+
+// The `-filter_complex` of the output ffmpeg. Watermark images are extra
+// inputs after the pipeline, in `inputs` order.
+#[derive(Debug, PartialEq)]
+pub struct FilterGraph {
+    pub inputs: Vec<String>,
+    pub graph: String,
+    pub output: String
+}
+
+pub fn build_filter_graph(
+    layers: &[OverlayLayer],
+    width: i32,
+    font_file: Option<&str>
+) -> Option<FilterGraph> {
+    if layers.is_empty() {
+        return None;
+    }
+
+    let width = if width > 0 { width } else { DEFAULT_VIDEO_WIDTH };
+    let mut inputs = Vec::new();
+    let mut steps = Vec::new();
+    let mut current = String::from("0:v");
+
+    for (index, layer) in layers.iter().enumerate() {
+        let output = if index + 1 == layers.len() {
+            String::from("vout")
+        } else {
+            format!("v{}", index)
+        };
+
+        match layer {
+            OverlayLayer::Watermark { file, position, opacity, scale } => {
+                inputs.push(file.clone());
+
+                let image_width = ((width as f32 * scale).round() as i32).max(1);
+                let (x, y) = position_xy(*position, "W", "w", "H", "h");
+
+                steps.push(format!(
+                    "[{}:v]scale={}:-1,format=rgba,colorchannelmixer=aa={:.2}[wm{}]",
+                    inputs.len(),
+                    image_width,
+                    opacity,
+                    index
+                ));
+                steps.push(format!("[{}][wm{}]overlay=x={}:y={}:format=auto[{}]", current, index, x, y, output));
+            }
+            OverlayLayer::Text { text_file, expand, position, style } => {
+                let (x, y) = position_xy(*position, "w", "tw", "h", "th");
+
+                steps.push(format!("[{}]{}[{}]", current, drawtext(text_file, *expand, style, &x, &y, font_file), output));
+            }
+            OverlayLayer::Ticker { text_file, edge, speed, style } => {
+                let x = format!("w-mod(t*{},w+tw)", speed);
+                let y = match edge {
+                    TickerEdge::Top => OVERLAY_MARGIN.to_string(),
+                    TickerEdge::Bottom => format!("h-th-{}", OVERLAY_MARGIN)
+                };
+
+                steps.push(format!("[{}]{}[{}]", current, drawtext(text_file, false, style, &x, &y, font_file), output));
+            }
+        }
+
+        current = output;
+    }
+
+    Some(FilterGraph {
+        inputs,
+        graph: steps.join(";"),
+        output: current
+    })
+}
+
+// The host and the address an overlay text URL is fetched from. The address
+// is pinned, so the name can not resolve to a private one in between.
+async fn resolve_text_url(url: &Url) -> Result<Option<(String, SocketAddr)>, String> {
+    let host = match url_host(url) {
+        Some(val) => val,
+        None => return Err("URL has no host".to_string())
+    };
+
+    if is_allowed_host(host) {
+        return Ok(None);
+    }
+
+    let port = url.port_or_known_default().unwrap_or(80);
+    let addrs: Vec<SocketAddr> = match lookup_host((host, port)).await {
+        Ok(val) => val.collect(),
+        Err(err) => return Err(err.to_string())
+    };
+
+    match addrs.first() {
+        Some(addr) if addrs.iter().all(|addr| is_public_address(addr.ip())) => Ok(Some((host.to_string(), *addr))),
+        _ => Err(format!("{} does not resolve to a public address", host))
+    }
+}
+
+async fn fetch_text(url: &str) -> Result<String, String> {
+    let url = Url::parse(url).map_err(|err| err.to_string())?;
+    // Redirects could lead anywhere, they are not followed.
+    let mut builder = Client::builder().timeout(URL_TIMEOUT).redirect(Policy::none());
+
+    if let Some((host, addr)) = resolve_text_url(&url).await? {
+        builder = builder.resolve(&host, addr);
+    }
+
+    let client = builder.build().map_err(|err| err.to_string())?;
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|err| err.to_string())?;
+    let mut chunks = response.bytes_stream();
+    let mut body: Vec<u8> = Vec::new();
+
+    while let Some(chunk) = chunks.next().await {
+        let chunk = chunk.map_err(|err| err.to_string())?;
+
+        if body.len() + chunk.len() > MAX_URL_BYTES {
+            return Err(format!("Response is larger than {} bytes", MAX_URL_BYTES));
+        }
+
+        body.extend_from_slice(&chunk);
+    }
+
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
+
+// Keeps the text file of one overlay up to date with its URL, until the job
+// is gone or the overlay has been changed.
+async fn poll_text_url(
+    state: Arc<LiveStreamState>,
+    stream_id: i64,
+    index: usize,
+    url: String,
+    interval: u64,
+    path: PathBuf,
+    generation: u32
+) {
+    loop {
+        let single_line = match state.jobs.get(&stream_id) {
+            Some(job) if !job.status.is_final() => match &job.overlays {
+                Some(runtime) if runtime.generation == generation => match runtime.config.get(index) {
+                    Some(Overlay::Text { content: TextContent::Url { url: current, .. }, .. }) if *current == url => false,
+                    Some(Overlay::Ticker { content: TextContent::Url { url: current, .. }, .. }) if *current == url => true,
+                    _ => return
+                },
+                _ => return
+            },
+            _ => return
+        };
+
+        match fetch_text(&url).await {
+            Ok(text) => {
+                if let Err(err) = write_text(&path, &display_text(&text, single_line)) {
+                    warn!(%stream_id, index, "failed to write overlay text: {}", err);
+                }
+            }
+            Err(err) => warn!(%stream_id, index, "failed to fetch overlay text from {}: {}", url, err)
+        }
+
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+    }
+}
+
+pub fn start_text_pollers(
+    state: &Arc<LiveStreamState>,
+    stream_id: i64,
+    overlays: &[Overlay],
+    dir: &Path,
+    generation: u32
+) {
+    for (index, overlay) in overlays.iter().enumerate() {
+        if let Overlay::Text { content: TextContent::Url { url, interval }, .. }
+            | Overlay::Ticker { content: TextContent::Url { url, interval }, .. } = overlay {
+            tokio::spawn(poll_text_url(
+                state.clone(),
+                stream_id,
+                index,
+                url.clone(),
+                *interval,
+                text_file(dir, index),
+                generation
+            ));
+        }
+    }
+}
+
+// Overlays with their text taken out, text changes are picked up by ffmpeg
+// from the text files without a restart.
+fn same_layout(old: &[Overlay], new: &[Overlay]) -> bool {
+    let strip = |overlays: &[Overlay]| -> Vec<Overlay> {
+        overlays
+            .iter()
+            .map(|overlay| match overlay {
+                Overlay::Text { position, style, .. } => Overlay::Text {
+                    content: TextContent::Static { text: String::new() },
+                    position: *position,
+                    style: style.clone()
+                },
+                Overlay::Ticker { edge, speed, style, .. } => Overlay::Ticker {
+                    content: TextContent::Static { text: String::new() },
+                    edge: *edge,
+                    speed: *speed,
+                    style: style.clone()
+                },
+                other => other.clone()
+            })
+            .collect()
+    };
+
+    strip(old) == strip(new)
+}
+
+// Applies edited overlays to a job that is scheduled or running. New text is
+// written to the text files, any other change spawns the output ffmpeg again,
+// which briefly reconnects to the destination.
+pub async fn apply_overlays(
+    state: &Arc<LiveStreamState>,
+    stream_id: i64,
+    overlays: &[Overlay],
+    pool: &Pool<Postgres>
+) -> Result<(), AppError> {
+    if !state.jobs.contains_key(&stream_id) {
+        return Ok(());
+    }
+
+    let upload_dir = state.upload_dir.clone();
+    let image_files = load_image_files(overlays, pool).await?;
+
+    let (dir, generation, respawn) = {
+        let mut job = match state.jobs.get_mut(&stream_id) {
+            Some(val) if !val.status.is_final() => val,
+            _ => return Ok(())
+        };
+        // Re-adopted jobs have no overlay state, they get the overlays on their next start.
+        let runtime = match job.overlays.as_mut() {
+            Some(val) => val,
+            None => return Ok(())
+        };
+        let is_same_layout = same_layout(&runtime.config, overlays);
+        let layers = prepare_layers(overlays, &image_files, &runtime.dir, &upload_dir)?;
+
+        runtime.config = overlays.to_vec();
+        runtime.layers = layers.clone();
+        runtime.generation += 1;
+
+        let dir = runtime.dir.clone();
+        let generation = runtime.generation;
+        let respawn = match job.launch.as_mut() {
+            Some(launch) if !is_same_layout => {
+                launch.overlays = layers;
+
+                true
+            }
+            _ => false
+        };
+
+        (dir, generation, respawn)
+    };
+
+    start_text_pollers(state, stream_id, overlays, &dir, generation);
+
+    if respawn {
+        info!(%stream_id, "overlays changed, spawning the output ffmpeg again");
+
+        respawn_ffmpeg(state, stream_id, pool).await;
+    }
+
+    Ok(())
+}
+
+// Replaces the text of a text or ticker overlay of a job. The overlay keeps
+// the text until the live stream ends or is edited.
+pub fn set_overlay_text(
+    state: &Arc<LiveStreamState>,
+    stream_id: i64,
+    index: usize,
+    text: &str
+) -> Result<(), AppError> {
+    let mut job = match state.jobs.get_mut(&stream_id) {
+        Some(val) if !val.status.is_final() => val,
+        _ => return Err(AppError::Conflict("Live stream is not running.".to_string()))
+    };
+    let runtime = match job.overlays.as_mut() {
+        Some(val) => val,
+        None => return Err(AppError::Conflict("Live stream has no overlays.".to_string()))
+    };
+    // A static content also stops the URL poller of the overlay.
+    let single_line = match runtime.config.get_mut(index) {
+        Some(Overlay::Text { content, .. }) => {
+            *content = TextContent::Static { text: text.to_string() };
+
+            false
+        }
+        Some(Overlay::Ticker { content, .. }) => {
+            *content = TextContent::Static { text: text.to_string() };
+
+            true
+        }
+        _ => return Err(AppError::BadRequest("The overlay does not show text.".to_string()))
+    };
+
+    write_text(&text_file(&runtime.dir, index), &display_text(text, single_line))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn text_urls_stay_off_the_private_network() {
+        let url = |url: &str| TextContent::Url { url: url.to_string(), interval: 60 };
+
+        assert!(is_public_address("1.1.1.1".parse().unwrap()));
+        assert!(!is_public_address("10.0.0.1".parse().unwrap()));
+        assert!(!is_public_address("100.100.0.1".parse().unwrap()));
+        assert!(!is_public_address("::ffff:127.0.0.1".parse().unwrap()));
+        assert!(!is_public_address("fd00::1".parse().unwrap()));
+        assert!(check_content(&url("https://example.com/now-playing.txt")).is_ok());
+        assert!(check_content(&url("http://169.254.169.254/latest/meta-data/")).is_err());
+        assert!(check_content(&url("http://[::1]:8080/")).is_err());
+        assert!(check_content(&url("file:///etc/passwd")).is_err());
+        assert!(fetch_text("http://localhost:1/").await.unwrap_err().contains("public address"));
+    }
+
+    #[test]
+    fn colors_are_validated() {
+        assert!(is_valid_color("white"));
+        assert!(is_valid_color("black@0.5"));
+        assert!(is_valid_color("#FF8800"));
+        assert!(is_valid_color("0xff8800@1"));
+        assert!(!is_valid_color("#FF88"));
+        assert!(!is_valid_color("white:x=0"));
+        assert!(!is_valid_color("black@2"));
+    }
+
+    #[test]
+    fn filter_values_are_escaped_for_option_and_graph() {
+        assert_eq!(escape_filter_value("/tmp/a.txt"), "/tmp/a.txt");
+        assert_eq!(escape_filter_value("a:b"), "a\\\\:b");
+        assert_eq!(escape_filter_value("w-mod(t*80,w+tw)"), "w-mod(t*80\\,w+tw)");
+    }
+
+    #[test]
+    fn clock_format_is_escaped() {
+        assert_eq!(clock_text("%H:%M"), "%{localtime:%H\\:%M}");
+    }
+
+    #[test]
+    fn filter_graph_chains_the_layers() {
+        let layers = vec![
+            OverlayLayer::Watermark {
+                file: String::from("/uploads/gallery_images/logo.png"),
+                position: OverlayPosition::TopRight,
+                opacity: 0.8,
+                scale: 0.1
+            },
+            OverlayLayer::Ticker {
+                text_file: PathBuf::from("/tmp/overlay-1.txt"),
+                edge: TickerEdge::Bottom,
+                speed: 80,
+                style: TextStyle::default()
+            }
+        ];
+        let graph = build_filter_graph(&layers, 1920, None).unwrap();
+
+        assert_eq!(graph.inputs, vec![String::from("/uploads/gallery_images/logo.png")]);
+        assert_eq!(graph.output, "vout");
+        assert_eq!(
+            graph.graph,
+            "[1:v]scale=192:-1,format=rgba,colorchannelmixer=aa=0.80[wm0];\
+             [0:v][wm0]overlay=x=W-w-20:y=20:format=auto[v0];\
+             [v0]drawtext=textfile=/tmp/overlay-1.txt:reload=1:expansion=none:fontsize=32:fontcolor=white:x=w-mod(t*80\\,w+tw):y=h-th-20[vout]"
+        );
+        assert_eq!(build_filter_graph(&[], 1920, None), None);
+    }
+
+    #[test]
+    fn text_changes_keep_the_layout() {
+        let text = |text: &str| Overlay::Text {
+            content: TextContent::Static { text: text.to_string() },
+            position: OverlayPosition::BottomLeft,
+            style: TextStyle::default()
+        };
+
+        assert!(same_layout(&[text("a")], &[text("b")]));
+        assert!(!same_layout(&[text("a")], &[]));
+        assert!(check_overlays(&[text("a")]).is_ok());
+        assert!(check_overlays(&vec![text("a"); MAX_OVERLAYS + 1]).is_err());
+    }
+}
@@ -0,0 +1,659 @@
+use nix::sys::stat::Mode;
+use nix::sys::signal::{kill, Signal};
+use nix::unistd::{mkfifo, setsid, Pid};
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use tokio::process::{Child, Command};
+use tokio::sync::Notify;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::task::JoinHandle;
+use tokio::time::{Duration, Instant};
+use tracing::{info, warn, error, debug};
+use uuid::Uuid;
+
+use crate::dto::live_stream_state::{
+    Background,
+    LiveStreamState,
+    PipelineCommand,
+    PlaybackPosition,
+    SourceItem
+};
+use crate::utils::live_stream_ingest::{IngestRelay, relay_ingest};
+
+// Time left between the last timestamp of a feeder and the first of the next.
+pub const FEEDER_TS_GAP: f64 = 0.1;
+pub const FEEDER_STOP_TIMEOUT: Duration = Duration::from_secs(2);
+// Delay before a slate feeder that exited is started again.
+pub const SLATE_RETRY_DELAY: Duration = Duration::from_secs(1);
+const DEFAULT_SLATE_BIT_RATE: i64 = 2500;   // In kbps
+const SLATE_AUDIO_BIT_RATE: i64 = 128;      // In kbps
+
+// Output profile of image audio streams. A still picture needs little video
+// bit rate, so most of the budget is left to the music.
+pub const IMAGE_AUDIO_WIDTH: i32 = 1920;
+pub const IMAGE_AUDIO_HEIGHT: i32 = 1080;
+pub const IMAGE_AUDIO_FRAME_RATE: i32 = 30;
+pub const IMAGE_AUDIO_VIDEO_BIT_RATE: i64 = 1500;  // In kbps
+pub const IMAGE_AUDIO_AUDIO_BIT_RATE: i64 = 192;   // In kbps
+pub const IMAGE_AUDIO_BIT_RATE: i64 = IMAGE_AUDIO_VIDEO_BIT_RATE + IMAGE_AUDIO_AUDIO_BIT_RATE;
+
+// Output profile of ingest streams. The pushed input and the fallback are
+// both encoded to it, so the output ffmpeg can switch between them.
+pub const INGEST_WIDTH: i32 = 1920;
+pub const INGEST_HEIGHT: i32 = 1080;
+pub const INGEST_FRAME_RATE: i32 = 30;
+pub const INGEST_VIDEO_BIT_RATE: i64 = 4500;   // In kbps
+pub const INGEST_AUDIO_BIT_RATE: i64 = 160;    // In kbps
+pub const INGEST_BIT_RATE: i64 = INGEST_VIDEO_BIT_RATE + INGEST_AUDIO_BIT_RATE;
+
+// The continuous input of the output ffmpeg. It is a named pipe that every
+// feeder ffmpeg writes MPEG-TS into, one after another. The pipe is also kept
+// open here, so the output ffmpeg never sees the end of its input while one
+// feeder is being replaced by the next.
+pub struct Pipeline {
+    pub path: PathBuf,
+    _keeper: File
+}
+
+impl Drop for Pipeline {
+    fn drop(&mut self) {
+        // Processes that already opened the pipe keep reading and writing it.
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+pub fn create_pipeline(stream_id: i64) -> Result<Pipeline, std::io::Error> {
+    let path = std::env::temp_dir().join(format!("streamtfhd-{}-{}.ts", stream_id, Uuid::new_v4()));
+
+    mkfifo(&path, Mode::S_IRUSR | Mode::S_IWUSR).map_err(std::io::Error::from)?;
+
+    // Opening a named pipe for both reading and writing does not block on Linux.
+    let keeper = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(&path)?;
+
+    Ok(Pipeline { path, _keeper: keeper })
+}
+
+// Input arguments that loop the background for as long as the feeder runs.
+fn background_args(background: &Background, frame_rate: i32) -> Vec<String> {
+    match background {
+        Background::Image(file) => vec![
+            "-re".to_string(),
+            "-loop".to_string(), "1".to_string(),
+            "-framerate".to_string(), frame_rate.to_string(),
+            "-i".to_string(), file.clone()
+        ],
+        Background::Video(file) => vec![
+            "-re".to_string(),
+            "-stream_loop".to_string(), "-1".to_string(),
+            "-i".to_string(), file.clone()
+        ]
+    }
+}
+
+// A still picture compresses much better with the still image tuning.
+fn background_tune(background: &Background) -> &'static str {
+    match background {
+        Background::Image(_) => "stillimage",
+        Background::Video(_) => "zerolatency"
+    }
+}
+
+// Output arguments of the encoding feeders. The picture is scaled and
+// padded into the frame, audio is always stereo AAC at 44.1 kHz.
+fn encode_args(
+    tune: &str,
+    width: i32,
+    height: i32,
+    frame_rate: i32,
+    video_bit_rate: i64,
+    audio_bit_rate: i64
+) -> Vec<String> {
+    let video_filter = format!(
+        "scale={w}:{h}:force_original_aspect_ratio=decrease,pad={w}:{h}:(ow-iw)/2:(oh-ih)/2,fps={fps},format=yuv420p",
+        w = width,
+        h = height,
+        fps = frame_rate
+    );
+    vec![
+        "-vf".to_string(), video_filter,
+        "-c:v".to_string(), "libx264".to_string(),
+        "-preset".to_string(), "veryfast".to_string(),
+        "-tune".to_string(), tune.to_string(),
+        "-b:v".to_string(), format!("{}k", video_bit_rate),
+        "-maxrate".to_string(), format!("{}k", video_bit_rate),
+        "-bufsize".to_string(), format!("{}k", video_bit_rate * 2),
+        "-g".to_string(), (frame_rate * 2).to_string(),
+        "-c:a".to_string(), "aac".to_string(),
+        "-b:a".to_string(), format!("{}k", audio_bit_rate),
+        "-ar".to_string(), "44100".to_string(),
+        "-ac".to_string(), "2".to_string()
+    ]
+}
+
+fn ms_to_secs(ms: i64) -> String {
+    format!("{:.3}", ms as f64 / 1000.0)
+}
+
+fn feeder_args(item: &SourceItem) -> Vec<String> {
+    // The relay reads the warnings of the ingest listener.
+    let log_level = if matches!(item, SourceItem::Ingest { .. }) { "warning" } else { "error" };
+    let mut args: Vec<String> = ["-hide_banner", "-loglevel", log_level, "-nostdin"]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+
+    match item {
+        SourceItem::Video { file, start, end } => {
+            // Input options, with the video copied the cut lands on the
+            // keyframe at or before `start`.
+            if *start > 0 {
+                args.extend(["-ss".to_string(), ms_to_secs(*start)]);
+            }
+
+            if let Some(end) = end {
+                args.extend(["-to".to_string(), ms_to_secs(*end)]);
+            }
+
+            args.extend([
+                "-re".to_string(),
+                "-i".to_string(), file.clone(),
+                "-map".to_string(), "0:v:0?".to_string(),
+                "-map".to_string(), "0:a:0?".to_string(),
+                "-c".to_string(), "copy".to_string()
+            ]);
+        }
+        SourceItem::ImageAudio { background, audio } => {
+            args.extend(background_args(background, IMAGE_AUDIO_FRAME_RATE));
+            args.extend([
+                "-re".to_string(),
+                "-i".to_string(), audio.clone(),
+                "-map".to_string(), "0:v:0".to_string(),
+                "-map".to_string(), "1:a:0".to_string()
+            ]);
+            args.extend(encode_args(
+                background_tune(background),
+                IMAGE_AUDIO_WIDTH,
+                IMAGE_AUDIO_HEIGHT,
+                IMAGE_AUDIO_FRAME_RATE,
+                IMAGE_AUDIO_VIDEO_BIT_RATE,
+                IMAGE_AUDIO_AUDIO_BIT_RATE
+            ));
+            // The background loops forever, the track decides when the item ends.
+            args.push("-shortest".to_string());
+        }
+        SourceItem::Slate { background, audio, width, height, frame_rate, bit_rate } => {
+            // The slate is encoded, so it fits in after whatever the main content was.
+            let frame_rate = if *frame_rate > 0 { *frame_rate } else { 30 };
+            let bit_rate = if *bit_rate > 0 { *bit_rate } else { DEFAULT_SLATE_BIT_RATE };
+
+            args.extend(background_args(background, frame_rate));
+
+            match audio {
+                Some(file) => args.extend([
+                    "-re".to_string(),
+                    "-stream_loop".to_string(), "-1".to_string(),
+                    "-i".to_string(), file.clone()
+                ]),
+                None => args.extend([
+                    "-f".to_string(), "lavfi".to_string(),
+                    "-i".to_string(), "anullsrc=channel_layout=stereo:sample_rate=44100".to_string()
+                ])
+            }
+
+            args.extend([
+                "-map".to_string(), "0:v:0".to_string(),
+                "-map".to_string(), "1:a:0".to_string()
+            ]);
+            args.extend(encode_args(background_tune(background), *width, *height, frame_rate, bit_rate, SLATE_AUDIO_BIT_RATE));
+        }
+        SourceItem::Ingest { url, .. } => {
+            // Packets are stamped with the wall clock when they arrive, the
+            // publisher may connect long after the feeder was spawned.
+            args.extend([
+                "-use_wallclock_as_timestamps".to_string(), "1".to_string(),
+                "-copyts".to_string()
+            ]);
+
+            if url.starts_with("rtmp://") {
+                args.extend(["-listen".to_string(), "1".to_string()]);
+            }
+
+            args.extend([
+                "-i".to_string(), url.clone(),
+                "-map".to_string(), "0:v:0".to_string(),
+                "-map".to_string(), "0:a:0?".to_string(),
+                "-af".to_string(), "aresample=async=1".to_string()
+            ]);
+            args.extend(encode_args(
+                "zerolatency",
+                INGEST_WIDTH,
+                INGEST_HEIGHT,
+                INGEST_FRAME_RATE,
+                INGEST_VIDEO_BIT_RATE,
+                INGEST_AUDIO_BIT_RATE
+            ));
+        }
+        SourceItem::Filler { file, start } => {
+            match file {
+                Some(file) => {
+                    if *start > 0 {
+                        args.extend(["-ss".to_string(), ms_to_secs(*start)]);
+                    }
+
+                    args.extend([
+                        "-re".to_string(),
+                        "-stream_loop".to_string(), "-1".to_string(),
+                        "-i".to_string(), file.clone(),
+                        "-map".to_string(), "0:v:0".to_string(),
+                        "-map".to_string(), "0:a:0?".to_string()
+                    ]);
+                }
+                None => args.extend([
+                    "-re".to_string(),
+                    "-f".to_string(), "lavfi".to_string(),
+                    "-i".to_string(), format!("color=c=black:s={}x{}:r={}", INGEST_WIDTH, INGEST_HEIGHT, INGEST_FRAME_RATE),
+                    "-f".to_string(), "lavfi".to_string(),
+                    "-i".to_string(), "anullsrc=channel_layout=stereo:sample_rate=44100".to_string(),
+                    "-map".to_string(), "0:v:0".to_string(),
+                    "-map".to_string(), "1:a:0".to_string()
+                ])
+            }
+
+            args.extend(encode_args(
+                "zerolatency",
+                INGEST_WIDTH,
+                INGEST_HEIGHT,
+                INGEST_FRAME_RATE,
+                INGEST_VIDEO_BIT_RATE,
+                INGEST_AUDIO_BIT_RATE
+            ));
+        }
+    }
+
+    args
+}
+
+pub fn spawn_feeder(
+    item: &SourceItem,
+    pipeline_path: &Path,
+    ts_offset: f64
+) -> Result<Child, std::io::Error> {
+    spawn_feeder_to(item, &pipeline_path.to_string_lossy(), ts_offset, false)
+}
+
+// Same as `spawn_feeder`, writing to `output` instead of the pipeline. When
+// piped, the MPEG-TS of `pipe:1` and the log can be read from the child.
+pub fn spawn_feeder_to(
+    item: &SourceItem,
+    output: &str,
+    ts_offset: f64,
+    is_piped: bool
+) -> Result<Child, std::io::Error> {
+    let mut cmd = Command::new("ffmpeg");
+    let mut args = feeder_args(item);
+
+    args.extend([
+        "-output_ts_offset".to_string(), format!("{:.3}", ts_offset),
+        "-f".to_string(), "mpegts".to_string(),
+        "-y".to_string(), output.to_string()
+    ]);
+
+    info!("ffmpeg {}", args.join(" "));
+    cmd.args(&args);
+
+    if is_piped {
+        cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+    } else {
+        cmd.stdout(Stdio::null()).stderr(Stdio::null());
+    }
+
+    cmd.stdin(Stdio::null());
+
+    // Feeders get their own session, a restart of the output ffmpeg group
+    // must not take the feeder down with it.
+    unsafe {
+        cmd.pre_exec(|| {
+            setsid().map_err(std::io::Error::other)?;
+            Ok(())
+        });
+    }
+
+    cmd.spawn()
+}
+
+// SIGTERM lets ffmpeg finish the MPEG-TS packet it is writing, so the output
+// ffmpeg does not read half a packet before the next feeder starts.
+pub async fn stop_feeder(feeder: &mut Child) {
+    if let Some(pid) = feeder.id() {
+        let _ = kill(Pid::from_raw(pid as i32), Signal::SIGTERM);
+    }
+
+    if tokio::time::timeout(FEEDER_STOP_TIMEOUT, feeder.wait()).await.is_err() {
+        let _ = feeder.kill().await;
+    }
+}
+
+pub fn set_feeder_pid(
+    state: &LiveStreamState,
+    stream_id: i64,
+    pid: Option<u32>
+) {
+    if let Some(mut job) = state.jobs.get_mut(&stream_id) {
+        job.feeder_pid = pid;
+    }
+}
+
+fn set_playback(
+    state: &LiveStreamState,
+    stream_id: i64,
+    position_ms: Option<i64>,
+    is_advancing: bool
+) {
+    if let Some(mut job) = state.jobs.get_mut(&stream_id) {
+        job.playback = PlaybackPosition {
+            position_ms,
+            is_advancing,
+            last_out_time: None
+        };
+    }
+}
+
+// Stops counting the output of the job as playback of the video, returns
+// where the video was.
+fn pause_playback(
+    state: &LiveStreamState,
+    stream_id: i64
+) -> Option<i64> {
+    let mut job = state.jobs.get_mut(&stream_id)?;
+
+    job.playback.is_advancing = false;
+    job.playback.position_ms
+}
+
+fn set_loop_iteration(
+    state: &LiveStreamState,
+    stream_id: i64,
+    iteration: u32
+) {
+    if let Some(mut job) = state.jobs.get_mut(&stream_id) && job.loops.iteration != iteration {
+        if iteration > 1 {
+            info!(%stream_id, iteration, "live stream playlist looped");
+        }
+
+        job.loops.iteration = iteration;
+    }
+}
+
+// What plays an item into the pipeline, a feeder ffmpeg or the relay of an
+// ingest live stream, which runs feeders of its own.
+enum Feeder {
+    Process(Child),
+    Relay {
+        task: JoinHandle<()>,
+        stop: Arc<Notify>
+    }
+}
+
+impl Feeder {
+    // Whether the item played to its end. A relay never does.
+    async fn wait(&mut self) -> bool {
+        match self {
+            Feeder::Process(child) => matches!(child.wait().await, Ok(val) if val.success()),
+            Feeder::Relay { task, .. } => {
+                let _ = task.await;
+
+                false
+            }
+        }
+    }
+
+    async fn stop(&mut self) {
+        match self {
+            Feeder::Process(child) => stop_feeder(child).await,
+            Feeder::Relay { task, stop } => {
+                stop.notify_one();
+
+                if !task.is_finished() {
+                    let _ = task.await;
+                }
+            }
+        }
+    }
+}
+
+fn spawn_item(
+    state: &Arc<LiveStreamState>,
+    stream_id: i64,
+    item: &SourceItem,
+    pipeline_path: &Path,
+    started_at: Instant
+) -> Result<Feeder, std::io::Error> {
+    let SourceItem::Ingest { url, fallback, fallback_length } = item else {
+        let ts_offset = started_at.elapsed().as_secs_f64() + FEEDER_TS_GAP;
+        let feeder = spawn_feeder(item, pipeline_path, ts_offset)?;
+
+        set_feeder_pid(state, stream_id, feeder.id());
+
+        return Ok(Feeder::Process(feeder));
+    };
+    let stop = Arc::new(Notify::new());
+    let relay = IngestRelay {
+        url: url.clone(),
+        fallback: fallback.clone().map(|file| (file, *fallback_length)),
+        pipeline_path: pipeline_path.to_path_buf(),
+        started_at
+    };
+    let task = tokio::spawn(relay_ingest(state.clone(), stream_id, relay, stop.clone()));
+
+    Ok(Feeder::Relay { task, stop })
+}
+
+// Fails the job once the output ffmpeg has read what is left of the pipeline.
+fn set_pipeline_error(
+    state: &LiveStreamState,
+    stream_id: i64,
+    reason: &str
+) {
+    warn!(%stream_id, "pipeline gave up: {}", reason);
+
+    if let Some(mut job) = state.jobs.get_mut(&stream_id) {
+        job.pipeline_error = Some(reason.to_string());
+    }
+}
+
+pub fn get_slate(
+    state: &LiveStreamState,
+    stream_id: i64
+) -> Option<SourceItem> {
+    state.jobs.get(&stream_id).and_then(|job| job.slate.clone())
+}
+
+// Plays the playlist into the pipeline, `passes` times or forever without,
+// following the swap commands of the job. When the playlist is done or an item fails the slate
+// of the job takes over, if it has one. Without a slate the pipeline gives up
+// once every item has failed in a row. The first video starts at `resume_at`
+// when given. Returns once the pipeline is done or the job is gone.
+pub async fn run_pipeline(
+    state: Arc<LiveStreamState>,
+    stream_id: i64,
+    pipeline: Pipeline,
+    items: Vec<SourceItem>,
+    passes: Option<u32>,
+    resume_at: Option<i64>,
+    mut commands: UnboundedReceiver<PipelineCommand>
+) {
+    let started_at = Instant::now();
+    let mut items = items;
+    let mut index = 0;
+    let mut pass = 1;
+    let mut on_slate = false;
+    let mut resume_at = resume_at;
+    // Swapped in videos do not count as playback of the live stream.
+    let mut is_original = true;
+    // Items failed in a row, a whole pass of them ends the pipeline.
+    let mut failures = 0;
+
+    loop {
+        if failures > 0 && failures >= items.len() {
+            set_pipeline_error(&state, stream_id, "Every item of the playlist failed to play.");
+            break;
+        }
+
+        let slate = get_slate(&state, stream_id);
+
+        if on_slate && slate.is_none() {
+            on_slate = false;
+        }
+
+        if !on_slate {
+            if index >= items.len() {
+                index = 0;
+                pass += 1;
+            }
+
+            if items.is_empty() || passes.is_some_and(|passes| pass > passes) {
+                if slate.is_none() {
+                    info!(%stream_id, "pipeline playlist finished");
+                    break;
+                }
+
+                info!(%stream_id, "pipeline playlist finished, switching to slate");
+                on_slate = true;
+            }
+        }
+
+        let item = match (on_slate, &slate) {
+            (true, Some(val)) => val.clone(),
+            _ => items[index].clone()
+        };
+        let is_tracked = !on_slate && is_original && matches!(item, SourceItem::Video { .. });
+        let item = match (item, resume_at.take()) {
+            (SourceItem::Video { file, end, .. }, Some(start)) if is_tracked => SourceItem::Video { file, start, end },
+            (item, _) => item
+        };
+
+        if is_tracked && let SourceItem::Video { start, .. } = &item {
+            set_playback(&state, stream_id, Some(*start), true);
+        }
+
+        if !on_slate {
+            set_loop_iteration(&state, stream_id, pass);
+        }
+
+        let is_relay = matches!(item, SourceItem::Ingest { .. });
+        let mut feeder = match spawn_item(&state, stream_id, &item, &pipeline.path, started_at) {
+            Ok(val) => val,
+            Err(err) => {
+                error!("Error while spawning feeder ffmpeg.");
+                debug!("{}.", err);
+
+                if is_tracked {
+                    pause_playback(&state, stream_id);
+                }
+
+                if on_slate {
+                    set_pipeline_error(&state, stream_id, "The slate could not be played.");
+                    break;
+                }
+
+                if slate.is_some() {
+                    on_slate = true;
+                } else {
+                    failures += 1;
+                    index += 1;
+                    tokio::time::sleep(SLATE_RETRY_DELAY).await;
+                }
+
+                continue;
+            }
+        };
+
+        tokio::select! {
+            is_success = feeder.wait() => {
+                let is_last = index + 1 >= items.len() && passes.is_some_and(|passes| pass >= passes);
+
+                if is_tracked && is_success && is_last {
+                    set_playback(&state, stream_id, None, false);
+                } else if is_tracked {
+                    pause_playback(&state, stream_id);
+                }
+
+                if on_slate {
+                    // The slate loops forever, it only exits on errors.
+                    warn!(%stream_id, "slate feeder ffmpeg exited, starting it again");
+                    tokio::time::sleep(SLATE_RETRY_DELAY).await;
+                } else if is_relay {
+                    // So does the relay, which plays its fallback on its own.
+                    warn!(%stream_id, "ingest relay stopped, starting it again");
+                    tokio::time::sleep(SLATE_RETRY_DELAY).await;
+                } else if is_success {
+                    failures = 0;
+                    index += 1;
+                } else if slate.is_some() {
+                    warn!(%stream_id, "feeder ffmpeg failed, switching to slate");
+                    on_slate = true;
+                } else {
+                    warn!(%stream_id, "feeder ffmpeg failed, skipping to the next item");
+                    failures += 1;
+                    index += 1;
+                    tokio::time::sleep(SLATE_RETRY_DELAY).await;
+                }
+            }
+            command = commands.recv() => {
+                match command {
+                    Some(PipelineCommand::Swap(new_items)) => {
+                        info!(%stream_id, "swapping pipeline source to {} items", new_items.len());
+                        feeder.stop().await;
+
+                        if is_tracked {
+                            pause_playback(&state, stream_id);
+                        }
+
+                        on_slate = new_items.is_empty();
+                        is_original = false;
+                        items = new_items;
+                        index = 0;
+                        pass = 1;
+                        failures = 0;
+                    }
+                    Some(PipelineCommand::RestartFeeder) => {
+                        info!(%stream_id, "restarting feeder ffmpeg");
+                        feeder.stop().await;
+
+                        // The video goes on from where it stalled.
+                        if is_tracked {
+                            resume_at = pause_playback(&state, stream_id);
+                        }
+                    }
+                    Some(PipelineCommand::Detach) => {
+                        // The feeder is left running, the output ffmpeg reads
+                        // until it is done with the current item. A relay
+                        // lives in this process and can not be left behind.
+                        info!(%stream_id, "pipeline detached");
+
+                        if is_relay {
+                            feeder.stop().await;
+                        }
+
+                        return;
+                    }
+                    None => {
+                        feeder.stop().await;
+
+                        return;
+                    }
+                }
+            }
+        }
+
+        set_feeder_pid(&state, stream_id, None);
+    }
+
+    // Dropping the pipeline closes its keeper, the output ffmpeg reads what
+    // is left and then finishes with `progress=end`.
+}
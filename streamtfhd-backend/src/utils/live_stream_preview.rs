@@ -0,0 +1,227 @@
+use std::env::var;
+use std::path::PathBuf;
+use tracing::warn;
+
+use crate::dto::live_stream_state::PreviewOutput;
+
+pub const PREVIEW_HLS: &str = "hls";
+pub const PREVIEW_JPEG: &str = "jpeg";
+pub const PREVIEW_PLAYLIST: &str = "index.m3u8";
+pub const PREVIEW_SNAPSHOT: &str = "snapshot.jpg";
+
+#[derive(Debug, Clone)]
+pub struct PreviewConfig {
+    pub format: String,
+    pub height: i32,
+    pub video_bit_rate: i64,
+    pub snapshot_seconds: u64
+}
+
+impl PreviewConfig {
+    pub fn from_env() -> Self {
+        let format = match var("PREVIEW_FORMAT") {
+            Ok(val) if val.trim().eq_ignore_ascii_case(PREVIEW_JPEG) => String::from(PREVIEW_JPEG),
+            _ => String::from(PREVIEW_HLS)
+        };
+        let height = match var("PREVIEW_HEIGHT") {
+            Ok(val) => match val.trim().parse::<i32>() {
+                Ok(val) if (144..=1080).contains(&val) => val,
+                _ => {
+                    warn!("Invalid PREVIEW_HEIGHT value in env file, using 360.");
+
+                    360
+                }
+            },
+            Err(_) => 360
+        };
+        let video_bit_rate = match var("PREVIEW_BIT_RATE") {
+            Ok(val) => match val.trim().parse::<i64>() {
+                Ok(val) if val > 0 => val,
+                _ => {
+                    warn!("Invalid PREVIEW_BIT_RATE value in env file, using 400 kbps.");
+
+                    400
+                }
+            },
+            Err(_) => 400
+        };
+        let snapshot_seconds = match var("PREVIEW_SNAPSHOT_SECONDS") {
+            Ok(val) => match val.trim().parse::<u64>() {
+                Ok(val) if val > 0 => val,
+                _ => {
+                    warn!("Invalid PREVIEW_SNAPSHOT_SECONDS value in env file, using 5 secs.");
+
+                    5
+                }
+            },
+            Err(_) => 5
+        };
+
+        PreviewConfig {
+            format,
+            height,
+            video_bit_rate,
+            snapshot_seconds
+        }
+    }
+}
+
+// Where the preview of a live stream is written. It does not depend on the
+// launch, so the preview of a re-adopted job is still found and removed.
+pub fn preview_dir(upload_dir: &str, stream_id: i64) -> String {
+    format!("{}/previews/{}", upload_dir, stream_id)
+}
+
+pub fn preview_output(
+    stream_id: i64,
+    upload_dir: &str,
+    config: &PreviewConfig
+) -> PreviewOutput {
+    PreviewOutput {
+        dir: preview_dir(upload_dir, stream_id),
+        format: config.format.clone(),
+        height: config.height,
+        video_bit_rate: config.video_bit_rate,
+        snapshot_seconds: config.snapshot_seconds
+    }
+}
+
+// The file the dashboard opens, the playlist or the latest snapshot.
+pub fn preview_entry(preview: &PreviewOutput) -> &'static str {
+    match preview.format.as_str() {
+        PREVIEW_JPEG => PREVIEW_SNAPSHOT,
+        _ => PREVIEW_PLAYLIST
+    }
+}
+
+// Video filter shrinking the outgoing picture for the preview.
+pub fn preview_filter(preview: &PreviewOutput) -> String {
+    match preview.format.as_str() {
+        PREVIEW_JPEG => format!("fps=1/{},scale=-2:{}", preview.snapshot_seconds, preview.height),
+        _ => format!("scale=-2:{}", preview.height)
+    }
+}
+
+// Second output of the output ffmpeg. `video` is the stream to map, the
+// filter is left out when the overlay graph already applies it.
+pub fn preview_args(
+    preview: &PreviewOutput,
+    video: &str,
+    is_filtered: bool
+) -> Vec<String> {
+    let mut args = vec!["-map".to_string(), video.to_string()];
+
+    if !is_filtered {
+        args.extend(["-vf".to_string(), preview_filter(preview)]);
+    }
+
+    match preview.format.as_str() {
+        PREVIEW_JPEG => args.extend([
+            "-q:v".to_string(), "5".to_string(),
+            "-f".to_string(), "image2".to_string(),
+            "-update".to_string(), "1".to_string(),
+            format!("{}/{}", preview.dir, PREVIEW_SNAPSHOT)
+        ]),
+        _ => args.extend([
+            "-map".to_string(), "0:a:0?".to_string(),
+            "-c:v".to_string(), "libx264".to_string(),
+            "-preset".to_string(), "veryfast".to_string(),
+            "-tune".to_string(), "zerolatency".to_string(),
+            "-b:v".to_string(), format!("{}k", preview.video_bit_rate),
+            "-maxrate".to_string(), format!("{}k", preview.video_bit_rate),
+            "-bufsize".to_string(), format!("{}k", preview.video_bit_rate * 2),
+            "-force_key_frames".to_string(), "expr:gte(t,n_forced*2)".to_string(),
+            "-pix_fmt".to_string(), "yuv420p".to_string(),
+            "-c:a".to_string(), "aac".to_string(),
+            "-b:a".to_string(), "64k".to_string(),
+            "-ac".to_string(), "2".to_string(),
+            "-f".to_string(), "hls".to_string(),
+            "-hls_time".to_string(), "2".to_string(),
+            "-hls_list_size".to_string(), "5".to_string(),
+            "-hls_flags".to_string(), "delete_segments".to_string(),
+            "-hls_segment_filename".to_string(), format!("{}/segment_%05d.ts", preview.dir),
+            format!("{}/{}", preview.dir, PREVIEW_PLAYLIST)
+        ])
+    }
+
+    args
+}
+
+// Files left by an earlier ffmpeg would make the new one ask before
+// overwriting them.
+pub fn prepare_preview_dir(preview: &PreviewOutput) -> Result<(), std::io::Error> {
+    let _ = std::fs::remove_dir_all(&preview.dir);
+
+    std::fs::create_dir_all(&preview.dir)
+}
+
+pub fn remove_preview(upload_dir: &str, stream_id: i64) {
+    let dir = preview_dir(upload_dir, stream_id);
+
+    if let Err(err) = std::fs::remove_dir_all(&dir) && err.kind() != std::io::ErrorKind::NotFound {
+        warn!(stream_id, "failed to remove live stream preview: {}", err);
+    }
+}
+
+// A file of the preview of a live stream. Only the playlist, its segments and
+// the snapshot are served.
+pub fn preview_file(
+    upload_dir: &str,
+    stream_id: i64,
+    file: &str
+) -> Option<(PathBuf, &'static str)> {
+    let is_segment = file
+        .strip_prefix("segment_")
+        .and_then(|rest| rest.strip_suffix(".ts"))
+        .is_some_and(|number| !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()));
+    let content_type = match file {
+        PREVIEW_PLAYLIST => "application/vnd.apple.mpegurl",
+        PREVIEW_SNAPSHOT => "image/jpeg",
+        _ if is_segment => "video/mp2t",
+        _ => return None
+    };
+
+    Some((PathBuf::from(preview_dir(upload_dir, stream_id)).join(file), content_type))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preview(format: &str) -> PreviewOutput {
+        PreviewOutput {
+            dir: String::from("/uploads/previews/7"),
+            format: format.to_string(),
+            height: 360,
+            video_bit_rate: 400,
+            snapshot_seconds: 5
+        }
+    }
+
+    #[test]
+    fn preview_is_written_as_hls_or_snapshots() {
+        let hls = preview_args(&preview(PREVIEW_HLS), "0:v:0", false);
+
+        assert_eq!(hls[..4], ["-map", "0:v:0", "-vf", "scale=-2:360"]);
+        assert_eq!(hls.last().map(String::as_str), Some("/uploads/previews/7/index.m3u8"));
+
+        let jpeg = preview_args(&preview(PREVIEW_JPEG), "[pvout]", true);
+
+        assert_eq!(jpeg[..2], ["-map", "[pvout]"]);
+        assert!(!jpeg.contains(&"-vf".to_string()));
+        assert_eq!(jpeg.last().map(String::as_str), Some("/uploads/previews/7/snapshot.jpg"));
+        assert_eq!(preview_filter(&preview(PREVIEW_JPEG)), "fps=1/5,scale=-2:360");
+    }
+
+    #[test]
+    fn only_preview_files_are_served() {
+        assert_eq!(
+            preview_file("/uploads", 7, "segment_00012.ts"),
+            Some((PathBuf::from("/uploads/previews/7/segment_00012.ts"), "video/mp2t"))
+        );
+        assert!(preview_file("/uploads", 7, "index.m3u8").is_some());
+        assert!(preview_file("/uploads", 7, "../../videos/a.mp4").is_none());
+        assert!(preview_file("/uploads", 7, "segment_../1.ts").is_none());
+        assert!(preview_file("/uploads", 7, "segment_.ts").is_none());
+    }
+}
@@ -0,0 +1,164 @@
+use sqlx::{Pool, Postgres};
+use std::env::var;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    dto::live_stream_destination::DestinationOutput,
+    dto::live_stream_state::RecordingOutput,
+    models::gallery_upload_video::{create_video_thumbnail, save_video_info_to_database},
+    models::live_stream_recording::link_recording
+};
+
+#[derive(Debug, Clone)]
+pub struct RecordingConfig {
+    pub segment_seconds: u64,
+    pub format: String
+}
+
+impl RecordingConfig {
+    pub fn from_env() -> Self {
+        let segment_seconds = match var("RECORDING_SEGMENT_SECONDS") {
+            Ok(val) => match val.trim().parse::<u64>() {
+                Ok(val) if val > 0 => val,
+                _ => {
+                    warn!("Invalid RECORDING_SEGMENT_SECONDS value in env file, using 3600 secs.");
+
+                    3600
+                }
+            },
+            Err(_) => 3600
+        };
+        // Matroska stays readable when ffmpeg is killed in the middle of a
+        // segment, MP4 segments are fragmented for the same reason.
+        let format = match var("RECORDING_FORMAT") {
+            Ok(val) if val.trim().eq_ignore_ascii_case("mp4") => String::from("mp4"),
+            _ => String::from("mkv")
+        };
+
+        RecordingConfig {
+            segment_seconds,
+            format
+        }
+    }
+}
+
+pub fn recording_output(
+    stream_id: i64,
+    title: &str,
+    upload_dir: &str,
+    config: &RecordingConfig
+) -> RecordingOutput {
+    RecordingOutput {
+        upload_dir: upload_dir.to_string(),
+        prefix: format!("{}-recording-{}-", Uuid::new_v4(), stream_id),
+        title: title.to_string(),
+        segment_seconds: config.segment_seconds,
+        format: config.format.clone()
+    }
+}
+
+// Output writing the segments. Segments are named after the time they
+// start, so an output ffmpeg spawned again does not overwrite the ones before it.
+pub fn recording_destination(recording: &RecordingOutput) -> DestinationOutput {
+    let mut options = vec![
+        (String::from("segment_time"), recording.segment_seconds.to_string()),
+        (String::from("segment_format"), String::from(if recording.format == "mp4" { "mp4" } else { "matroska" }))
+    ];
+
+    if recording.format == "mp4" {
+        options.push((String::from("segment_format_options"), String::from("movflags=+frag_keyframe+empty_moov+default_base_moof")));
+    }
+
+    options.push((String::from("reset_timestamps"), String::from("1")));
+    options.push((String::from("strftime"), String::from("1")));
+
+    DestinationOutput {
+        format: String::from("segment"),
+        options,
+        target: format!("{}/videos/{}%Y%m%d-%H%M%S.{}", recording.upload_dir, recording.prefix, recording.format),
+        local_dir: None
+    }
+}
+
+// Segment files of a recording in the order they were written.
+fn segment_files(recording: &RecordingOutput) -> Vec<String> {
+    let entries = match std::fs::read_dir(format!("{}/videos", recording.upload_dir)) {
+        Ok(val) => val,
+        Err(err) => {
+            warn!("Failed to read the recording directory: {}", err);
+
+            return Vec::new();
+        }
+    };
+    let mut files: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.metadata().is_ok_and(|meta| meta.is_file() && meta.len() > 0))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.starts_with(&recording.prefix))
+        .collect();
+
+    files.sort();
+    files
+}
+
+// Adds the finished segments of a run to the gallery of the owner and links
+// them to the history row of the run. Segments ffprobe can not read are left
+// on disk for inspection.
+pub async fn register_recordings(
+    history: i64,
+    recording: RecordingOutput,
+    owner: String,
+    pool: Pool<Postgres>
+) {
+    let upload_dir = &recording.upload_dir;
+    let files = segment_files(&recording);
+
+    info!(history, "registering {} recording segments", files.len());
+
+    for (index, file) in files.iter().enumerate() {
+        let part = index as i32 + 1;
+        let title = format!("{} (recording {})", recording.title, part);
+        let thumbnail = match create_video_thumbnail(file, upload_dir).await {
+            Ok(val) => val,
+            Err(err) => {
+                warn!(history, "failed to create thumbnail of recording {}: {}", file, err);
+
+                continue;
+            }
+        };
+        let video = match save_video_info_to_database(&pool, upload_dir, &title, file, &thumbnail, &owner).await {
+            Ok(val) => val,
+            Err(err) => {
+                warn!(history, "failed to register recording {}: {}", file, err);
+
+                continue;
+            }
+        };
+
+        link_recording(history, part, video, &pool).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::live_stream_destination::tee_slave;
+
+    #[test]
+    fn recording_writes_timestamped_segments() {
+        let recording = RecordingOutput {
+            upload_dir: String::from("/uploads"),
+            prefix: String::from("abc-recording-7-"),
+            title: String::from("Morning show"),
+            segment_seconds: 3600,
+            format: String::from("mkv")
+        };
+
+        assert_eq!(
+            tee_slave(&recording_destination(&recording), true),
+            "[f=segment:segment_time=3600:segment_format=matroska:reset_timestamps=1:strftime=1:bsfs/a=aac_adtstoasc:onfail=ignore]/uploads/videos/abc-recording-7-%Y%m%d-%H%M%S.mkv"
+        );
+        assert!(tee_slave(&recording_destination(&RecordingOutput { format: String::from("mp4"), ..recording }), true).contains("movflags=+frag_keyframe"));
+    }
+}
@@ -0,0 +1,90 @@
+use std::sync::Arc;
+use std::time::Duration;
+use sysinfo::{Pid, ProcessesToUpdate, System};
+
+use crate::dto::live_stream_state::LiveStreamState;
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(2);
+
+struct GroupUsage {
+    cpu_percent: f32,
+    memory_kb: u64,
+    disk_read_bytes: u64
+}
+
+// ffmpeg is spawned with `setsid`, so every process it starts shares its
+// session id, which is the pid of the ffmpeg leader.
+fn group_usage(sys: &System, leader: u32) -> GroupUsage {
+    let leader_pid = Pid::from_u32(leader);
+    let mut usage = GroupUsage {
+        cpu_percent: 0.0,
+        memory_kb: 0,
+        disk_read_bytes: 0
+    };
+
+    for (pid, process) in sys.processes() {
+        if *pid != leader_pid && process.session_id() != Some(leader_pid) {
+            continue;
+        }
+
+        usage.cpu_percent += process.cpu_usage();
+        usage.memory_kb += process.memory() / 1024;
+        usage.disk_read_bytes += process.disk_usage().total_read_bytes;
+    }
+
+    usage
+}
+
+pub async fn resources_collector(state: Arc<LiveStreamState>) {
+    let mut sys = System::new();
+
+    loop {
+        tokio::time::sleep(SAMPLE_INTERVAL).await;
+
+        let leaders: Vec<(i64, u32, Option<u32>)> = state.jobs
+            .iter()
+            .filter_map(|entry| {
+                let job = entry.value();
+                let pid = match &job.output {
+                    Some(output) => output.pid()?,
+                    None => job.pid?
+                };
+
+                Some((*entry.key(), pid, job.feeder_pid))
+            })
+            .collect();
+
+        if leaders.is_empty() {
+            continue;
+        }
+
+        sys.refresh_processes(ProcessesToUpdate::All, true);
+
+        for (stream_id, leader, feeder) in leaders {
+            let mut usage = group_usage(&sys, leader);
+
+            // The feeder runs in a session of its own.
+            if let Some(feeder) = feeder {
+                let feeder_usage = group_usage(&sys, feeder);
+
+                usage.cpu_percent += feeder_usage.cpu_percent;
+                usage.memory_kb += feeder_usage.memory_kb;
+                usage.disk_read_bytes += feeder_usage.disk_read_bytes;
+            }
+
+            if let Some(mut job) = state.jobs.get_mut(&stream_id) {
+                let net_tx_bytes = job.progress.total_size;
+                let resources = &mut job.resources;
+
+                resources.cpu_percent = usage.cpu_percent;
+                resources.memory_kb = usage.memory_kb;
+                resources.disk_read_bytes = usage.disk_read_bytes;
+                resources.net_tx_bytes = net_tx_bytes;
+                resources.samples += 1;
+                resources.cpu_percent_sum += usage.cpu_percent as f64;
+                resources.peak_cpu_percent = resources.peak_cpu_percent.max(usage.cpu_percent);
+                resources.peak_memory_kb = resources.peak_memory_kb.max(usage.memory_kb);
+            }
+        }
+    }
+}
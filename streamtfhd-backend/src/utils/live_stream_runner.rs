@@ -0,0 +1,380 @@
+use futures_util::future::BoxFuture;
+use nix::sys::signal::{killpg, Signal};
+use nix::unistd::{Pid, setsid};
+use std::env::var;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::mpsc;
+use tokio::time::Duration;
+use tracing::info;
+
+use crate::{
+    dto::live_stream_runner::{OutputProcess, Playlist, RunnerEvent, RunnerOutput, StreamRunner},
+    dto::live_stream_state::{FfmpegLaunch, FfmpegProgress, LiveStreamState},
+    utils::live_stream_destination::{failed_tee_slave, output_args, tee_slave},
+    utils::live_stream_overlay::build_filter_graph,
+    utils::live_stream_pipeline::{create_pipeline, run_pipeline},
+    utils::live_stream_preview::{preview_args, preview_filter, prepare_preview_dir},
+    utils::live_stream_recording::recording_destination
+};
+
+// Time a gracefully stopped output ffmpeg gets before it is killed.
+const OUTPUT_STOP_GRACE: Duration = Duration::from_millis(500);
+
+// The output ffmpeg reads the pipeline of the job and pushes it to the
+// destination, it stays connected while the feeders come and go. The video is
+// copied, unless there are overlays to draw over it.
+fn spawn_ffmpeg(launch: &FfmpegLaunch) -> Result<Child, std::io::Error> {
+    let input = launch.input.to_string_lossy().to_string();
+
+    info!("Spawning ffmpeg using {}", input);
+
+    for output in &launch.outputs {
+        if let Some(dir) = &output.local_dir {
+            std::fs::create_dir_all(dir)?;
+        }
+    }
+
+    if let Some(preview) = &launch.preview {
+        prepare_preview_dir(preview)?;
+    }
+
+    // Every endpoint at once when redundant, only the active one otherwise.
+    let outputs = match launch.redundant {
+        true => &launch.outputs[..],
+        false => &launch.outputs[launch.active..=launch.active]
+    };
+    let is_teed = outputs.len() > 1 || launch.recording.is_some();
+    let font_file = var("OVERLAY_FONT_FILE").ok().filter(|val| !val.trim().is_empty());
+    let filter_graph = build_filter_graph(&launch.overlays, launch.width, font_file.as_deref());
+
+    let mut cmd = Command::new("ffmpeg");
+    let mut args: Vec<String> = vec![
+        "-f".to_string(), "mpegts".to_string(),
+        "-i".to_string(), input
+    ];
+
+    if let Some(graph) = &filter_graph {
+        for file in &graph.inputs {
+            args.extend(["-loop".to_string(), "1".to_string(), "-i".to_string(), file.clone()]);
+        }
+    }
+
+    // machine-readable progress
+    args.extend([
+        "-progress".to_string(), "pipe:2".to_string(),
+        "-stats_period".to_string(), "1".to_string()
+    ]);
+
+    match &filter_graph {
+        Some(graph) => {
+            let frame_rate = if launch.frame_rate > 0 { launch.frame_rate } else { 30 };
+            let bit_rate = if launch.video_bit_rate > 0 { launch.video_bit_rate } else { 2500 };
+            // The preview is split off after the overlays are drawn.
+            let (filter_complex, video) = match &launch.preview {
+                Some(preview) => (
+                    format!("{};[{}]split=2[vmain][pvin];[pvin]{}[pvout]", graph.graph, graph.output, preview_filter(preview)),
+                    String::from("vmain")
+                ),
+                None => (graph.graph.clone(), graph.output.clone())
+            };
+
+            args.extend([
+                "-filter_complex".to_string(), filter_complex,
+                "-map".to_string(), format!("[{}]", video),
+                "-map".to_string(), "0:a:0?".to_string(),
+                "-c:v".to_string(), "libx264".to_string(),
+                "-preset".to_string(), "veryfast".to_string(),
+                "-tune".to_string(), "zerolatency".to_string(),
+                "-b:v".to_string(), format!("{}k", bit_rate),
+                "-maxrate".to_string(), format!("{}k", bit_rate),
+                "-bufsize".to_string(), format!("{}k", bit_rate * 2),
+                "-g".to_string(), (frame_rate * 2).to_string(),
+                "-pix_fmt".to_string(), "yuv420p".to_string()
+            ]);
+        }
+        None => {
+            // The tee muxer does not pick streams on its own.
+            if is_teed {
+                args.extend([
+                    "-map".to_string(), "0:v:0?".to_string(),
+                    "-map".to_string(), "0:a:0?".to_string()
+                ]);
+            }
+
+            args.extend([
+                "-c:v".to_string(), "copy".to_string(),
+                "-preset".to_string(), "veryfast".to_string(),
+                "-tune".to_string(), "zerolatency".to_string()
+            ]);
+        }
+    }
+
+    args.extend(["-c:a".to_string(), "copy".to_string()]);
+
+    if is_teed {
+        if filter_graph.is_some() {
+            args.extend(["-flags".to_string(), "+global_header".to_string()]);
+        }
+
+        // Losing the recording or one of the redundant endpoints leaves the
+        // broadcast up, losing the only endpoint ends ffmpeg like it always did.
+        let mut slaves: Vec<String> = outputs
+            .iter()
+            .map(|output| tee_slave(output, launch.redundant))
+            .collect();
+
+        if let Some(recording) = &launch.recording {
+            slaves.push(tee_slave(&recording_destination(recording), true));
+        }
+
+        args.extend(["-f".to_string(), "tee".to_string(), slaves.join("|")]);
+    } else {
+        args.extend(output_args(&outputs[0]));
+    }
+
+    if let Some(preview) = &launch.preview {
+        match &filter_graph {
+            Some(_) => args.extend(preview_args(preview, "[pvout]", true)),
+            None => args.extend(preview_args(preview, "0:v:0", false))
+        }
+    }
+
+    info!("ffmpeg {}", args.join(" "));
+    cmd.args(&args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::piped())
+        .stdout(Stdio::null());
+
+    unsafe {
+        cmd.pre_exec(|| {
+            setsid().map_err(std::io::Error::other)?;
+            Ok(())
+        });
+    }
+
+    cmd.spawn()
+}
+
+// Applies one `key=value` line of ffmpeg `-progress` output.
+fn apply_progress_line(progress: &mut FfmpegProgress, line: &str) {
+    let (key, value) = match line.split_once('=') {
+        Some(val) => val,
+        None => return
+    };
+    let value = value.trim();
+
+    match key {
+        "bitrate" => {
+            progress.bitrate_kbps = value.trim_end_matches("kbits/s").parse().unwrap_or(0.0);
+        }
+        "fps" => {
+            progress.fps = value.parse().unwrap_or(0.0);
+        }
+        "speed" => {
+            progress.speed = value.trim_end_matches('x').parse().unwrap_or(0.0);
+        }
+        "drop_frames" => {
+            progress.drop_frames = value.parse().unwrap_or(progress.drop_frames);
+        }
+        "dup_frames" => {
+            progress.dup_frames = value.parse().unwrap_or(progress.dup_frames);
+        }
+        "out_time_ms" => {
+            progress.out_time_ms = value.parse().unwrap_or(progress.out_time_ms);
+        }
+        "total_size" => {
+            progress.total_size = value.parse().unwrap_or(progress.total_size);
+        }
+        _ => {}
+    }
+}
+
+// Lines of the output ffmpeg that mean it lost its endpoint.
+fn is_endpoint_failure(line: &str) -> bool {
+    [
+        "Broken pipe",
+        "Connection refused",
+        "Connection reset by peer",
+        "Connection timed out",
+        "failed, aborting"
+    ].iter().any(|pattern| line.contains(pattern))
+}
+
+// Turns the stderr of the output ffmpeg into runner events.
+#[derive(Debug, Default)]
+pub struct FfmpegEvents {
+    progress: FfmpegProgress
+}
+
+impl FfmpegEvents {
+    pub fn read_line(&mut self, line: &str) -> Vec<RunnerEvent> {
+        apply_progress_line(&mut self.progress, line);
+
+        if line == "progress=continue" {
+            return vec![RunnerEvent::Progress(self.progress.clone())];
+        }
+
+        // The last report still moves the playback position.
+        if line == "progress=end" {
+            return vec![RunnerEvent::Progress(self.progress.clone()), RunnerEvent::Finished];
+        }
+
+        if let Some(slave) = failed_tee_slave(line) {
+            return vec![RunnerEvent::SlaveFailed(slave)];
+        }
+
+        if line.contains("error") {
+            return vec![RunnerEvent::Error(line.to_string())];
+        }
+
+        if is_endpoint_failure(line) {
+            return vec![RunnerEvent::EndpointFailed(line.to_string())];
+        }
+
+        Vec::new()
+    }
+}
+
+async fn kill_group(pid: Option<u32>, graceful: bool) {
+    let pid = match pid {
+        Some(val) => Pid::from_raw(val as i32),
+        None => return
+    };
+
+    if graceful {
+        info!(pid = pid.as_raw(), "SIGTERM ffmpeg group");
+        let _ = killpg(pid, Signal::SIGTERM);
+
+        tokio::time::sleep(OUTPUT_STOP_GRACE).await;
+    }
+
+    info!(pid = pid.as_raw(), "SIGKILL ffmpeg group");
+    let _ = killpg(pid, Signal::SIGKILL);
+}
+
+pub struct FfmpegOutput {
+    child: Child
+}
+
+impl OutputProcess for FfmpegOutput {
+    fn pid(&self) -> Option<u32> {
+        self.child.id()
+    }
+
+    fn stop(mut self: Box<Self>, graceful: bool) -> BoxFuture<'static, ()> {
+        Box::pin(async move {
+            kill_group(self.child.id(), graceful).await;
+
+            let _ = self.child.wait().await;
+        })
+    }
+}
+
+// Output ffmpeg left running by the previous shutdown. It is not a child of
+// this process, so it can not be waited on.
+pub struct AdoptedOutput {
+    pub pid: u32
+}
+
+impl OutputProcess for AdoptedOutput {
+    fn pid(&self) -> Option<u32> {
+        Some(self.pid)
+    }
+
+    fn stop(self: Box<Self>, graceful: bool) -> BoxFuture<'static, ()> {
+        Box::pin(kill_group(Some(self.pid), graceful))
+    }
+}
+
+// Feeder ffmpegs write the playlist into a named pipe, the output ffmpeg
+// reads it.
+pub struct FfmpegRunner;
+
+impl StreamRunner for FfmpegRunner {
+    fn start_pipeline(
+        &self,
+        state: &Arc<LiveStreamState>,
+        stream_id: i64,
+        playlist: Playlist
+    ) -> Result<PathBuf, std::io::Error> {
+        let pipeline = create_pipeline(stream_id)?;
+        let path = pipeline.path.clone();
+
+        tokio::spawn(run_pipeline(
+            state.clone(),
+            stream_id,
+            pipeline,
+            playlist.items,
+            playlist.passes,
+            playlist.resume_at,
+            playlist.commands
+        ));
+
+        Ok(path)
+    }
+
+    fn spawn_output(&self, launch: &FfmpegLaunch) -> Result<RunnerOutput, std::io::Error> {
+        let mut child = spawn_ffmpeg(launch)?;
+        let stderr = child.stderr.take();
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        tokio::spawn(async move {
+            let Some(stderr) = stderr else {
+                return;
+            };
+            let mut lines = BufReader::new(stderr).lines();
+            let mut events = FfmpegEvents::default();
+
+            while let Ok(Some(line)) = lines.next_line().await {
+                for event in events.read_line(&line) {
+                    if tx.send(event).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        Ok(RunnerOutput {
+            process: Box::new(FfmpegOutput { child }),
+            events: rx
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ffmpeg_output_is_read_as_events() {
+        let mut events = FfmpegEvents::default();
+
+        assert!(events.read_line("out_time_ms=2000000").is_empty());
+        assert!(events.read_line("speed=1.01x").is_empty());
+
+        match events.read_line("progress=continue").as_slice() {
+            [RunnerEvent::Progress(progress)] => {
+                assert_eq!(progress.out_time_ms, 2000000);
+                assert_eq!(progress.speed, 1.01);
+            }
+            other => panic!("unexpected events {:?}", other)
+        }
+
+        assert!(matches!(
+            events.read_line("[flv @ 0x55] Failed to update header with correct duration: Broken pipe").as_slice(),
+            [RunnerEvent::EndpointFailed(_)]
+        ));
+        assert!(matches!(
+            events.read_line("Error muxing a packet, error code -32").as_slice(),
+            [RunnerEvent::Error(_)]
+        ));
+        assert!(matches!(
+            events.read_line("progress=end").as_slice(),
+            [RunnerEvent::Progress(_), RunnerEvent::Finished]
+        ));
+    }
+}
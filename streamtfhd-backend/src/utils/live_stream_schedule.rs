@@ -0,0 +1,211 @@
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    dto::live_stream_schedule::{ScheduleCandidate, ScheduleWindow},
+    dto::live_stream_source::{MODE_IMAGE_AUDIO, MODE_INGEST},
+    errors::AppError,
+    models::live_stream_schedule::{
+        get_overlapping_windows,
+        get_same_destination_ids,
+        get_video_bit_rate
+    },
+    utils::live_stream_admission::AdmissionConfig,
+    utils::live_stream_pipeline::{IMAGE_AUDIO_BIT_RATE, INGEST_BIT_RATE}
+};
+
+// Highest usage reached by the given windows at any point in [start, end).
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct PeakUsage {
+    pub streams: u64,
+    pub streams_for_owner: u64,
+    pub bit_rate: u64
+}
+
+pub fn peak_usage(
+    windows: &[ScheduleWindow],
+    owner: &str,
+    start: i64,
+    end: Option<i64>
+) -> PeakUsage {
+    let end = end.unwrap_or(i64::MAX);
+    // (time, is_start, is_owner, bit rate)
+    let mut events: Vec<(i64, bool, bool, u64)> = Vec::new();
+
+    for window in windows {
+        let window_start = window.schedule_start.max(start);
+        let window_end = window.schedule_end.unwrap_or(i64::MAX).min(end);
+
+        if window_start >= window_end {
+            continue;
+        }
+
+        let is_owner = window.owner == owner;
+        let bit_rate = window.bit_rate.max(0) as u64;
+
+        events.push((window_start, true, is_owner, bit_rate));
+        events.push((window_end, false, is_owner, bit_rate));
+    }
+
+    // Windows are half open, so an end is handled before a start at the same time.
+    events.sort_by_key(|(time, is_start, _, _)| (*time, *is_start));
+
+    let mut current = PeakUsage::default();
+    let mut peak = PeakUsage::default();
+
+    for (_, is_start, is_owner, bit_rate) in events {
+        if is_start {
+            current.streams += 1;
+            current.bit_rate += bit_rate;
+
+            if is_owner {
+                current.streams_for_owner += 1;
+            }
+        } else {
+            current.streams -= 1;
+            current.bit_rate -= bit_rate;
+
+            if is_owner {
+                current.streams_for_owner -= 1;
+            }
+        }
+
+        peak.streams = peak.streams.max(current.streams);
+        peak.streams_for_owner = peak.streams_for_owner.max(current.streams_for_owner);
+        peak.bit_rate = peak.bit_rate.max(current.bit_rate);
+    }
+
+    peak
+}
+
+// Compares the peak usage of the other windows plus the new one against the admission limits.
+pub fn check_schedule_limits(
+    peak: &PeakUsage,
+    bit_rate: u64,
+    config: &AdmissionConfig
+) -> Result<(), String> {
+    if config.max_concurrent_streams > 0 && peak.streams >= config.max_concurrent_streams {
+        return Err(format!("The schedule would exceed the maximum of {} concurrent live streams", config.max_concurrent_streams));
+    }
+
+    if config.max_streams_per_user > 0 && peak.streams_for_owner >= config.max_streams_per_user {
+        return Err(format!("The schedule would exceed the maximum of {} concurrent live streams per user", config.max_streams_per_user));
+    }
+
+    if config.max_total_bit_rate > 0 && peak.bit_rate + bit_rate > config.max_total_bit_rate {
+        return Err(format!("The schedule would exceed the total outgoing bit rate of {} kbps", config.max_total_bit_rate));
+    }
+
+    Ok(())
+}
+
+// Validates the schedule of a live stream being created or edited. Streams
+// without a schedule start are started by hand and are checked on admission.
+pub async fn validate_schedule(
+    candidate: &ScheduleCandidate,
+    pool: &Pool<Postgres>
+) -> Result<(), AppError> {
+    let schedule_end = candidate.schedule_end;
+    let start = match candidate.schedule_start {
+        Some(val) => val,
+        None => return Ok(())
+    };
+
+    if let Some(end) = schedule_end && end <= start {
+        return Err(AppError::ValidationError("Schedule end must be later than schedule start.".to_string()));
+    }
+
+    let windows = get_overlapping_windows(candidate.id, start, schedule_end, pool).await?;
+    let ids: Vec<i64> = windows.iter().map(|window| window.id).collect();
+    let same_destination = match candidate.dry_run {
+        true => Vec::new(),
+        false => get_same_destination_ids(&ids, &candidate.endpoints, &candidate.stream_key, pool).await?
+    };
+
+    if let Some(conflict) = windows.iter().find(|window| same_destination.contains(&window.id)) {
+        return Err(AppError::Conflict(format!(
+            "The schedule overlaps with live stream \"{}\" which uses the same destination endpoint and stream key.",
+            conflict.title
+        )));
+    }
+
+    // Image audio and ingest streams are encoded with a fixed profile,
+    // whatever the background or fallback video.
+    let bit_rate = match (candidate.mode.as_str(), candidate.video) {
+        (MODE_IMAGE_AUDIO, _) => IMAGE_AUDIO_BIT_RATE as u64,
+        (MODE_INGEST, _) => INGEST_BIT_RATE as u64,
+        (_, Some(video)) => match get_video_bit_rate(video, pool).await? {
+            Some(val) => val.max(0) as u64,
+            None => return Err(AppError::BadRequest("Invalid video ID".to_string()))
+        },
+        (_, None) => return Err(AppError::BadRequest("Invalid video ID".to_string()))
+    };
+    // A dry run sends nothing out, it only counts as a stream.
+    let bit_rate = match candidate.dry_run {
+        true => 0,
+        false => bit_rate
+    };
+    let peak = peak_usage(&windows, &candidate.owner, start, schedule_end);
+
+    if let Err(err) = check_schedule_limits(&peak, bit_rate, &AdmissionConfig::from_env()) {
+        return Err(AppError::Conflict(err));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::live_stream_admission::AdmissionPolicy;
+
+    fn window(id: i64, owner: &str, start: i64, end: Option<i64>, bit_rate: i32) -> ScheduleWindow {
+        ScheduleWindow {
+            id,
+            owner: owner.to_string(),
+            title: format!("Stream {}", id),
+            schedule_start: start,
+            schedule_end: end,
+            bit_rate
+        }
+    }
+
+    #[test]
+    fn peak_usage_counts_only_simultaneous_windows() {
+        let windows = vec![
+            window(1, "a", 0, Some(100), 1000),
+            window(2, "b", 100, Some(200), 2000),
+            window(3, "a", 150, None, 500)
+        ];
+        let peak = peak_usage(&windows, "a", 0, Some(300));
+
+        assert_eq!(peak, PeakUsage { streams: 2, streams_for_owner: 1, bit_rate: 2500 });
+    }
+
+    #[test]
+    fn peak_usage_ignores_time_outside_the_new_window() {
+        let windows = vec![
+            window(1, "a", 0, Some(100), 1000),
+            window(2, "a", 50, Some(200), 1000)
+        ];
+        let peak = peak_usage(&windows, "a", 100, Some(200));
+
+        assert_eq!(peak, PeakUsage { streams: 1, streams_for_owner: 1, bit_rate: 1000 });
+    }
+
+    #[test]
+    fn check_schedule_limits_rejects_when_full() {
+        let config = AdmissionConfig {
+            max_concurrent_streams: 2,
+            max_streams_per_user: 0,
+            max_total_bit_rate: 5000,
+            min_free_cpu_percent: 0,
+            min_free_memory_mb: 0,
+            policy: AdmissionPolicy::Reject
+        };
+        let peak = PeakUsage { streams: 1, streams_for_owner: 1, bit_rate: 3000 };
+
+        assert!(check_schedule_limits(&peak, 2000, &config).is_ok());
+        assert!(check_schedule_limits(&peak, 2001, &config).is_err());
+        assert!(check_schedule_limits(&PeakUsage { streams: 2, ..peak }, 0, &config).is_err());
+    }
+}
@@ -0,0 +1,131 @@
+use futures_util::future::BoxFuture;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+
+use crate::{
+    dto::live_stream_runner::{OutputProcess, Playlist, RunnerEvent, RunnerOutput, StreamRunner},
+    dto::live_stream_state::{FfmpegLaunch, FfmpegProgress, LiveStreamState, PipelineCommand}
+};
+
+// What one spawned output does, in spawn order.
+pub enum Script {
+    // The output cannot be spawned.
+    Refuse,
+    // Sends every event after its delay from the one before, then stays up
+    // without a word until it is stopped.
+    Run(Vec<(Duration, RunnerEvent)>)
+}
+
+// What the state machine asked of the runner.
+#[derive(Debug, Clone, Default)]
+pub struct RunnerLog {
+    pub pipelines: usize,
+    pub spawns: usize,
+    pub stops: Vec<bool>,       // Graceful flag of every stopped output
+    pub feeder_restarts: usize
+}
+
+// Runner for tests, nothing is spawned. Outputs past the last script stay
+// up without reporting anything, like a stalled ffmpeg.
+#[derive(Default)]
+pub struct ScriptedRunner {
+    scripts: Mutex<VecDeque<Script>>,
+    log: Arc<Mutex<RunnerLog>>
+}
+
+impl ScriptedRunner {
+    pub fn new(scripts: Vec<Script>) -> Self {
+        ScriptedRunner {
+            scripts: Mutex::new(scripts.into()),
+            log: Arc::default()
+        }
+    }
+
+    pub fn log(&self) -> RunnerLog {
+        self.log.lock().unwrap().clone()
+    }
+}
+
+// One second of output at `second`.
+pub fn progress(second: i64) -> RunnerEvent {
+    RunnerEvent::Progress(FfmpegProgress {
+        speed: 1.0,
+        out_time_ms: second * 1_000_000,
+        total_size: second as u64 * 250_000,
+        ..FfmpegProgress::default()
+    })
+}
+
+struct ScriptedOutput {
+    task: JoinHandle<()>,
+    log: Arc<Mutex<RunnerLog>>
+}
+
+impl OutputProcess for ScriptedOutput {
+    fn pid(&self) -> Option<u32> {
+        None
+    }
+
+    fn stop(self: Box<Self>, graceful: bool) -> BoxFuture<'static, ()> {
+        // Dropping the sender closes the events of the output.
+        self.task.abort();
+        self.log.lock().unwrap().stops.push(graceful);
+
+        Box::pin(async {})
+    }
+}
+
+impl StreamRunner for ScriptedRunner {
+    fn start_pipeline(
+        &self,
+        _state: &Arc<LiveStreamState>,
+        stream_id: i64,
+        mut playlist: Playlist
+    ) -> Result<PathBuf, std::io::Error> {
+        let log = self.log.clone();
+
+        log.lock().unwrap().pipelines += 1;
+
+        tokio::spawn(async move {
+            while let Some(command) = playlist.commands.recv().await {
+                if matches!(command, PipelineCommand::RestartFeeder) {
+                    log.lock().unwrap().feeder_restarts += 1;
+                }
+            }
+        });
+
+        Ok(PathBuf::from(format!("scripted-{}", stream_id)))
+    }
+
+    fn spawn_output(&self, _launch: &FfmpegLaunch) -> Result<RunnerOutput, std::io::Error> {
+        let script = self.scripts.lock().unwrap().pop_front().unwrap_or(Script::Run(Vec::new()));
+
+        self.log.lock().unwrap().spawns += 1;
+
+        let events = match script {
+            Script::Refuse => return Err(std::io::Error::other("scripted spawn failure")),
+            Script::Run(events) => events
+        };
+        let (tx, rx) = mpsc::unbounded_channel();
+        let task = tokio::spawn(async move {
+            for (delay, event) in events {
+                tokio::time::sleep(delay).await;
+
+                if tx.send(event).is_err() {
+                    return;
+                }
+            }
+
+            std::future::pending::<()>().await;
+        });
+
+        Ok(RunnerOutput {
+            process: Box::new(ScriptedOutput { task, log: self.log.clone() }),
+            events: rx
+        })
+    }
+}
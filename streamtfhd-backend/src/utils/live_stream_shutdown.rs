@@ -0,0 +1,325 @@
+use futures_util::future::join_all;
+use nix::sys::signal::{killpg, Signal};
+use nix::unistd::Pid;
+use sqlx::{Pool, Postgres};
+use std::env::var;
+use std::sync::Arc;
+use sqlx::types::Json;
+use sysinfo::{ProcessesToUpdate, System};
+use tokio::time::Duration;
+use tracing::{info, warn};
+
+use crate::{
+    dto::live_stream_detached::DetachedStream,
+    dto::live_stream_state::{
+        LiveStreamState,
+        PipelineCommand,
+        StreamStatus,
+        StreamJob,
+        FfmpegProgress,
+        PlaybackPosition,
+        LoopProgress,
+        EndpointProgress,
+        ProcessResources
+    },
+    dto::live_stream_write_history::History,
+    models::{
+        live_stream_detached,
+        live_stream_empty_schedule,
+        live_stream_write_history
+    },
+    utils::live_stream::{stop_stream_internal, write_history},
+    utils::live_stream_event::transition_stream,
+    utils::live_stream_preview::remove_preview,
+    utils::live_stream_runner::AdoptedOutput,
+    utils::live_stream_admission::is_running,
+    utils::time::current_unix_timestamp
+};
+
+const DEFAULT_DRAIN_TIMEOUT: u64 = 30;
+const ADOPTED_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShutdownPolicy {
+    // Stop every live stream and record it as interrupted.
+    Stop,
+    // Leave running ffmpeg groups alive and re-adopt them on the next start.
+    Detach
+}
+
+#[derive(Debug, Clone)]
+pub struct ShutdownConfig {
+    pub policy: ShutdownPolicy,
+    pub drain_timeout: Duration
+}
+
+impl ShutdownConfig {
+    pub fn from_env() -> Self {
+        let policy = match var("SHUTDOWN_POLICY") {
+            Ok(val) if val.trim().eq_ignore_ascii_case("detach") => ShutdownPolicy::Detach,
+            _ => ShutdownPolicy::Stop
+        };
+        let drain_timeout = match var("SHUTDOWN_DRAIN_TIMEOUT") {
+            Ok(val) => match val.trim().parse::<u64>() {
+                Ok(val) => val,
+                Err(_) => {
+                    warn!("Invalid SHUTDOWN_DRAIN_TIMEOUT value in env file, using {} secs.", DEFAULT_DRAIN_TIMEOUT);
+
+                    DEFAULT_DRAIN_TIMEOUT
+                }
+            },
+            Err(_) => DEFAULT_DRAIN_TIMEOUT
+        };
+
+        ShutdownConfig {
+            policy,
+            drain_timeout: Duration::from_secs(drain_timeout)
+        }
+    }
+}
+
+// Stores the running jobs so the next start can re-adopt them. Returns the
+// ids that were detached, every other job still has to be stopped.
+async fn detach_running_streams(
+    state: &Arc<LiveStreamState>,
+    pool: &Pool<Postgres>
+) -> Vec<i64> {
+    let now = current_unix_timestamp() as i64;
+    let running: Vec<DetachedStream> = state.jobs
+        .iter()
+        .filter_map(|entry| {
+            let job = entry.value();
+            let pid = job.pid?;
+
+            if job.status.is_final() || !is_running(&job.status) {
+                return None;
+            }
+
+            Some(DetachedStream {
+                live_stream: job.id,
+                owner: job.owner.clone(),
+                pid: pid as i32,
+                actual_start: job.actual_start,
+                schedule_end: job.schedule_end,
+                bit_rate: job.bit_rate,
+                detached_at: now,
+                dry_run: job.dry_run
+            })
+        })
+        .collect();
+    let mut detached = Vec::new();
+
+    for data in running {
+        if live_stream_detached::insert_detached(&data, pool).await {
+            info!(stream_id = data.live_stream, pid = data.pid, "detached live stream");
+
+            // Dropping the job here keeps the Child handle from being waited on
+            // or killed. ffmpeg runs in its own session and ignores SIGPIPE, so
+            // it carries on streaming once its stderr pipe closes with us. The
+            // pipeline leaves its current feeder running, so the stream goes on
+            // until the feeder is done with its item.
+            if let Some((_, job)) = state.jobs.remove(&data.live_stream)
+                && let Some(pipeline) = &job.pipeline {
+                let _ = pipeline.send(PipelineCommand::Detach);
+            }
+            detached.push(data.live_stream);
+        }
+    }
+
+    detached
+}
+
+// Finalizes every running job, stopping or detaching its ffmpeg group, and
+// writes the history of each stopped job before returning. Jobs that have not
+// started yet are only dropped, their schedule stays for the next boot.
+pub async fn shutdown_streams(
+    state: &Arc<LiveStreamState>,
+    config: &ShutdownConfig,
+    pool: &Pool<Postgres>
+) {
+    if config.policy == ShutdownPolicy::Detach {
+        let detached = detach_running_streams(state, pool).await;
+
+        info!("Detached {} running live streams.", detached.len());
+    }
+
+    let mut ids: Vec<i64> = Vec::new();
+    let mut waiting: usize = 0;
+
+    state.jobs.retain(|stream_id, job| {
+        if is_running(&job.status) {
+            ids.push(*stream_id);
+
+            return true;
+        }
+
+        waiting += 1;
+
+        false
+    });
+
+    if waiting > 0 {
+        info!("Dropped {} live streams that have not started yet.", waiting);
+    }
+
+    if ids.is_empty() {
+        return;
+    }
+
+    info!("Stopping {} live streams before shutdown.", ids.len());
+
+    let stops = join_all(ids.iter().map(|stream_id| {
+        stop_stream_internal(state, *stream_id, StreamStatus::Interrupted, pool)
+    }));
+
+    if tokio::time::timeout(config.drain_timeout, stops).await.is_ok() {
+        return;
+    }
+
+    warn!("Live streams did not stop within the drain timeout, killing the rest.");
+
+    let remaining: Vec<(i64, Option<u32>)> = state.jobs
+        .iter()
+        .map(|entry| (*entry.key(), entry.value().pid))
+        .collect();
+
+    for (stream_id, pid) in remaining {
+        if let Some(pid) = pid {
+            let _ = killpg(Pid::from_raw(pid as i32), Signal::SIGKILL);
+        }
+
+        write_history(state, stream_id, pool, StreamStatus::Interrupted).await;
+    }
+}
+
+// Pid reuse after a reboot could point at an unrelated process.
+fn is_ffmpeg_alive(sys: &mut System, pid: i32) -> bool {
+    let pid = sysinfo::Pid::from_u32(pid as u32);
+
+    sys.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+
+    match sys.process(pid) {
+        Some(process) => process.name().to_string_lossy().contains("ffmpeg"),
+        None => false
+    }
+}
+
+// Watches a re-adopted ffmpeg group, which has no stderr pipe to follow.
+async fn watch_adopted(
+    state: Arc<LiveStreamState>,
+    stream_id: i64,
+    pid: i32,
+    cancel_notify: Arc<tokio::sync::Notify>,
+    pool: Pool<Postgres>
+) {
+    let mut sys = System::new();
+
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(ADOPTED_POLL_INTERVAL) => {}
+            _ = cancel_notify.notified() => return
+        }
+
+        if !is_ffmpeg_alive(&mut sys, pid) {
+            info!(%stream_id, pid, "adopted ffmpeg finished");
+            stop_stream_internal(&state, stream_id, StreamStatus::Stopped, &pool).await;
+
+            return;
+        }
+
+        // Read every time, the end may have been edited.
+        let schedule_end = state.jobs.get(&stream_id).and_then(|job| job.schedule_end);
+
+        if let Some(stop_at) = schedule_end && stop_at <= current_unix_timestamp() as i64 {
+            stop_stream_internal(&state, stream_id, StreamStatus::Done, &pool).await;
+
+            return;
+        }
+    }
+}
+
+// Re-adopts the live streams detached by the previous shutdown. Streams whose
+// ffmpeg group died in the meantime are recorded as interrupted.
+pub async fn readopt_streams(
+    state: &Arc<LiveStreamState>,
+    pool: &Pool<Postgres>
+) {
+    let detached = live_stream_detached::take_detached(pool).await;
+    let mut sys = System::new();
+
+    for data in detached {
+        let stream_id = data.live_stream;
+
+        if !is_ffmpeg_alive(&mut sys, data.pid) {
+            warn!(%stream_id, pid = data.pid, "detached ffmpeg is gone, recording as interrupted");
+
+            live_stream_empty_schedule::empty_schedule(stream_id, pool).await;
+
+            remove_preview(&state.upload_dir, stream_id);
+
+            let history = History {
+                owner: data.owner,
+                live_stream: stream_id,
+                start_time: data.actual_start.unwrap_or(0),
+                end_time: data.detached_at,
+                end_status: String::from("Interrupted"),
+                avg_cpu: None,
+                peak_cpu: None,
+                peak_memory: None,
+                net_tx_bytes: None,
+                loop_iteration: None,
+                loop_position: None,
+                dry_run: data.dry_run,
+                events: Json(Vec::new())
+            };
+
+            live_stream_write_history::write_history(&history, pool).await;
+
+            continue;
+        }
+
+        info!(%stream_id, pid = data.pid, "re-adopting detached live stream");
+
+        let cancel_notify = Arc::new(tokio::sync::Notify::new());
+
+        state.jobs.insert(stream_id, StreamJob {
+            id: stream_id,
+            owner: data.owner,
+            schedule_start: None,
+            schedule_end: data.schedule_end,
+            bit_rate: data.bit_rate,
+            dry_run: data.dry_run,
+            actual_start: data.actual_start,
+            actual_stop: None,
+            status: StreamStatus::Offline,
+            output: Some(Box::new(AdoptedOutput { pid: data.pid as u32 })),
+            pid: Some(data.pid as u32),
+            feeder_pid: None,
+            pipeline: None,
+            slate: None,
+            cancel_notify: cancel_notify.clone(),
+            schedule_notify: Arc::new(tokio::sync::Notify::new()),
+            events: Vec::new(),
+            progress: FfmpegProgress::default(),
+            playback: PlaybackPosition::default(),
+            loops: LoopProgress::default(),
+            restart_count: 0,
+            ffmpeg_restarts: 0,
+            pipeline_error: None,
+            generation: 0,
+            launch: None,
+            endpoints: EndpointProgress::default(),
+            overlays: None,
+            resources: ProcessResources::default()
+        });
+        transition_stream(state, stream_id, StreamStatus::Live);
+
+        tokio::spawn(watch_adopted(
+            state.clone(),
+            stream_id,
+            data.pid,
+            cancel_notify,
+            pool.clone()
+        ));
+    }
+}
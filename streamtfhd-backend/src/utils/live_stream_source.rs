@@ -0,0 +1,225 @@
+use sqlx::{Pool, Postgres};
+use std::collections::HashSet;
+
+use crate::{
+    dto::live_stream_source::{
+        LiveStreamSource,
+        INFINITE_LOOP,
+        INGEST_RTMP,
+        INGEST_SRT,
+        MODE_IMAGE_AUDIO,
+        MODE_INGEST,
+        MODE_VIDEO
+    },
+    errors::AppError,
+    models::gallery_delete_audio::get_audio_owner,
+    models::gallery_delete_image::get_image_owner,
+    models::gallery_delete_video::get_video_owner,
+    models::live_stream_audio_playlist::count_owned_audios,
+    models::live_stream_playback::get_video_length
+};
+
+// Checks which inputs the mode needs, without looking at the database.
+pub fn check_source_shape(source: &LiveStreamSource) -> Result<(), String> {
+    match source.mode.as_str() {
+        MODE_VIDEO => {
+            if source.video.is_none() {
+                return Err("A video is required.".to_string());
+            }
+
+            if source.background_image.is_some() || !source.audios.is_empty() {
+                return Err(format!("Background image and audios are only used in {} mode.", MODE_IMAGE_AUDIO));
+            }
+
+            if source.trim_start.is_some_and(|start| start < 0) {
+                return Err("Trim start can not be negative.".to_string());
+            }
+
+            if let Some(end) = source.trim_end && end <= source.trim_start.unwrap_or(0) {
+                return Err("Trim end must be after trim start.".to_string());
+            }
+        }
+        MODE_IMAGE_AUDIO => {
+            if source.video.is_some() == source.background_image.is_some() {
+                return Err("Either a background image or a background video is required.".to_string());
+            }
+
+            if source.audios.is_empty() {
+                return Err("At least one audio is required.".to_string());
+            }
+
+            if source.trim_start.is_some() || source.trim_end.is_some() {
+                return Err(format!("Trim points are only used in {} mode.", MODE_VIDEO));
+            }
+        }
+        MODE_INGEST => {
+            // The video is optional here, it fills the gaps of the live input.
+            if source.background_image.is_some() || !source.audios.is_empty() {
+                return Err(format!("Background image and audios are only used in {} mode.", MODE_IMAGE_AUDIO));
+            }
+
+            if source.trim_start.is_some() || source.trim_end.is_some() {
+                return Err(format!("Trim points are only used in {} mode.", MODE_VIDEO));
+            }
+
+            if !matches!(source.ingest_protocol.as_deref(), Some(INGEST_RTMP) | Some(INGEST_SRT)) {
+                return Err(format!("Ingest protocol must be {} or {}.", INGEST_RTMP, INGEST_SRT));
+            }
+        }
+        _ => return Err(format!("Mode must be {}, {} or {}.", MODE_VIDEO, MODE_IMAGE_AUDIO, MODE_INGEST))
+    }
+
+    if source.mode != MODE_INGEST && source.ingest_protocol.is_some() {
+        return Err(format!("Ingest protocol is only used in {} mode.", MODE_INGEST));
+    }
+
+    if source.stream_loop < INFINITE_LOOP {
+        return Err(format!("Stream loop must be {} to loop forever, or at least 0.", INFINITE_LOOP));
+    }
+
+    if source.slate_video.is_some() && source.slate_image.is_some() {
+        return Err("A slate uses either a video or an image.".to_string());
+    }
+
+    if source.slate_audio.is_some() && source.slate_video.is_none() && source.slate_image.is_none() {
+        return Err("A slate audio needs a slate video or image.".to_string());
+    }
+
+    Ok(())
+}
+
+fn check_owner(
+    owner: Option<String>,
+    user_id: &String,
+    message: &str
+) -> Result<(), AppError> {
+    match owner {
+        Some(val) if val.eq(user_id) => Ok(()),
+        _ => Err(AppError::BadRequest(message.to_string()))
+    }
+}
+
+// Validates the source of a live stream being created or edited. Every
+// referenced asset must belong to the user.
+pub async fn validate_source(
+    source: &LiveStreamSource,
+    user_id: &String,
+    pool: &Pool<Postgres>
+) -> Result<(), AppError> {
+    if let Err(err) = check_source_shape(source) {
+        return Err(AppError::ValidationError(err));
+    }
+
+    if let Some(video) = source.video {
+        check_owner(get_video_owner(video, pool).await?, user_id, "Invalid video ID")?;
+
+        // The length is rounded to whole seconds, so is the end of the video.
+        let length_ms = get_video_length(video, pool).await?.unwrap_or(0) as i64 * 1000;
+
+        if source.trim_start.unwrap_or(0).max(source.trim_end.unwrap_or(0)) > length_ms + 1000 {
+            return Err(AppError::ValidationError("Trim points must be inside the video.".to_string()));
+        }
+    }
+
+    if let Some(image) = source.background_image {
+        check_owner(get_image_owner(image, pool).await?, user_id, "Invalid background image ID")?;
+    }
+
+    if !source.audios.is_empty() {
+        let distinct: HashSet<i64> = source.audios.iter().copied().collect();
+        let ids: Vec<i64> = distinct.into_iter().collect();
+
+        if count_owned_audios(&ids, user_id, pool).await? != ids.len() as i64 {
+            return Err(AppError::BadRequest("Invalid audio ID".to_string()));
+        }
+    }
+
+    if let Some(video) = source.slate_video {
+        check_owner(get_video_owner(video, pool).await?, user_id, "Invalid slate video ID")?;
+    }
+
+    if let Some(image) = source.slate_image {
+        check_owner(get_image_owner(image, pool).await?, user_id, "Invalid slate image ID")?;
+    }
+
+    if let Some(audio) = source.slate_audio {
+        check_owner(get_audio_owner(audio, pool).await?, user_id, "Invalid slate audio ID")?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(mode: &str) -> LiveStreamSource {
+        LiveStreamSource {
+            mode: mode.to_string(),
+            video: None,
+            background_image: None,
+            audios: Vec::new(),
+            slate_video: None,
+            slate_image: None,
+            slate_audio: None,
+            stream_loop: 0,
+            trim_start: None,
+            trim_end: None,
+            ingest_protocol: None
+        }
+    }
+
+    #[test]
+    fn video_mode_needs_only_a_video() {
+        assert!(check_source_shape(&source(MODE_VIDEO)).is_err());
+        assert!(check_source_shape(&LiveStreamSource { video: Some(1), ..source(MODE_VIDEO) }).is_ok());
+        assert!(check_source_shape(&LiveStreamSource { video: Some(1), audios: vec![2], ..source(MODE_VIDEO) }).is_err());
+        assert!(check_source_shape(&LiveStreamSource { video: Some(1), stream_loop: INFINITE_LOOP, ..source(MODE_VIDEO) }).is_ok());
+        assert!(check_source_shape(&LiveStreamSource { video: Some(1), stream_loop: -2, ..source(MODE_VIDEO) }).is_err());
+    }
+
+    #[test]
+    fn trim_points_must_be_ordered() {
+        let video = LiveStreamSource { video: Some(1), ..source(MODE_VIDEO) };
+
+        assert!(check_source_shape(&LiveStreamSource { trim_start: Some(5000), trim_end: Some(9000), ..video.clone() }).is_ok());
+        assert!(check_source_shape(&LiveStreamSource { trim_end: Some(9000), ..video.clone() }).is_ok());
+        assert!(check_source_shape(&LiveStreamSource { trim_start: Some(9000), trim_end: Some(5000), ..video.clone() }).is_err());
+        assert!(check_source_shape(&LiveStreamSource { trim_start: Some(-1), ..video }).is_err());
+        assert!(check_source_shape(&LiveStreamSource {
+            background_image: Some(1),
+            audios: vec![2],
+            trim_start: Some(1000),
+            ..source(MODE_IMAGE_AUDIO)
+        }).is_err());
+    }
+
+    #[test]
+    fn image_audio_mode_needs_one_background_and_audios() {
+        let image = LiveStreamSource { background_image: Some(1), audios: vec![2, 3], ..source(MODE_IMAGE_AUDIO) };
+
+        assert!(check_source_shape(&image).is_ok());
+        assert!(check_source_shape(&LiveStreamSource { video: Some(4), background_image: None, ..image.clone() }).is_ok());
+        assert!(check_source_shape(&LiveStreamSource { video: Some(4), ..image.clone() }).is_err());
+        assert!(check_source_shape(&LiveStreamSource { audios: Vec::new(), ..image }).is_err());
+        assert!(check_source_shape(&source("audio")).is_err());
+    }
+
+    #[test]
+    fn ingest_mode_needs_a_protocol() {
+        let ingest = LiveStreamSource { ingest_protocol: Some(INGEST_RTMP.to_string()), ..source(MODE_INGEST) };
+
+        assert!(check_source_shape(&ingest).is_ok());
+        assert!(check_source_shape(&LiveStreamSource { video: Some(1), ..ingest.clone() }).is_ok());
+        assert!(check_source_shape(&LiveStreamSource { ingest_protocol: Some(INGEST_SRT.to_string()), ..ingest.clone() }).is_ok());
+        assert!(check_source_shape(&LiveStreamSource { ingest_protocol: Some("rtsp".to_string()), ..ingest.clone() }).is_err());
+        assert!(check_source_shape(&LiveStreamSource { audios: vec![2], ..ingest.clone() }).is_err());
+        assert!(check_source_shape(&source(MODE_INGEST)).is_err());
+        assert!(check_source_shape(&LiveStreamSource { trim_start: Some(1000), ..ingest }).is_err());
+        assert!(check_source_shape(&LiveStreamSource {
+            video: Some(1),
+            ingest_protocol: Some(INGEST_RTMP.to_string()),
+            ..source(MODE_VIDEO)
+        }).is_err());
+    }
+}
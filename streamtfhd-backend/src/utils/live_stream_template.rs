@@ -0,0 +1,207 @@
+use crate::{
+    dto::live_stream_create_stream::CreateLiveStreamData,
+    dto::live_stream_template::{Recurrence, TemplateSettings, RECURRENCE_DAILY, RECURRENCE_WEEKLY},
+    errors::AppError,
+    utils::live_stream_destination::resolve_destination,
+    utils::live_stream_group::normalize_tags
+};
+
+pub const MAX_TEMPLATE_NAME_LENGTH: usize = 100;
+
+const DAY: i64 = 86400;
+const WEEK: i64 = 7 * DAY;
+
+// `overrides` over `base`, field by field.
+pub fn merge_settings(base: TemplateSettings, overrides: TemplateSettings) -> TemplateSettings {
+    TemplateSettings {
+        title: overrides.title.or(base.title),
+        mode: overrides.mode.or(base.mode),
+        video: overrides.video.or(base.video),
+        background_image: overrides.background_image.or(base.background_image),
+        audios: overrides.audios.or(base.audios),
+        stream_loop: overrides.stream_loop.or(base.stream_loop),
+        slate_video: overrides.slate_video.or(base.slate_video),
+        slate_image: overrides.slate_image.or(base.slate_image),
+        slate_audio: overrides.slate_audio.or(base.slate_audio),
+        overlays: overrides.overlays.or(base.overlays),
+        trim_start: overrides.trim_start.or(base.trim_start),
+        trim_end: overrides.trim_end.or(base.trim_end),
+        resume: overrides.resume.or(base.resume),
+        record: overrides.record.or(base.record),
+        preview: overrides.preview.or(base.preview),
+        dry_run: overrides.dry_run.or(base.dry_run),
+        destination: overrides.destination.or(base.destination),
+        ingest_protocol: overrides.ingest_protocol.or(base.ingest_protocol),
+        tags: overrides.tags.or(base.tags),
+        recurrence: overrides.recurrence.or(base.recurrence),
+        duration: overrides.duration.or(base.duration)
+    }
+}
+
+fn recurrence_period(recurrence: &Recurrence) -> Option<i64> {
+    match recurrence.every.as_str() {
+        RECURRENCE_DAILY => Some(DAY),
+        RECURRENCE_WEEKLY => Some(WEEK),
+        _ => None
+    }
+}
+
+// Sources, overlays and the schedule are checked when a live stream is made
+// from the template, they may change before that.
+pub fn validate_template(name: &str, settings: &TemplateSettings) -> Result<(), AppError> {
+    let name = name.trim();
+
+    if name.is_empty() {
+        return Err(AppError::ValidationError("Template name can not be empty.".to_string()));
+    }
+
+    if name.chars().count() > MAX_TEMPLATE_NAME_LENGTH {
+        return Err(AppError::ValidationError(format!("Template name can not be longer than {} characters.", MAX_TEMPLATE_NAME_LENGTH)));
+    }
+
+    if let Some(recurrence) = &settings.recurrence {
+        if recurrence_period(recurrence).is_none() {
+            return Err(AppError::ValidationError("Recurrence must be daily or weekly.".to_string()));
+        }
+
+        if recurrence.first_start <= 0 {
+            return Err(AppError::ValidationError("Recurrence needs a first start.".to_string()));
+        }
+    }
+
+    if let Some(duration) = settings.duration && duration <= 0 {
+        return Err(AppError::ValidationError("Duration must be longer than zero.".to_string()));
+    }
+
+    if let Some(tags) = &settings.tags {
+        normalize_tags(tags)?;
+    }
+
+    if settings.destination.is_some() {
+        resolve_destination(&settings.destination, &None, &None)?;
+    }
+
+    Ok(())
+}
+
+// The first occurrence at or after `now`.
+pub fn next_occurrence(recurrence: &Recurrence, now: i64) -> Option<i64> {
+    let period = recurrence_period(recurrence)?;
+
+    if recurrence.first_start >= now {
+        return Some(recurrence.first_start);
+    }
+
+    let passed = (now - recurrence.first_start + period - 1) / period;
+
+    Some(recurrence.first_start + passed * period)
+}
+
+// Given times win, then the next occurrence and the duration after it.
+pub fn clone_schedule(
+    settings: &TemplateSettings,
+    schedule_start: Option<i64>,
+    schedule_end: Option<i64>,
+    now: i64
+) -> (Option<i64>, Option<i64>) {
+    let start = schedule_start.or_else(|| {
+        settings.recurrence.as_ref().and_then(|recurrence| next_occurrence(recurrence, now))
+    });
+    let end = schedule_end.or(match (start, settings.duration) {
+        (Some(start), Some(duration)) => Some(start + duration),
+        _ => None
+    });
+
+    (start, end)
+}
+
+// A new live stream out of merged settings. It plays once unless the settings
+// tell otherwise.
+pub fn clone_data(
+    settings: TemplateSettings,
+    schedule_start: Option<i64>,
+    schedule_end: Option<i64>
+) -> CreateLiveStreamData {
+    CreateLiveStreamData {
+        title: settings.title.unwrap_or_default(),
+        mode: settings.mode,
+        video: settings.video,
+        background_image: settings.background_image,
+        audios: settings.audios,
+        rtmp_url: None,
+        stream_key: None,
+        stream_loop: settings.stream_loop.unwrap_or(0),
+        schedule_start,
+        schedule_end,
+        slate_video: settings.slate_video,
+        slate_image: settings.slate_image,
+        slate_audio: settings.slate_audio,
+        overlays: settings.overlays,
+        trim_start: settings.trim_start,
+        trim_end: settings.trim_end,
+        resume: settings.resume,
+        record: settings.record,
+        preview: settings.preview,
+        dry_run: settings.dry_run,
+        destination: settings.destination,
+        ingest_protocol: settings.ingest_protocol,
+        tags: settings.tags
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weekly(first_start: i64) -> Recurrence {
+        Recurrence { every: RECURRENCE_WEEKLY.to_string(), first_start }
+    }
+
+    #[test]
+    fn clones_land_on_the_next_occurrence() {
+        let first_start = 1_700_000_000;
+
+        assert_eq!(next_occurrence(&weekly(first_start), first_start - 10), Some(first_start));
+        assert_eq!(next_occurrence(&weekly(first_start), first_start), Some(first_start));
+        assert_eq!(next_occurrence(&weekly(first_start), first_start + 1), Some(first_start + WEEK));
+        assert_eq!(next_occurrence(&weekly(first_start), first_start + 3 * WEEK), Some(first_start + 3 * WEEK));
+
+        let settings = TemplateSettings {
+            recurrence: Some(weekly(first_start)),
+            duration: Some(3600),
+            ..TemplateSettings::default()
+        };
+
+        assert_eq!(
+            clone_schedule(&settings, None, None, first_start + DAY),
+            (Some(first_start + WEEK), Some(first_start + WEEK + 3600))
+        );
+        assert_eq!(
+            clone_schedule(&settings, Some(first_start + 60), None, first_start),
+            (Some(first_start + 60), Some(first_start + 3660))
+        );
+        assert_eq!(clone_schedule(&TemplateSettings::default(), None, None, first_start), (None, None));
+    }
+
+    #[test]
+    fn overrides_go_over_the_base() {
+        let base = TemplateSettings {
+            title: Some("Morning show".to_string()),
+            stream_loop: Some(2),
+            tags: Some(vec!["news".to_string()]),
+            ..TemplateSettings::default()
+        };
+        let overrides = TemplateSettings {
+            title: Some("Evening show".to_string()),
+            ..TemplateSettings::default()
+        };
+        let merged = merge_settings(base, overrides);
+
+        assert_eq!(merged.title.as_deref(), Some("Evening show"));
+        assert_eq!(merged.stream_loop, Some(2));
+        assert_eq!(merged.tags, Some(vec!["news".to_string()]));
+        assert!(validate_template(" ", &merged).is_err());
+        assert!(validate_template("Shows", &merged).is_ok());
+        assert!(validate_template("Shows", &TemplateSettings { duration: Some(0), ..merged }).is_err());
+    }
+}
@@ -0,0 +1,263 @@
+use std::env::var;
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command;
+use tokio::time::Duration;
+use tracing::{info, warn};
+
+use crate::{
+    dto::live_stream_destination::{Destination, DestinationOutput},
+    dto::live_stream_test_destination::{DestinationTest, EndpointProbe, ProbeOutcome},
+    utils::live_stream_destination::{destination_outputs, output_args},
+    utils::time::current_unix_timestamp
+};
+
+// Time the probe gets on top of its push to connect and finish.
+const PROBE_GRACE_SECONDS: u64 = 15;
+const MAX_PROBE_LOG_LINES: usize = 200;
+
+// Lower case parts of ffmpeg log lines, connection problems first.
+const UNREACHABLE_PATTERNS: [&str; 10] = [
+    "connection refused",
+    "timed out",
+    "no route to host",
+    "network is unreachable",
+    "name or service not known",
+    "failed to resolve",
+    "name resolution",
+    "handshake",
+    "cannot open connection",
+    "host is down"
+];
+const REJECTED_PATTERNS: [&str; 9] = [
+    "rejected",
+    "badname",
+    "denied",
+    "server error",
+    "unauthorized",
+    "forbidden",
+    "authentication",
+    "broken pipe",
+    "connection reset by peer"
+];
+
+#[derive(Debug, Clone)]
+pub struct ProbeConfig {
+    pub seconds: u64
+}
+
+impl ProbeConfig {
+    pub fn from_env() -> Self {
+        let seconds = match var("DESTINATION_TEST_SECONDS") {
+            Ok(val) => match val.trim().parse::<u64>() {
+                Ok(val) if (1..=30).contains(&val) => val,
+                _ => {
+                    warn!("Invalid DESTINATION_TEST_SECONDS value in env file, using 5 secs.");
+
+                    5
+                }
+            },
+            Err(_) => 5
+        };
+
+        ProbeConfig {
+            seconds
+        }
+    }
+}
+
+// Pushes `seconds` of the video, or of a test pattern with a tone, to one
+// output. A local stand-in such as
+// `ffmpeg -listen 1 -i rtmp://127.0.0.1:1935/live/key -f null -` takes it.
+fn probe_args(
+    output: &DestinationOutput,
+    video_file: Option<&str>,
+    seconds: u64
+) -> Vec<String> {
+    let mut args: Vec<String> = ["-hide_banner", "-loglevel", "warning", "-nostdin"]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+
+    match video_file {
+        Some(file) => args.extend([
+            "-re".to_string(),
+            "-t".to_string(), seconds.to_string(),
+            "-i".to_string(), file.to_string(),
+            "-map".to_string(), "0:v:0?".to_string(),
+            "-map".to_string(), "0:a:0?".to_string(),
+            "-c".to_string(), "copy".to_string()
+        ]),
+        None => args.extend([
+            "-re".to_string(),
+            "-f".to_string(), "lavfi".to_string(),
+            "-i".to_string(), "testsrc2=size=1280x720:rate=30".to_string(),
+            "-f".to_string(), "lavfi".to_string(),
+            "-i".to_string(), "sine=frequency=1000:sample_rate=44100".to_string(),
+            "-map".to_string(), "0:v:0".to_string(),
+            "-map".to_string(), "1:a:0".to_string(),
+            "-t".to_string(), seconds.to_string(),
+            "-c:v".to_string(), "libx264".to_string(),
+            "-preset".to_string(), "veryfast".to_string(),
+            "-tune".to_string(), "zerolatency".to_string(),
+            "-pix_fmt".to_string(), "yuv420p".to_string(),
+            "-b:v".to_string(), "2500k".to_string(),
+            "-g".to_string(), "60".to_string(),
+            "-c:a".to_string(), "aac".to_string(),
+            "-b:a".to_string(), "128k".to_string(),
+            "-ac".to_string(), "2".to_string()
+        ])
+    }
+
+    args.extend(output_args(output));
+    args
+}
+
+fn find_line(log: &[String], patterns: &[&str]) -> Option<String> {
+    log.iter()
+        .find(|line| {
+            let line = line.to_lowercase();
+
+            patterns.iter().any(|pattern| line.contains(pattern))
+        })
+        .cloned()
+}
+
+// Reads the outcome of a probe from whether ffmpeg exited cleanly and what
+// it logged.
+fn classify_probe(is_success: bool, log: &[String]) -> (ProbeOutcome, Option<String>) {
+    if is_success {
+        return (ProbeOutcome::Success, None);
+    }
+
+    if let Some(line) = find_line(log, &UNREACHABLE_PATTERNS) {
+        return (ProbeOutcome::Unreachable, Some(line));
+    }
+
+    if let Some(line) = find_line(log, &REJECTED_PATTERNS) {
+        return (ProbeOutcome::Rejected, Some(line));
+    }
+
+    (ProbeOutcome::Failed, log.last().cloned())
+}
+
+async fn probe_output(
+    output: &DestinationOutput,
+    video_file: Option<&str>,
+    seconds: u64
+) -> (ProbeOutcome, Option<String>) {
+    if let Some(dir) = &output.local_dir && let Err(err) = std::fs::create_dir_all(dir) {
+        return (ProbeOutcome::Failed, Some(err.to_string()));
+    }
+
+    let args = probe_args(output, video_file, seconds);
+
+    info!("ffmpeg {}", args.join(" "));
+
+    let mut child = match Command::new("ffmpeg")
+        .args(&args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn() {
+        Ok(val) => val,
+        Err(err) => return (ProbeOutcome::Failed, Some(err.to_string()))
+    };
+    let mut log: Vec<String> = Vec::new();
+    let limit = Duration::from_secs(seconds + PROBE_GRACE_SECONDS);
+    let result = tokio::time::timeout(limit, async {
+        if let Some(stderr) = child.stderr.take() {
+            let mut lines = BufReader::new(stderr).lines();
+
+            while let Ok(Some(line)) = lines.next_line().await {
+                if log.len() < MAX_PROBE_LOG_LINES {
+                    log.push(line);
+                }
+            }
+        }
+
+        child.wait().await
+    }).await;
+
+    match result {
+        Ok(Ok(status)) => classify_probe(status.success(), &log),
+        Ok(Err(err)) => (ProbeOutcome::Failed, Some(err.to_string())),
+        Err(_) => {
+            let _ = child.kill().await;
+
+            match classify_probe(false, &log) {
+                (ProbeOutcome::Failed, _) => (ProbeOutcome::Unreachable, Some("No answer from the destination in time.".to_string())),
+                outcome => outcome
+            }
+        }
+    }
+}
+
+// Probes every endpoint of the destination, one after another.
+pub async fn test_destination(
+    stream_id: i64,
+    destination: &Destination,
+    video_file: Option<&str>,
+    upload_dir: &str,
+    config: &ProbeConfig
+) -> DestinationTest {
+    let mut endpoints = Vec::new();
+
+    for (endpoint, output) in destination.endpoints().into_iter().zip(destination_outputs(destination, stream_id, upload_dir)) {
+        let (outcome, message) = probe_output(&output, video_file, config.seconds).await;
+
+        info!("Destination test of {}: {:?}", endpoint, outcome);
+        endpoints.push(EndpointProbe { endpoint, outcome, message });
+    }
+
+    DestinationTest {
+        tested_at: current_unix_timestamp() as i64,
+        endpoints
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn probe_outcomes_are_read_from_the_log() {
+        assert_eq!(classify_probe(true, &[]).0, ProbeOutcome::Success);
+        assert_eq!(
+            classify_probe(false, &log(&["[tcp @ 0x55] Connection to tcp://127.0.0.1:1935 failed: Connection refused"])).0,
+            ProbeOutcome::Unreachable
+        );
+        assert_eq!(
+            classify_probe(false, &log(&["[rtmp @ 0x55] Server error: Publishing denied", "Error writing trailer"])).0,
+            ProbeOutcome::Rejected
+        );
+        assert_eq!(
+            classify_probe(false, &log(&["av_interleaved_write_frame(): Broken pipe"])).0,
+            ProbeOutcome::Rejected
+        );
+        assert_eq!(
+            classify_probe(false, &log(&["Unknown encoder 'libx264'"])),
+            (ProbeOutcome::Failed, Some("Unknown encoder 'libx264'".to_string()))
+        );
+    }
+
+    #[test]
+    fn probe_pushes_a_test_pattern_for_a_few_seconds() {
+        let output = DestinationOutput {
+            format: String::from("flv"),
+            options: Vec::new(),
+            target: String::from("rtmp://127.0.0.1:1935/live/key"),
+            local_dir: None
+        };
+        let args = probe_args(&output, None, 5);
+
+        assert!(args.contains(&"testsrc2=size=1280x720:rate=30".to_string()));
+        assert_eq!(args[args.len() - 3..], ["-f", "flv", "rtmp://127.0.0.1:1935/live/key"]);
+        assert!(probe_args(&output, Some("/uploads/videos/a.mp4"), 5).contains(&"copy".to_string()));
+    }
+}
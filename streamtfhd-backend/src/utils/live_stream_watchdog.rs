@@ -0,0 +1,293 @@
+use sqlx::{Pool, Postgres};
+use std::env::var;
+use std::sync::Arc;
+use tokio::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::{
+    dto::live_stream_state::{LiveStreamState, StreamStatus},
+    utils::live_stream::{restart_ffmpeg, stop_stream_internal},
+    utils::live_stream_event::transition
+};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchdogAction {
+    // Only mark the job as degraded until the output recovers.
+    Degrade,
+    // Restart ffmpeg, failing the job once the restart limit is reached. The
+    // restarts are forgotten after a healthy period, so the limit counts
+    // restarts in a row rather than over the whole run.
+    Restart,
+    Fail
+}
+
+#[derive(Debug, Clone)]
+pub struct WatchdogConfig {
+    pub stall_timeout: Duration,
+    pub min_speed: f64,
+    pub slow_timeout: Duration,
+    pub max_restarts: u32,
+    pub restart_reset: Duration,    // Healthy output after which the restarts are forgotten
+    pub action: WatchdogAction
+}
+
+fn secs_from_env(key: &str, default: u64) -> Duration {
+    match var(key) {
+        Ok(val) => match val.trim().parse::<u64>() {
+            Ok(val) => Duration::from_secs(val),
+            Err(_) => {
+                warn!("Invalid {} value in env file, using {} secs.", key, default);
+
+                Duration::from_secs(default)
+            }
+        },
+        Err(_) => Duration::from_secs(default)
+    }
+}
+
+impl WatchdogConfig {
+    pub fn from_env() -> Self {
+        let action = match var("WATCHDOG_ACTION") {
+            Ok(val) if val.trim().eq_ignore_ascii_case("degrade") => WatchdogAction::Degrade,
+            Ok(val) if val.trim().eq_ignore_ascii_case("fail") => WatchdogAction::Fail,
+            _ => WatchdogAction::Restart
+        };
+        let min_speed = match var("WATCHDOG_MIN_SPEED") {
+            Ok(val) => val.trim().parse::<f64>().unwrap_or(0.5),
+            Err(_) => 0.5
+        };
+        let max_restarts = match var("WATCHDOG_MAX_RESTARTS") {
+            Ok(val) => val.trim().parse::<u32>().unwrap_or(3),
+            Err(_) => 3
+        };
+
+        WatchdogConfig {
+            stall_timeout: secs_from_env("WATCHDOG_STALL_TIMEOUT", 30),
+            min_speed,
+            slow_timeout: secs_from_env("WATCHDOG_SLOW_TIMEOUT", 60),
+            max_restarts,
+            restart_reset: secs_from_env("WATCHDOG_RESTART_RESET", 300),
+            action
+        }
+    }
+}
+
+// Progress of one ffmpeg generation as seen by the watchdog.
+#[derive(Debug)]
+pub struct OutputTracker {
+    last_out_time: i64,
+    last_total_size: u64,
+    last_advance: Instant,
+    slow_since: Option<Instant>
+}
+
+impl OutputTracker {
+    pub fn new(now: Instant) -> Self {
+        OutputTracker {
+            last_out_time: 0,
+            last_total_size: 0,
+            last_advance: now,
+            slow_since: None
+        }
+    }
+
+    // Feeds a progress sample and returns why the output is unhealthy, if it is.
+    pub fn check(
+        &mut self,
+        out_time_ms: i64,
+        total_size: u64,
+        speed: f64,
+        now: Instant,
+        config: &WatchdogConfig
+    ) -> Option<String> {
+        if out_time_ms > self.last_out_time || total_size > self.last_total_size {
+            self.last_out_time = out_time_ms;
+            self.last_total_size = total_size;
+            self.last_advance = now;
+        }
+
+        // A zero speed is reported before the first frame, the stall check covers it.
+        if speed > 0.0 && speed < config.min_speed {
+            self.slow_since.get_or_insert(now);
+        } else {
+            self.slow_since = None;
+        }
+
+        let stalled_for = now.duration_since(self.last_advance);
+
+        if stalled_for >= config.stall_timeout {
+            return Some(format!("Output stalled for {} secs", stalled_for.as_secs()));
+        }
+
+        if let Some(slow_since) = self.slow_since && now.duration_since(slow_since) >= config.slow_timeout {
+            return Some(format!(
+                "Speed stayed at {:.2}x, below {:.2}x, for {} secs",
+                speed,
+                config.min_speed,
+                now.duration_since(slow_since).as_secs()
+            ));
+        }
+
+        None
+    }
+}
+
+// Watches the output of a job until it is finalized, applying the configured
+// action whenever ffmpeg stops making progress.
+pub async fn watch_stream(
+    state: Arc<LiveStreamState>,
+    stream_id: i64,
+    config: WatchdogConfig,
+    pool: Pool<Postgres>
+) {
+    let mut tracker = OutputTracker::new(Instant::now());
+    let mut generation = 0;
+    let mut healthy_since: Option<Instant> = None;
+
+    loop {
+        tokio::time::sleep(CHECK_INTERVAL).await;
+
+        let (progress, restart_count, job_generation) = match state.jobs.get(&stream_id) {
+            Some(job) => {
+                if job.status.is_final() {
+                    return;
+                }
+
+                (job.progress.clone(), job.restart_count, job.generation)
+            }
+            None => return
+        };
+
+        // A new ffmpeg starts its progress from zero.
+        if job_generation != generation {
+            generation = job_generation;
+            tracker = OutputTracker::new(Instant::now());
+            healthy_since = None;
+
+            continue;
+        }
+
+        let now = Instant::now();
+        let reason = tracker.check(
+            progress.out_time_ms,
+            progress.total_size,
+            progress.speed,
+            now,
+            &config
+        );
+
+        let reason = match reason {
+            Some(val) => val,
+            None => {
+                // Healthy only while the output moves, a fresh ffmpeg has
+                // the whole stall timeout before it is reported.
+                if tracker.last_advance != now {
+                    continue;
+                }
+
+                let since = *healthy_since.get_or_insert(now);
+
+                if let Some(mut job) = state.jobs.get_mut(&stream_id) {
+                    if matches!(job.status, StreamStatus::Degraded) {
+                        info!(%stream_id, "live stream output recovered");
+
+                        transition(&mut job, StreamStatus::Live, &state.events);
+                    }
+
+                    if job.restart_count > 0 && now.duration_since(since) >= config.restart_reset {
+                        info!(%stream_id, restarts = job.restart_count, "live stream output healthy again, restarts forgotten");
+
+                        job.restart_count = 0;
+                    }
+                }
+
+                continue;
+            }
+        };
+
+        healthy_since = None;
+
+        match config.action {
+            WatchdogAction::Degrade => {
+                if let Some(mut job) = state.jobs.get_mut(&stream_id) && !matches!(job.status, StreamStatus::Degraded) {
+                    warn!(%stream_id, "live stream degraded: {}", reason);
+
+                    transition(&mut job, StreamStatus::Degraded, &state.events);
+                }
+            }
+            WatchdogAction::Restart if restart_count < config.max_restarts => {
+                warn!(%stream_id, "restarting ffmpeg: {}", reason);
+
+                if !restart_ffmpeg(&state, stream_id, &pool).await {
+                    return;
+                }
+            }
+            WatchdogAction::Restart => {
+                let reason = format!("{}, gave up after {} restarts", reason, restart_count);
+
+                warn!(%stream_id, "live stream failed by watchdog: {}", reason);
+                stop_stream_internal(&state, stream_id, StreamStatus::Failed(reason), &pool).await;
+
+                return;
+            }
+            WatchdogAction::Fail => {
+                warn!(%stream_id, "live stream failed by watchdog: {}", reason);
+                stop_stream_internal(&state, stream_id, StreamStatus::Failed(reason), &pool).await;
+
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> WatchdogConfig {
+        WatchdogConfig {
+            stall_timeout: Duration::from_secs(30),
+            min_speed: 0.5,
+            slow_timeout: Duration::from_secs(60),
+            max_restarts: 3,
+            restart_reset: Duration::from_secs(300),
+            action: WatchdogAction::Restart
+        }
+    }
+
+    #[test]
+    fn advancing_output_is_healthy() {
+        let start = Instant::now();
+        let mut tracker = OutputTracker::new(start);
+
+        for i in 1..20 {
+            let now = start + Duration::from_secs(i * 5);
+
+            assert_eq!(tracker.check(i as i64 * 5_000_000, i * 1000, 1.0, now, &config()), None);
+        }
+    }
+
+    #[test]
+    fn frozen_output_is_reported_after_the_stall_timeout() {
+        let start = Instant::now();
+        let mut tracker = OutputTracker::new(start);
+
+        assert_eq!(tracker.check(1_000_000, 1000, 1.0, start + Duration::from_secs(5), &config()), None);
+        assert_eq!(tracker.check(1_000_000, 1000, 1.0, start + Duration::from_secs(30), &config()), None);
+        assert!(tracker.check(1_000_000, 1000, 1.0, start + Duration::from_secs(35), &config()).is_some());
+    }
+
+    #[test]
+    fn slow_output_is_reported_after_the_slow_timeout() {
+        let start = Instant::now();
+        let mut tracker = OutputTracker::new(start);
+
+        assert_eq!(tracker.check(1, 1, 0.2, start, &config()), None);
+        assert_eq!(tracker.check(2, 2, 0.2, start + Duration::from_secs(30), &config()), None);
+        assert_eq!(tracker.check(3, 3, 0.9, start + Duration::from_secs(40), &config()), None);
+        assert_eq!(tracker.check(4, 4, 0.2, start + Duration::from_secs(50), &config()), None);
+        assert!(tracker.check(5, 5, 0.2, start + Duration::from_secs(110), &config()).is_some());
+    }
+}
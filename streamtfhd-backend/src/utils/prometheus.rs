@@ -0,0 +1,75 @@
+use std::fmt::Write;
+use std::time::Duration;
+
+use crate::dto::metrics_state::{
+    HttpRequestKey,
+    MetricsState,
+    HTTP_LATENCY_BUCKETS
+};
+
+// Content type of the Prometheus text exposition format.
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+pub fn write_header(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    metric_type: &str
+) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, metric_type);
+}
+
+pub fn write_sample(
+    out: &mut String,
+    name: &str,
+    labels: &[(&str, &str)],
+    value: f64
+) {
+    out.push_str(name);
+
+    if !labels.is_empty() {
+        let labels_str: Vec<String> = labels
+            .iter()
+            .map(|(key, val)| format!("{}=\"{}\"", key, escape_label_value(val)))
+            .collect();
+
+        let _ = write!(out, "{{{}}}", labels_str.join(","));
+    }
+
+    let _ = writeln!(out, " {}", value);
+}
+
+pub fn observe_http_request(
+    metrics_state: &MetricsState,
+    method: &str,
+    route: &str,
+    status: u16,
+    elapsed: Duration
+) {
+    let key = HttpRequestKey {
+        method: method.to_string(),
+        route: route.to_string(),
+        status
+    };
+    let seconds = elapsed.as_secs_f64();
+    let mut stats = metrics_state.http_requests
+        .entry(key)
+        .or_default();
+
+    for (i, bound) in HTTP_LATENCY_BUCKETS.iter().enumerate() {
+        if seconds <= *bound {
+            stats.buckets[i] += 1;
+        }
+    }
+
+    stats.sum += seconds;
+    stats.count += 1;
+}
@@ -35,4 +35,21 @@ pub mod history_search;
 pub mod history_delete_all;
 pub mod get_server_time;
 pub mod get_avatar;
-pub mod setup_account_check;
\ No newline at end of file
+pub mod setup_account_check;
+pub mod metrics;
+pub mod dashboard_metrics_history;
+pub mod live_stream_calendar;
+pub mod live_stream_calendar_feed;
+pub mod live_stream_swap_source;
+pub mod gallery_upload_audio;
+pub mod gallery_get_audios;
+pub mod gallery_delete_audio;
+pub mod gallery_upload_image;
+pub mod gallery_get_images;
+pub mod gallery_delete_image;
+pub mod live_stream_overlay_text;
+pub mod live_stream_test_destination;
+pub mod live_stream_preview;
+pub mod live_stream_bulk;
+pub mod live_stream_template;
+pub mod live_stream_clone;
\ No newline at end of file
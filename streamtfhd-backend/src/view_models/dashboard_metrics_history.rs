@@ -0,0 +1,68 @@
+use actix_web::HttpRequest;
+use sqlx::{Pool, Postgres};
+use tracing::{error, debug, warn};
+use std::env::var;
+
+use crate::{
+    dto::dashboard_metrics_history::MetricsHistory,
+    errors::AppError,
+    models::dashboard_metrics_history::{
+        self,
+        RAW_RESOLUTION,
+        MINUTE_RESOLUTION
+    },
+    utils::time::current_unix_timestamp,
+    utils::token::{decode_token, get_jwt_from_header}
+};
+
+// Ranges longer than this are served from the per minute rollup.
+const MAX_RAW_RANGE: i64 = 21600;   // 6 hours
+
+pub async fn get_metrics_history(
+    from: i64,
+    to: i64,
+    req: &HttpRequest,
+    pool: &Pool<Postgres>
+) -> Result<MetricsHistory, AppError> {
+    let secret_key = match var("JWT_SECRET_KEY") {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Missing JWT_SECRET_KEY key and value in env file.");
+            debug!("{}", err.to_string());
+
+            return Err(AppError::EnvVarError(err));
+        }
+    };
+    let jwt = match get_jwt_from_header(req) {
+        Some(val) => val,
+        None => {
+            warn!("An attemp to access metrics history endpoint without credentials.");
+
+            return Err(AppError::Unauthorized);
+        }
+    };
+    let _claims = match decode_token(&jwt, &secret_key) {
+        Ok(val) => val,
+        Err(_err) => {
+            warn!("An attemp to access metrics history endpoint with invalid credentials.");
+
+            return Err(AppError::Unauthorized);
+        }
+    };
+
+    if from >= to {
+        return Err(AppError::BadRequest("The from parameter must be earlier than the to parameter.".to_string()));
+    }
+
+    let now = current_unix_timestamp() as i64;
+    let raw_available_from = now - dashboard_metrics_history::raw_retention();
+    let resolution = if from < raw_available_from || to - from > MAX_RAW_RANGE {
+        MINUTE_RESOLUTION
+    } else {
+        RAW_RESOLUTION
+    };
+
+    let samples = dashboard_metrics_history::get_history(from, to, resolution, pool).await?;
+
+    Ok(MetricsHistory { resolution, samples })
+}
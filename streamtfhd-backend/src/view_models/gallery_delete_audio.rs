@@ -0,0 +1,75 @@
+use crate::errors::AppError;
+use actix_web::HttpRequest;
+use sqlx::{Pool, Postgres};
+use tracing::{error, debug, warn};
+use std::env::var;
+use crate::utils::token::{get_jwt_from_header, decode_token};
+use crate::utils::user::get_user_id_from_username;
+use crate::models::gallery_delete_audio;
+
+pub async fn delete_audio(
+    audio_id: i64,
+    pool: &Pool<Postgres>,
+    req: &HttpRequest,
+) -> Result<bool, AppError> {
+    let secret_key = match var("JWT_SECRET_KEY") {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Missing JWT_SECRET_KEY key and value in env file.");
+            debug!("{}", err.to_string());
+
+            return Err(AppError::EnvVarError(err));
+        }
+    };
+    let upload_directory = match var("UPLOAD_DIRECTORY") {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Missing UPLOAD_DIRECTORY key and value in env file.");
+            debug!("{}", err.to_string());
+
+            return Err(AppError::EnvVarError(err));
+        }
+    };
+    let jwt = match get_jwt_from_header(req) {
+        Some(val) => val,
+        None => {
+            warn!("An attemp to access delete audio endpoint without credentials 1.");
+
+            return Err(AppError::Unauthorized);
+        }
+    };
+    let claims = match decode_token(&jwt, &secret_key) {
+        Ok(val) => val,
+        Err(_err) => {
+            warn!("An attemp to access delete audio endpoint with invalid credentials 2.");
+
+            return Err(AppError::Unauthorized);
+        }
+    };
+    let user_id = match get_user_id_from_username(claims.username, pool).await? {
+        Some(val) => val,
+        None => {
+            warn!("An attemp to access delete audio endpoint with invalid credentials 3.");
+
+            return Err(AppError::Unauthorized);
+        }
+    };
+    let audio_owner = match gallery_delete_audio::get_audio_owner(audio_id, pool).await? {
+        Some(val) => val,
+        None => {
+            warn!("An attemp to delete audio with invalid credentials 1");
+
+            return Err(AppError::Forbidden);
+        }
+    };
+
+    if audio_owner.ne(&user_id) {
+        warn!("An attemp to delete audio with invalid credentials 2");
+
+        return Err(AppError::Forbidden);
+    }
+
+    let delete = gallery_delete_audio::delete_audio(audio_id, &upload_directory, pool).await?;
+
+    Ok(delete)
+}
\ No newline at end of file
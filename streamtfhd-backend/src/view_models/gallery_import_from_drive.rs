@@ -1,9 +1,11 @@
+use std::sync::Arc;
 use actix_web::HttpRequest;
 use sqlx::{Pool, Postgres};
 use std::env::var;
 use tracing::{debug, error, warn};
 
 use crate::errors::AppError;
+use crate::dto::metrics_state::MetricsState;
 use crate::utils::token::get_jwt_from_header;
 use crate::utils::token::decode_token;
 use crate::utils::user::get_user_id_from_username;
@@ -13,6 +15,7 @@ pub async fn import_from_drive(
     google_drive_url: &String,
     req: &HttpRequest,
     pool: &Pool<Postgres>,
+    metrics_state: &Arc<MetricsState>
 ) -> Result<bool, AppError> {
     let secret_key = match var("JWT_SECRET_KEY") {
         Ok(val) => val,
@@ -58,6 +61,7 @@ pub async fn import_from_drive(
         }
     };
 
+    let _ingest = metrics_state.start_ingest();
     let import = gallery_import_from_drive::import_from_drive(&google_drive_url, &upload_directory, &user_id, &pool).await?;
 
     Ok(import)
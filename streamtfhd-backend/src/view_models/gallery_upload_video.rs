@@ -1,6 +1,8 @@
+use std::sync::Arc;
 use actix_multipart::Multipart;
 use actix_web::HttpRequest;
 use crate::errors::AppError;
+use crate::dto::metrics_state::MetricsState;
 use tracing::{error, debug, warn};
 use std::env::var;
 use crate::utils::token::{decode_token, get_jwt_from_header};
@@ -9,7 +11,7 @@ use sqlx::{Pool, Postgres};
 use crate::models::gallery_upload_video;
 use std::process::Command;
 
-fn is_ffmpeg_installed() -> Result<bool, AppError> {
+pub fn is_ffmpeg_installed() -> Result<bool, AppError> {
     let output = match Command::new("which").arg("ffmpeg").output() {
         Ok(val) => val,
         Err(err) => {
@@ -31,7 +33,8 @@ fn is_ffmpeg_installed() -> Result<bool, AppError> {
 pub async fn upload_video(
     pool: &Pool<Postgres>,
     req: &HttpRequest,
-    payload: Multipart
+    payload: Multipart,
+    metrics_state: &Arc<MetricsState>
 ) -> Result<bool, AppError> {
     let secret_key = match var("JWT_SECRET_KEY") {
         Ok(val) => val,
@@ -87,6 +90,7 @@ pub async fn upload_video(
         return Err(AppError::InternalError(String::from("ffmpeg is not installed")));
     }
 
+    let _ingest = metrics_state.start_ingest();
     let upload = gallery_upload_video::upload_video(payload, &pool, &upload_directory, &user_id).await?;
 
     Ok(upload)
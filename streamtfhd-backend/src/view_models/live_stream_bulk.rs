@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use actix_web::HttpRequest;
+use futures_util::future::join_all;
+use sqlx::{Pool, Postgres};
+use tracing::{error, debug, info, warn};
+use std::env::var;
+
+use crate::{
+    dto::live_stream_bulk::{
+        BulkRequest,
+        BulkResult,
+        StreamGroup,
+        BULK_CANCEL,
+        BULK_DELETE,
+        BULK_RESCHEDULE,
+        BULK_START,
+        BULK_STOP
+    },
+    dto::live_stream_state::{LiveStreamState, StreamStatus},
+    errors::AppError,
+    utils::live_stream::{reschedule_stream, start_stream, stop_stream_internal},
+    utils::live_stream_schedule::validate_schedule,
+    utils::token::{decode_token, get_jwt_from_header},
+    utils::user::get_user_id_from_username,
+    models::{live_stream_bulk, live_stream_delete_stream, live_stream_start}
+};
+
+const MAX_BULK_ITEMS: usize = 200;
+
+async fn get_user_id(
+    req: &HttpRequest,
+    pool: &Pool<Postgres>
+) -> Result<String, AppError> {
+    let secret_key = match var("JWT_SECRET_KEY") {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Missing JWT_SECRET_KEY key and value in env file.");
+            debug!("{}", err.to_string());
+
+            return Err(AppError::EnvVarError(err));
+        }
+    };
+    let jwt = match get_jwt_from_header(req) {
+        Some(val) => val,
+        None => {
+            warn!("An attemp to access bulk live stream endpoint without credentials 1.");
+
+            return Err(AppError::Unauthorized);
+        }
+    };
+    let claims = match decode_token(&jwt, &secret_key) {
+        Ok(val) => val,
+        Err(_err) => {
+            warn!("An attemp to access bulk live stream endpoint with invalid credentials 2.");
+
+            return Err(AppError::Unauthorized);
+        }
+    };
+
+    match get_user_id_from_username(claims.username, pool).await? {
+        Some(val) => Ok(val),
+        None => {
+            warn!("An attemp to access bulk live stream endpoint with invalid credentials 3.");
+
+            Err(AppError::Unauthorized)
+        }
+    }
+}
+
+// The same steps as the endpoint for one live stream, after its owner has
+// been checked.
+async fn apply_action(
+    id: i64,
+    data: &BulkRequest,
+    state: &Arc<LiveStreamState>,
+    pool: &Pool<Postgres>
+) -> Result<(), AppError> {
+    match data.action.as_str() {
+        BULK_START => {
+            let live_stream_data = match live_stream_start::get_live_stream_data(id, pool).await? {
+                Some(val) => val,
+                None => return Err(AppError::BadRequest("Invalid stream ID".to_string()))
+            };
+
+            start_stream(&live_stream_data, state, pool).await?;
+        }
+        BULK_STOP => {
+            info!("Stopping live stream with id {}.", id);
+
+            if !stop_stream_internal(state, id, StreamStatus::Stopped, pool).await {
+                return Err(AppError::Conflict("The live stream is not running or scheduled.".to_string()));
+            }
+        }
+        BULK_CANCEL => {
+            info!("Cancelling live stream schedule with id {}.", id);
+
+            if !stop_stream_internal(state, id, StreamStatus::Cancelled, pool).await {
+                return Err(AppError::Conflict("The live stream is not running or scheduled.".to_string()));
+            }
+        }
+        BULK_RESCHEDULE => {
+            let mut candidate = match live_stream_bulk::get_schedule_candidate(id, pool).await? {
+                Some(val) => val,
+                None => return Err(AppError::BadRequest("Invalid stream ID".to_string()))
+            };
+
+            candidate.schedule_start = data.schedule_start;
+            candidate.schedule_end = data.schedule_end;
+
+            validate_schedule(&candidate, pool).await?;
+
+            if live_stream_bulk::update_schedule(id, data.schedule_start, data.schedule_end, pool).await? {
+                reschedule_stream(state, id, data.schedule_start, data.schedule_end);
+            }
+        }
+        BULK_DELETE => {
+            if state.jobs.get(&id).is_some_and(|job| !job.status.is_final()) {
+                return Err(AppError::Conflict("Stop or cancel the live stream before deleting it.".to_string()));
+            }
+
+            live_stream_delete_stream::delete_stream(id, pool).await?;
+        }
+        _ => return Err(AppError::BadRequest("Unknown bulk action.".to_string()))
+    }
+
+    Ok(())
+}
+
+pub async fn bulk(
+    data: &BulkRequest,
+    req: &HttpRequest,
+    state: &Arc<LiveStreamState>,
+    pool: &Pool<Postgres>
+) -> Result<Vec<BulkResult>, AppError> {
+    let user_id = get_user_id(req, pool).await?;
+
+    if ![BULK_START, BULK_STOP, BULK_CANCEL, BULK_RESCHEDULE, BULK_DELETE].contains(&data.action.as_str()) {
+        return Err(AppError::BadRequest("Unknown bulk action.".to_string()));
+    }
+
+    let ids = match (&data.ids, &data.group) {
+        (Some(ids), None) => {
+            let mut unique: Vec<i64> = Vec::new();
+
+            for id in ids {
+                if !unique.contains(id) {
+                    unique.push(*id);
+                }
+            }
+
+            unique
+        }
+        (None, Some(group)) => live_stream_bulk::get_group_ids(&user_id, &group.trim().to_lowercase(), pool).await?,
+        _ => return Err(AppError::BadRequest("Send either live stream IDs or a group.".to_string()))
+    };
+
+    if ids.len() > MAX_BULK_ITEMS {
+        return Err(AppError::BadRequest(format!("At most {} live streams per bulk request.", MAX_BULK_ITEMS)));
+    }
+
+    let owners: HashMap<i64, String> = live_stream_bulk::get_live_stream_owners(&ids, pool)
+        .await?
+        .into_iter()
+        .collect();
+
+    info!("Bulk {} of {} live streams.", data.action, ids.len());
+
+    let run = |id: i64| {
+        let owners = &owners;
+        let user_id = &user_id;
+
+        async move {
+            let result = match owners.get(&id) {
+                Some(owner) if owner == user_id => apply_action(id, data, state, pool).await,
+                Some(_) => {
+                    warn!("An attemp to {} live stream that not owned by her/him.", data.action);
+
+                    Err(AppError::Forbidden)
+                }
+                None => Err(AppError::BadRequest("Invalid stream ID".to_string()))
+            };
+
+            BulkResult {
+                id,
+                success: result.is_ok(),
+                error: result.err().map(|err| err.to_string())
+            }
+        }
+    };
+
+    // Stopping waits for every ffmpeg to exit, so those run side by side.
+    // Starts go one by one in the order given, admission control sees them
+    // in that order.
+    let results = match data.action.as_str() {
+        BULK_STOP | BULK_CANCEL => join_all(ids.iter().map(|id| run(*id))).await,
+        _ => {
+            let mut results = Vec::new();
+
+            for id in &ids {
+                results.push(run(*id).await);
+            }
+
+            results
+        }
+    };
+
+    Ok(results)
+}
+
+pub async fn get_groups(
+    req: &HttpRequest,
+    pool: &Pool<Postgres>
+) -> Result<Vec<StreamGroup>, AppError> {
+    let user_id = get_user_id(req, pool).await?;
+
+    live_stream_bulk::get_groups(&user_id, pool).await
+}
@@ -0,0 +1,68 @@
+use actix_web::HttpRequest;
+use sqlx::{Pool, Postgres};
+use tracing::{error, debug, warn};
+use std::env::var;
+
+use crate::{
+    dto::live_stream_schedule::CalendarEntry,
+    errors::AppError,
+    models::live_stream_schedule,
+    utils::token::{decode_token, get_jwt_from_header},
+    utils::user::get_user_id_from_username
+};
+
+// Longest range served by the calendar endpoint.
+const MAX_CALENDAR_RANGE: i64 = 31622400;   // 366 days
+
+pub async fn get_calendar(
+    from: i64,
+    to: i64,
+    req: &HttpRequest,
+    pool: &Pool<Postgres>
+) -> Result<Vec<CalendarEntry>, AppError> {
+    let secret_key = match var("JWT_SECRET_KEY") {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Missing JWT_SECRET_KEY key and value in env file.");
+            debug!("{}", err.to_string());
+
+            return Err(AppError::EnvVarError(err));
+        }
+    };
+    let jwt = match get_jwt_from_header(req) {
+        Some(val) => val,
+        None => {
+            warn!("An attemp to access live stream calendar endpoint without credentials 1.");
+
+            return Err(AppError::Unauthorized);
+        }
+    };
+    let claims = match decode_token(&jwt, &secret_key) {
+        Ok(val) => val,
+        Err(_err) => {
+            warn!("An attemp to access live stream calendar endpoint with invalid credentials 2.");
+
+            return Err(AppError::Unauthorized);
+        }
+    };
+    let user_id = match get_user_id_from_username(claims.username, pool).await? {
+        Some(val) => val,
+        None => {
+            warn!("An attemp to access live stream calendar endpoint with invalid credentials 3.");
+
+            return Err(AppError::Unauthorized);
+        }
+    };
+
+    if from >= to {
+        return Err(AppError::BadRequest("The from parameter must be earlier than the to parameter.".to_string()));
+    }
+
+    if to - from > MAX_CALENDAR_RANGE {
+        return Err(AppError::BadRequest("The calendar range can not be longer than 366 days.".to_string()));
+    }
+
+    let entries = live_stream_schedule::get_calendar(&user_id, from, to, pool).await?;
+
+    Ok(entries)
+}
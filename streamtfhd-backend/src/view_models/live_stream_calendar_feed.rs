@@ -0,0 +1,85 @@
+use actix_web::HttpRequest;
+use sqlx::{Pool, Postgres};
+use tracing::{error, debug, warn};
+use std::env::var;
+use uuid::Uuid;
+
+use crate::{
+    errors::AppError,
+    models::live_stream_schedule,
+    utils::icalendar::build_calendar,
+    utils::time::current_unix_timestamp,
+    utils::token::{decode_token, get_jwt_from_header},
+    utils::user::get_user_id_from_username
+};
+
+// Returns the token of the user's calendar feed, creating it on first use.
+pub async fn get_feed_token(
+    regenerate: bool,
+    req: &HttpRequest,
+    pool: &Pool<Postgres>
+) -> Result<String, AppError> {
+    let secret_key = match var("JWT_SECRET_KEY") {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Missing JWT_SECRET_KEY key and value in env file.");
+            debug!("{}", err.to_string());
+
+            return Err(AppError::EnvVarError(err));
+        }
+    };
+    let jwt = match get_jwt_from_header(req) {
+        Some(val) => val,
+        None => {
+            warn!("An attemp to access calendar feed token endpoint without credentials 1.");
+
+            return Err(AppError::Unauthorized);
+        }
+    };
+    let claims = match decode_token(&jwt, &secret_key) {
+        Ok(val) => val,
+        Err(_err) => {
+            warn!("An attemp to access calendar feed token endpoint with invalid credentials 2.");
+
+            return Err(AppError::Unauthorized);
+        }
+    };
+    let user_id = match get_user_id_from_username(claims.username, pool).await? {
+        Some(val) => val,
+        None => {
+            warn!("An attemp to access calendar feed token endpoint with invalid credentials 3.");
+
+            return Err(AppError::Unauthorized);
+        }
+    };
+
+    if !regenerate && let Some(token) = live_stream_schedule::get_calendar_token(&user_id, pool).await? {
+        return Ok(token);
+    }
+
+    let token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+
+    live_stream_schedule::set_calendar_token(&user_id, &token, pool).await?;
+
+    Ok(token)
+}
+
+// The feed is read by calendar applications, which can not send the JWT,
+// so the token in the URL is the only credential.
+pub async fn get_feed(
+    token: &String,
+    pool: &Pool<Postgres>
+) -> Result<String, AppError> {
+    let user_id = match live_stream_schedule::get_user_id_from_calendar_token(token, pool).await? {
+        Some(val) => val,
+        None => {
+            warn!("An attemp to access calendar feed with invalid token.");
+
+            return Err(AppError::NotFound);
+        }
+    };
+    let now = current_unix_timestamp() as i64;
+    let entries = live_stream_schedule::get_calendar(&user_id, now, i64::MAX, pool).await?;
+
+    Ok(build_calendar("StreamTFHD live streams", &entries, now))
+}
@@ -0,0 +1,79 @@
+use actix_web::HttpRequest;
+use sqlx::{Pool, Postgres};
+use tracing::{error, debug, warn};
+use std::env::var;
+
+use crate::{
+    dto::live_stream_template::CloneRequest,
+    errors::AppError,
+    utils::live_stream_template::{clone_data, clone_schedule, merge_settings},
+    utils::time::current_unix_timestamp,
+    utils::token::{decode_token, get_jwt_from_header},
+    utils::user::get_user_id_from_username,
+    view_models::live_stream_create_stream::create_owned_live_stream,
+    view_models::live_stream_template::{get_owned_live_stream_settings, get_owned_template}
+};
+
+// A new live stream from an existing one or from a template, with the
+// overrides over it. It is checked like any other new live stream.
+pub async fn clone_live_stream(
+    data: &CloneRequest,
+    req: &HttpRequest,
+    pool: &Pool<Postgres>
+) -> Result<i64, AppError> {
+    let secret_key = match var("JWT_SECRET_KEY") {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Missing JWT_SECRET_KEY key and value in env file.");
+            debug!("{}", err.to_string());
+
+            return Err(AppError::EnvVarError(err));
+        }
+    };
+    let jwt = match get_jwt_from_header(req) {
+        Some(val) => val,
+        None => {
+            warn!("An attemp to access clone live stream endpoint without credentials 1.");
+
+            return Err(AppError::Unauthorized);
+        }
+    };
+    let claims = match decode_token(&jwt, &secret_key) {
+        Ok(val) => val,
+        Err(_err) => {
+            warn!("An attemp to access clone live stream endpoint with invalid credentials 2.");
+
+            return Err(AppError::Unauthorized);
+        }
+    };
+    let user_id = match get_user_id_from_username(claims.username, pool).await? {
+        Some(val) => val,
+        None => {
+            warn!("An attemp to access clone live stream endpoint with invalid credentials 3.");
+
+            return Err(AppError::Unauthorized);
+        }
+    };
+
+    let base = match (data.live_stream, data.template) {
+        (Some(id), None) => get_owned_live_stream_settings(id, &user_id, pool).await?,
+        (None, Some(id)) => {
+            let template = get_owned_template(id, &user_id, pool).await?;
+            let mut settings = template.settings.0;
+
+            // A template without a title names its live streams.
+            settings.title = settings.title.or(Some(template.name));
+            settings
+        },
+        _ => return Err(AppError::BadRequest("Clone either a live stream or a template.".to_string()))
+    };
+    let settings = merge_settings(base, data.overrides.clone());
+    let (schedule_start, schedule_end) = clone_schedule(
+        &settings,
+        data.schedule_start,
+        data.schedule_end,
+        current_unix_timestamp() as i64
+    );
+
+    create_owned_live_stream(&user_id, &clone_data(settings, schedule_start, schedule_end), pool).await
+}
\ No newline at end of file
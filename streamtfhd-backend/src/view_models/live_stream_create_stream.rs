@@ -5,10 +5,18 @@ use std::env::var;
 
 use crate::{
     dto::live_stream_create_stream::CreateLiveStreamData,
+    dto::live_stream_schedule::ScheduleCandidate,
+    dto::live_stream_source::{LiveStreamSource, INGEST_RTMP, MODE_INGEST, MODE_VIDEO},
     errors::AppError,
     utils::token::get_jwt_from_header,
     utils::token::decode_token,
     utils::user::get_user_id_from_username,
+    utils::live_stream_schedule::validate_schedule,
+    utils::live_stream_source::validate_source,
+    utils::live_stream_destination::resolve_destination,
+    utils::live_stream_overlay::validate_overlays,
+    utils::live_stream_ingest::assign_ingest_port,
+    utils::live_stream_group::normalize_tags,
     models::live_stream_create_stream
 };
 
@@ -51,7 +59,63 @@ pub async fn create_live_stream(
         }
     };
 
-    let create = live_stream_create_stream::create_live_stream(&user_id, &data, &pool).await?;
+    create_owned_live_stream(&user_id, data, pool).await
+}
+
+// Checks and stores a new live stream of the user. Clones are stored through
+// it as well, so they are checked like any other new live stream.
+pub async fn create_owned_live_stream(
+    user_id: &String,
+    data: &CreateLiveStreamData,
+    pool: &Pool<Postgres>
+) -> Result<i64, AppError> {
+    let mode = data.mode.clone().unwrap_or(MODE_VIDEO.to_string());
+    // RTMP unless told otherwise, OBS pushes it out of the box.
+    let ingest_protocol = match mode == MODE_INGEST {
+        true => Some(data.ingest_protocol.clone().unwrap_or(INGEST_RTMP.to_string())),
+        false => data.ingest_protocol.clone()
+    };
+    let source = LiveStreamSource {
+        mode,
+        video: data.video.map(|val| val as i64),
+        background_image: data.background_image,
+        audios: data.audios.clone().unwrap_or_default(),
+        slate_video: data.slate_video,
+        slate_image: data.slate_image,
+        slate_audio: data.slate_audio,
+        stream_loop: data.stream_loop,
+        trim_start: data.trim_start,
+        trim_end: data.trim_end,
+        ingest_protocol
+    };
+
+    validate_source(&source, user_id, pool).await?;
+
+    if let Some(overlays) = &data.overlays {
+        validate_overlays(overlays, user_id, pool).await?;
+    }
+
+    let destination = resolve_destination(&data.destination, &data.rtmp_url, &data.stream_key)?;
+    let (_, stream_key) = destination.address();
+
+    let candidate = ScheduleCandidate {
+        id: None,
+        owner: user_id.clone(),
+        mode: source.mode.clone(),
+        video: source.video,
+        endpoints: destination.endpoints(),
+        stream_key,
+        schedule_start: data.schedule_start,
+        schedule_end: data.schedule_end,
+        dry_run: data.dry_run.unwrap_or(false)
+    };
+
+    validate_schedule(&candidate, pool).await?;
+
+    let ingest_port = assign_ingest_port(None, &source, pool).await?;
+    let tags = normalize_tags(data.tags.as_deref().unwrap_or_default())?;
+
+    let create = live_stream_create_stream::create_live_stream(user_id, data, &source, &destination, ingest_port, &tags, pool).await?;
 
     Ok(create)
 }
\ No newline at end of file
@@ -1,21 +1,36 @@
+use std::sync::Arc;
 use actix_web::HttpRequest;
 use sqlx::{Pool, Postgres};
 use std::env::var;
-use tracing::{error, debug, warn};
+use tracing::{error, debug, info, warn};
 
 use crate::{
-    dto::live_stream_edit_stream_post::LiveStream,
+    dto::live_stream_edit_stream_post::{EditOutcome, LiveStream},
+    dto::live_stream_state::LiveStreamState,
+    dto::live_stream_schedule::ScheduleCandidate,
+    dto::live_stream_source::{LiveStreamSource, INGEST_RTMP, MODE_INGEST, MODE_VIDEO},
     errors::AppError,
     utils::token::{decode_token, get_jwt_from_header},
     utils::user::get_user_id_from_username,
-    models::live_stream_edit_stream_post
+    utils::live_stream_schedule::validate_schedule,
+    utils::live_stream_source::validate_source,
+    utils::live_stream_destination::resolve_destination,
+    utils::live_stream_overlay::{apply_overlays, validate_overlays},
+    utils::live_stream_ingest::assign_ingest_port,
+    utils::live_stream_admission::is_running,
+    utils::live_stream_edit::{edited_settings, restart_changes},
+    utils::live_stream_group::normalize_tags,
+    utils::live_stream::{end_job_for_restart, reschedule_stream, start_stream},
+    models::{live_stream_edit_stream_post, live_stream_start}
 };
 
+
 pub async fn update_live_stream_data(
     data: &LiveStream,
     req: &HttpRequest,
-    pool: &Pool<Postgres>
-) -> Result<bool, AppError> {
+    pool: &Pool<Postgres>,
+    state: &Arc<LiveStreamState>
+) -> Result<EditOutcome, AppError> {
     let secret_key = match var("JWT_SECRET_KEY") {
         Ok(val) => val,
         Err(err) => {
@@ -50,7 +65,7 @@ pub async fn update_live_stream_data(
         }
     };
     
-    match live_stream_edit_stream_post::get_live_stream_owner(data.id, &pool).await? {
+    match live_stream_edit_stream_post::get_live_stream_owner(data.id, pool).await? {
         Some(val) => {
             if user_id.ne(&val) {
                 warn!("An attemp to edit live stream that not owned by him/her.");
@@ -62,7 +77,112 @@ pub async fn update_live_stream_data(
         }
     };
 
-    let update = live_stream_edit_stream_post::update_live_stream_data(&data, &pool).await?;
+    let mode = data.mode.clone().unwrap_or(MODE_VIDEO.to_string());
+    // RTMP unless told otherwise, OBS pushes it out of the box.
+    let ingest_protocol = match mode == MODE_INGEST {
+        true => Some(data.ingest_protocol.clone().unwrap_or(INGEST_RTMP.to_string())),
+        false => data.ingest_protocol.clone()
+    };
+    let source = LiveStreamSource {
+        mode,
+        video: data.video,
+        background_image: data.background_image,
+        audios: data.audios.clone().unwrap_or_default(),
+        slate_video: data.slate_video,
+        slate_image: data.slate_image,
+        slate_audio: data.slate_audio,
+        stream_loop: data.stream_loop,
+        trim_start: data.trim_start,
+        trim_end: data.trim_end,
+        ingest_protocol
+    };
+
+    validate_source(&source, &user_id, pool).await?;
+
+    if let Some(overlays) = &data.overlays {
+        validate_overlays(overlays, &user_id, pool).await?;
+    }
+
+    let destination = resolve_destination(&data.destination, &data.rtmp_url, &data.stream_key)?;
+    let (_, stream_key) = destination.address();
+
+    let candidate = ScheduleCandidate {
+        id: Some(data.id),
+        owner: user_id.clone(),
+        mode: source.mode.clone(),
+        video: source.video,
+        endpoints: destination.endpoints(),
+        stream_key,
+        schedule_start: data.schedule_start,
+        schedule_end: data.schedule_end,
+        dry_run: data.dry_run.unwrap_or(false)
+    };
+
+    validate_schedule(&candidate, pool).await?;
+
+    let ingest_port = assign_ingest_port(Some(data.id), &source, pool).await?;
+    let tags = match &data.tags {
+        Some(val) => Some(normalize_tags(val)?),
+        None => None
+    };
+
+    // A job is built from the settings when it starts. A scheduled one is
+    // simply built again, a running one only when the caller allows it.
+    let job_status = state.jobs.get(&data.id).map(|job| job.status.clone());
+    let is_restart = match &job_status {
+        Some(status) if !status.is_final() => {
+            let current = match live_stream_edit_stream_post::get_stream_settings(data.id, pool).await? {
+                Some(val) => val,
+                None => return Err(AppError::BadRequest("Invalid live stream ID".to_string()))
+            };
+            let changes = restart_changes(
+                &current,
+                &edited_settings(data, &source, &destination),
+                data.reset_ingest_key.unwrap_or(false)
+            );
+
+            if !changes.is_empty() && is_running(status) && !data.restart.unwrap_or(false) {
+                return Err(AppError::Conflict(format!(
+                    "The live stream is running, changing its {} needs a restart. Send the edit with restart to restart it now.",
+                    changes.join(", ")
+                )));
+            }
+
+            !changes.is_empty()
+        }
+        _ => false
+    };
+
+    // The edit is stored first, a failed update leaves the job as it was.
+    let update = live_stream_edit_stream_post::update_live_stream_data(data, &source, &destination, ingest_port, tags.as_deref(), pool).await?;
+
+    if update && is_restart {
+        info!(stream_id = data.id, "restarting live stream to apply an edit");
+
+        // The job finished on its own in the meantime.
+        if !end_job_for_restart(state, data.id, pool).await {
+            return Ok(EditOutcome {
+                update,
+                restarted: false
+            });
+        }
+
+        let live_stream_data = match live_stream_start::get_live_stream_data(data.id, pool).await? {
+            Some(val) => val,
+            None => return Err(AppError::BadRequest("Invalid live stream ID".to_string()))
+        };
+
+        start_stream(&live_stream_data, state, pool).await?;
+    } else if update {
+        if let Some(overlays) = &data.overlays {
+            apply_overlays(state, data.id, overlays, pool).await?;
+        }
+
+        reschedule_stream(state, data.id, data.schedule_start, data.schedule_end);
+    }
 
-    Ok(update)
+    Ok(EditOutcome {
+        update,
+        restarted: update && is_restart
+    })
 }
\ No newline at end of file
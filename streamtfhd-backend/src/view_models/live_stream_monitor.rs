@@ -1,6 +1,7 @@
 use std::sync::Arc;
 use sqlx::{Pool, Postgres};
 use std::env::var;
+use tokio::sync::broadcast::error::RecvError;
 use tracing::{error, warn, debug};
 use actix_ws::{Message, CloseReason, CloseCode};
 use actix_web::{web, HttpRequest, HttpResponse};
@@ -8,9 +9,10 @@ use serde::Deserialize;
 
 use crate::{
     dto::live_stream_state::LiveStreamState,
-    dto::live_stream_monitor::TickMessage,
-    dto::live_stream_state::StreamStatus,
+    dto::live_stream_monitor::{EventMessage, TickMessage},
+    dto::live_stream_state::LoopProgress,
     errors::AppError,
+    utils::live_stream_preview::preview_entry,
     utils::token::decode_token,
     utils::user::get_user_id_from_username
 };
@@ -18,7 +20,9 @@ use crate::{
 #[derive(Debug, Deserialize)]
 struct AuthMessage {
     msg_type: String,
-    jwt: String
+    jwt: String,
+    #[serde(default)]
+    events: bool
 }
 
 pub async fn monitor(
@@ -120,6 +124,7 @@ pub async fn monitor(
         };
 
         let mut interval = tokio::time::interval(std::time::Duration::from_secs(1));
+        let mut events = auth.events.then(|| live_stream_state_clone.events.subscribe());
 
         loop {
             tokio::select! {
@@ -128,22 +133,24 @@ pub async fn monitor(
 
                     for entry in &live_stream_state_clone.jobs {
                         if entry.value().owner == owner {
-                            let status_str = match entry.value().status {
-                                StreamStatus::Offline => "offline",
-                                StreamStatus::Scheduled => "scheduled",
-                                StreamStatus::Starting => "starting",
-                                StreamStatus::Live => "live",
-                                StreamStatus::Done => "done",
-                                StreamStatus::Stopped => "stopped",
-                                StreamStatus::Cancelled => "cancelled",
-                                StreamStatus::Failed(_) => "failed"
-                            };
+                            let status_str = entry.value().status.as_str();
                             let data = TickMessage {
                                 id: entry.value().id,
                                 schedule_start: entry.value().schedule_start,
                                 schedule_end: entry.value().schedule_end,
                                 started_at: entry.value().actual_start,
-                                status: status_str.to_string()
+                                status: status_str.to_string(),
+                                resources: entry.value().resources.clone(),
+                                loop_progress: LoopProgress {
+                                    position_ms: entry.value().playback.position_ms,
+                                    ..entry.value().loops.clone()
+                                },
+                                endpoints: entry.value().endpoints.clone(),
+                                preview: entry.value().launch
+                                    .as_ref()
+                                    .and_then(|launch| launch.preview.as_ref())
+                                    .map(|preview| preview_entry(preview).to_string()),
+                                dry_run: entry.value().dry_run
                             };
 
                             datas.push(data);
@@ -159,6 +166,37 @@ pub async fn monitor(
                     }
                 }
 
+                event = async {
+                    match events.as_mut() {
+                        Some(rx) => rx.recv().await,
+                        None => std::future::pending().await
+                    }
+                } => {
+                    match event {
+                        Ok(event) if event.owner == owner => {
+                            let message = EventMessage {
+                                msg_type: "event",
+                                event
+                            };
+
+                            if session
+                                .text(serde_json::to_string(&message).unwrap())
+                                .await
+                                .is_err()
+                            {
+                                break;
+                            }
+                        }
+                        Ok(_) => {}
+                        Err(RecvError::Lagged(skipped)) => {
+                            warn!("Monitor live stream client fell behind, {} events skipped.", skipped);
+                        }
+                        Err(RecvError::Closed) => {
+                            events = None;
+                        }
+                    }
+                }
+
                 msg = msg_stream.recv() => {
                     match msg {
                         Some(Ok(Message::Ping(p))) => {
@@ -0,0 +1,76 @@
+use std::sync::Arc;
+use actix_web::HttpRequest;
+use sqlx::{Pool, Postgres};
+use tracing::{error, debug, warn};
+use std::env::var;
+
+use crate::{
+    dto::live_stream_state::LiveStreamState,
+    dto::live_stream_overlay_text::OverlayTextData,
+    errors::AppError,
+    models::live_stream_edit_stream_post::get_live_stream_owner,
+    utils::live_stream_overlay::{MAX_TEXT_LENGTH, set_overlay_text},
+    utils::token::{decode_token, get_jwt_from_header},
+    utils::user::get_user_id_from_username
+};
+
+pub async fn update_overlay_text(
+    data: &OverlayTextData,
+    req: &HttpRequest,
+    pool: &Pool<Postgres>,
+    state: &Arc<LiveStreamState>
+) -> Result<bool, AppError> {
+    let secret_key = match var("JWT_SECRET_KEY") {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Missing JWT_SECRET_KEY key and value in env file.");
+            debug!("{}", err.to_string());
+
+            return Err(AppError::EnvVarError(err));
+        }
+    };
+    let jwt = match get_jwt_from_header(req) {
+        Some(val) => val,
+        None => {
+            warn!("An attemp to access overlay text endpoint without credentials 1.");
+
+            return Err(AppError::Unauthorized);
+        }
+    };
+    let claims = match decode_token(&jwt, &secret_key) {
+        Ok(val) => val,
+        Err(_err) => {
+            warn!("An attemp to access overlay text endpoint with invalid credentials 2.");
+
+            return Err(AppError::Unauthorized);
+        }
+    };
+    let user_id = match get_user_id_from_username(claims.username, pool).await? {
+        Some(val) => val,
+        None => {
+            warn!("An attemp to access overlay text endpoint with invalid credentials 3.");
+
+            return Err(AppError::Unauthorized);
+        }
+    };
+
+    match get_live_stream_owner(data.live_stream_id, pool).await? {
+        Some(val) => {
+            if user_id.ne(&val) {
+                warn!("An attemp to change the overlay text of a live stream that not owned by him/her.");
+                return Err(AppError::Forbidden);
+            }
+        },
+        None => {
+            return Err(AppError::BadRequest("Invalid live stream ID".to_string()));
+        }
+    };
+
+    if data.text.chars().count() > MAX_TEXT_LENGTH {
+        return Err(AppError::ValidationError(format!("Overlay text must be at most {} characters.", MAX_TEXT_LENGTH)));
+    }
+
+    set_overlay_text(state, data.live_stream_id, data.overlay, &data.text)?;
+
+    Ok(true)
+}
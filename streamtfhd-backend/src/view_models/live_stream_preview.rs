@@ -0,0 +1,88 @@
+use std::sync::Arc;
+use actix_web::{HttpRequest, HttpResponse};
+use actix_web::http::header;
+use sqlx::{Pool, Postgres};
+use tracing::{error, debug, warn};
+use std::env::var;
+
+use crate::{
+    dto::live_stream_state::LiveStreamState,
+    errors::AppError,
+    models::live_stream_edit_stream_post::get_live_stream_owner,
+    utils::live_stream_preview::preview_file,
+    utils::token::{decode_token, get_jwt_from_header},
+    utils::user::get_user_id_from_username
+};
+
+pub async fn get_preview(
+    id: i64,
+    file: &str,
+    req: &HttpRequest,
+    pool: &Pool<Postgres>,
+    state: &Arc<LiveStreamState>
+) -> Result<HttpResponse, AppError> {
+    let secret_key = match var("JWT_SECRET_KEY") {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Missing JWT_SECRET_KEY key and value in env file.");
+            debug!("{}", err.to_string());
+
+            return Err(AppError::EnvVarError(err));
+        }
+    };
+    let jwt = match get_jwt_from_header(req) {
+        Some(val) => val,
+        None => {
+            warn!("An attemp to access live stream preview endpoint without credentials 1.");
+
+            return Err(AppError::Unauthorized);
+        }
+    };
+    let claims = match decode_token(&jwt, &secret_key) {
+        Ok(val) => val,
+        Err(_err) => {
+            warn!("An attemp to access live stream preview endpoint with invalid credentials 2.");
+
+            return Err(AppError::Unauthorized);
+        }
+    };
+    let user_id = match get_user_id_from_username(claims.username, pool).await? {
+        Some(val) => val,
+        None => {
+            warn!("An attemp to access live stream preview endpoint with invalid credentials 3.");
+
+            return Err(AppError::Unauthorized);
+        }
+    };
+
+    match get_live_stream_owner(id, pool).await? {
+        Some(val) => {
+            if user_id.ne(&val) {
+                warn!("An attemp to watch the preview of a live stream that not owned by him/her.");
+                return Err(AppError::Forbidden);
+            }
+        },
+        None => {
+            return Err(AppError::BadRequest("Invalid live stream ID".to_string()));
+        }
+    };
+
+    if !state.jobs.contains_key(&id) {
+        return Err(AppError::NotFound);
+    }
+
+    let (path, content_type) = match preview_file(&state.upload_dir, id, file) {
+        Some(val) => val,
+        None => return Err(AppError::NotFound)
+    };
+    // Missing until ffmpeg writes the first segment or snapshot.
+    let body = match tokio::fs::read(&path).await {
+        Ok(val) => val,
+        Err(_) => return Err(AppError::NotFound)
+    };
+
+    Ok(HttpResponse::Ok()
+        .content_type(content_type)
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        .body(body))
+}
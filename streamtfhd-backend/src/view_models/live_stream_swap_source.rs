@@ -0,0 +1,92 @@
+use std::sync::Arc;
+use actix_web::HttpRequest;
+use sqlx::{Pool, Postgres};
+use tracing::{error, debug, warn};
+use std::env::var;
+
+use crate::{
+    dto::live_stream_state::{LiveStreamState, SourceItem},
+    dto::live_stream_swap_source::SwapSourceData,
+    errors::AppError,
+    models::live_stream_edit_stream_post::get_live_stream_owner,
+    models::live_stream_swap_source::get_video_files,
+    utils::live_stream::swap_source,
+    utils::token::{decode_token, get_jwt_from_header},
+    utils::user::get_user_id_from_username
+};
+
+pub async fn swap_live_stream_source(
+    data: &SwapSourceData,
+    req: &HttpRequest,
+    pool: &Pool<Postgres>,
+    state: &Arc<LiveStreamState>
+) -> Result<bool, AppError> {
+    let secret_key = match var("JWT_SECRET_KEY") {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Missing JWT_SECRET_KEY key and value in env file.");
+            debug!("{}", err.to_string());
+
+            return Err(AppError::EnvVarError(err));
+        }
+    };
+    let jwt = match get_jwt_from_header(req) {
+        Some(val) => val,
+        None => {
+            warn!("An attemp to access swap live stream source endpoint without credentials 1.");
+
+            return Err(AppError::Unauthorized);
+        }
+    };
+    let claims = match decode_token(&jwt, &secret_key) {
+        Ok(val) => val,
+        Err(_err) => {
+            warn!("An attemp to access swap live stream source endpoint with invalid credentials 2.");
+
+            return Err(AppError::Unauthorized);
+        }
+    };
+    let user_id = match get_user_id_from_username(claims.username, pool).await? {
+        Some(val) => val,
+        None => {
+            warn!("An attemp to access swap live stream source endpoint with invalid credentials 3.");
+
+            return Err(AppError::Unauthorized);
+        }
+    };
+
+    match get_live_stream_owner(data.live_stream_id, pool).await? {
+        Some(val) => {
+            if user_id.ne(&val) {
+                warn!("An attemp to swap the source of a live stream that not owned by him/her.");
+                return Err(AppError::Forbidden);
+            }
+        },
+        None => {
+            return Err(AppError::BadRequest("Invalid live stream ID".to_string()));
+        }
+    };
+
+    if data.videos.is_empty() {
+        return Err(AppError::ValidationError("At least one video is required.".to_string()));
+    }
+
+    let files = get_video_files(&data.videos, &user_id, pool).await?;
+    let mut items = Vec::new();
+
+    // Keeps the order of the request, a video may appear more than once.
+    for video_id in &data.videos {
+        match files.iter().find(|(id, _)| id == video_id) {
+            Some((_, file)) => items.push(SourceItem::Video {
+                file: format!("{}/videos/{}", state.upload_dir, file),
+                start: 0,
+                end: None
+            }),
+            None => return Err(AppError::BadRequest("Invalid video ID".to_string()))
+        }
+    }
+
+    swap_source(state, data.live_stream_id, items)?;
+
+    Ok(true)
+}
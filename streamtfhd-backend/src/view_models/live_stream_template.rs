@@ -0,0 +1,161 @@
+use actix_web::HttpRequest;
+use sqlx::{Pool, Postgres};
+use tracing::{error, debug, warn};
+use std::env::var;
+
+use crate::{
+    dto::live_stream_template::{Template, TemplateData, TemplateSettings},
+    errors::AppError,
+    utils::live_stream_template::{merge_settings, validate_template},
+    utils::token::{decode_token, get_jwt_from_header},
+    utils::user::get_user_id_from_username,
+    models::live_stream_template
+};
+
+async fn get_user_id(
+    req: &HttpRequest,
+    pool: &Pool<Postgres>
+) -> Result<String, AppError> {
+    let secret_key = match var("JWT_SECRET_KEY") {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Missing JWT_SECRET_KEY key and value in env file.");
+            debug!("{}", err.to_string());
+
+            return Err(AppError::EnvVarError(err));
+        }
+    };
+    let jwt = match get_jwt_from_header(req) {
+        Some(val) => val,
+        None => {
+            warn!("An attemp to access live stream template endpoint without credentials 1.");
+
+            return Err(AppError::Unauthorized);
+        }
+    };
+    let claims = match decode_token(&jwt, &secret_key) {
+        Ok(val) => val,
+        Err(_err) => {
+            warn!("An attemp to access live stream template endpoint with invalid credentials 2.");
+
+            return Err(AppError::Unauthorized);
+        }
+    };
+
+    match get_user_id_from_username(claims.username, pool).await? {
+        Some(val) => Ok(val),
+        None => {
+            warn!("An attemp to access live stream template endpoint with invalid credentials 3.");
+
+            Err(AppError::Unauthorized)
+        }
+    }
+}
+
+// Settings of a live stream owned by the user.
+pub async fn get_owned_live_stream_settings(
+    id: i64,
+    user_id: &String,
+    pool: &Pool<Postgres>
+) -> Result<TemplateSettings, AppError> {
+    let (owner, settings) = match live_stream_template::get_live_stream_settings(id, pool).await? {
+        Some(val) => val,
+        None => return Err(AppError::NotFound)
+    };
+
+    if owner != *user_id {
+        warn!("An attemp to copy a live stream that not owned by him/her.");
+
+        return Err(AppError::Forbidden);
+    }
+
+    Ok(settings)
+}
+
+// A template owned by the user.
+pub async fn get_owned_template(
+    id: i64,
+    user_id: &String,
+    pool: &Pool<Postgres>
+) -> Result<Template, AppError> {
+    let owner = match live_stream_template::get_template_owner(id, pool).await? {
+        Some(val) => val,
+        None => return Err(AppError::NotFound)
+    };
+
+    if owner != *user_id {
+        warn!("An attemp to access a live stream template that not owned by him/her.");
+
+        return Err(AppError::Forbidden);
+    }
+
+    match live_stream_template::get_template(id, pool).await? {
+        Some(val) => Ok(val),
+        None => Err(AppError::NotFound)
+    }
+}
+
+async fn template_settings(
+    data: &TemplateData,
+    user_id: &String,
+    pool: &Pool<Postgres>
+) -> Result<TemplateSettings, AppError> {
+    let settings = match data.from_live_stream {
+        Some(id) => merge_settings(get_owned_live_stream_settings(id, user_id, pool).await?, data.settings.clone()),
+        None => data.settings.clone()
+    };
+
+    validate_template(&data.name, &settings)?;
+
+    Ok(settings)
+}
+
+pub async fn create_template(
+    data: &TemplateData,
+    req: &HttpRequest,
+    pool: &Pool<Postgres>
+) -> Result<i64, AppError> {
+    let user_id = get_user_id(req, pool).await?;
+    let settings = template_settings(data, &user_id, pool).await?;
+
+    live_stream_template::insert_template(&user_id, data.name.trim(), &settings, pool).await
+}
+
+pub async fn get_templates(
+    req: &HttpRequest,
+    pool: &Pool<Postgres>
+) -> Result<Vec<Template>, AppError> {
+    let user_id = get_user_id(req, pool).await?;
+
+    live_stream_template::get_templates(&user_id, pool).await
+}
+
+pub async fn update_template(
+    data: &TemplateData,
+    req: &HttpRequest,
+    pool: &Pool<Postgres>
+) -> Result<bool, AppError> {
+    let user_id = get_user_id(req, pool).await?;
+    let id = match data.id {
+        Some(val) => val,
+        None => return Err(AppError::BadRequest("Missing template id.".to_string()))
+    };
+
+    get_owned_template(id, &user_id, pool).await?;
+
+    let settings = template_settings(data, &user_id, pool).await?;
+
+    live_stream_template::update_template(id, data.name.trim(), &settings, pool).await
+}
+
+pub async fn delete_template(
+    id: i64,
+    req: &HttpRequest,
+    pool: &Pool<Postgres>
+) -> Result<bool, AppError> {
+    let user_id = get_user_id(req, pool).await?;
+
+    get_owned_template(id, &user_id, pool).await?;
+
+    live_stream_template::delete_template(id, pool).await
+}
\ No newline at end of file
@@ -0,0 +1,94 @@
+use std::sync::Arc;
+use actix_web::HttpRequest;
+use sqlx::{Pool, Postgres};
+use tracing::{error, debug, warn};
+use std::env::var;
+
+use crate::{
+    dto::live_stream_state::LiveStreamState,
+    dto::live_stream_test_destination::{DestinationTest, TestDestinationData},
+    errors::AppError,
+    models::live_stream_edit_stream_post::get_live_stream_owner,
+    models::live_stream_swap_source::get_video_files,
+    models::live_stream_test_destination::{get_destination, save_destination_test},
+    utils::live_stream_admission::is_running,
+    utils::live_stream_test_destination::{ProbeConfig, test_destination},
+    utils::token::{decode_token, get_jwt_from_header},
+    utils::user::get_user_id_from_username
+};
+
+pub async fn test_live_stream_destination(
+    data: &TestDestinationData,
+    req: &HttpRequest,
+    pool: &Pool<Postgres>,
+    state: &Arc<LiveStreamState>
+) -> Result<DestinationTest, AppError> {
+    let secret_key = match var("JWT_SECRET_KEY") {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Missing JWT_SECRET_KEY key and value in env file.");
+            debug!("{}", err.to_string());
+
+            return Err(AppError::EnvVarError(err));
+        }
+    };
+    let jwt = match get_jwt_from_header(req) {
+        Some(val) => val,
+        None => {
+            warn!("An attemp to access test live stream destination endpoint without credentials 1.");
+
+            return Err(AppError::Unauthorized);
+        }
+    };
+    let claims = match decode_token(&jwt, &secret_key) {
+        Ok(val) => val,
+        Err(_err) => {
+            warn!("An attemp to access test live stream destination endpoint with invalid credentials 2.");
+
+            return Err(AppError::Unauthorized);
+        }
+    };
+    let user_id = match get_user_id_from_username(claims.username, pool).await? {
+        Some(val) => val,
+        None => {
+            warn!("An attemp to access test live stream destination endpoint with invalid credentials 3.");
+
+            return Err(AppError::Unauthorized);
+        }
+    };
+
+    match get_live_stream_owner(data.live_stream_id, pool).await? {
+        Some(val) => {
+            if user_id.ne(&val) {
+                warn!("An attemp to test the destination of a live stream that not owned by him/her.");
+                return Err(AppError::Forbidden);
+            }
+        },
+        None => {
+            return Err(AppError::BadRequest("Invalid live stream ID".to_string()));
+        }
+    };
+
+    // A second publisher on the same key would knock the running stream off.
+    if let Some(job) = state.jobs.get(&data.live_stream_id) && is_running(&job.status) {
+        return Err(AppError::Conflict("Live stream is running, its destination can not be tested now.".to_string()));
+    }
+
+    let destination = match get_destination(data.live_stream_id, pool).await? {
+        Some(val) => val,
+        None => return Err(AppError::BadRequest("Invalid live stream ID".to_string()))
+    };
+    let video_file = match data.video {
+        Some(video_id) => match get_video_files(&[video_id], &user_id, pool).await?.pop() {
+            Some((_, file)) => Some(format!("{}/videos/{}", state.upload_dir, file)),
+            None => return Err(AppError::BadRequest("Invalid video ID".to_string()))
+        },
+        None => None
+    };
+
+    let test = test_destination(data.live_stream_id, &destination, video_file.as_deref(), &state.upload_dir, &ProbeConfig::from_env()).await;
+
+    save_destination_test(data.live_stream_id, &test, pool).await?;
+
+    Ok(test)
+}
@@ -0,0 +1,44 @@
+use std::sync::Arc;
+use actix_web::HttpRequest;
+use subtle::ConstantTimeEq;
+use tracing::warn;
+use std::env::var;
+
+use crate::{
+    dto::live_stream_state::LiveStreamState,
+    dto::metrics_state::MetricsState,
+    errors::AppError,
+    models::metrics::render_metrics,
+    utils::token::get_jwt_from_header
+};
+
+pub fn metrics(
+    req: &HttpRequest,
+    metrics_state: &Arc<MetricsState>,
+    live_stream_state: &Arc<LiveStreamState>
+) -> Result<String, AppError> {
+    // The exporter is disabled unless a scrape token is configured.
+    let metrics_token = match var("METRICS_TOKEN") {
+        Ok(val) if !val.trim().is_empty() => val,
+        _ => {
+            return Err(AppError::NotFound);
+        }
+    };
+    let token = match get_jwt_from_header(req) {
+        Some(val) => val,
+        None => {
+            warn!("An attemp to access metrics endpoint without credentials.");
+
+            return Err(AppError::Unauthorized);
+        }
+    };
+
+    // Constant time, so the token can not be guessed from response times.
+    if !bool::from(token.as_bytes().ct_eq(metrics_token.trim().as_bytes())) {
+        warn!("An attemp to access metrics endpoint with invalid credentials.");
+
+        return Err(AppError::Unauthorized);
+    }
+
+    Ok(render_metrics(metrics_state, live_stream_state))
+}
@@ -0,0 +1,41 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+use serde_json::json;
+
+use crate::{
+    errors::AppError,
+    utils::time::current_unix_timestamp,
+    view_models::dashboard_metrics_history
+};
+
+#[derive(Deserialize)]
+pub struct Query {
+    from: Option<i64>,
+    to: Option<i64>
+}
+
+pub async fn get_metrics_history(
+    req: HttpRequest,
+    pool: web::Data<Pool<Postgres>>,
+    query: web::Query<Query>
+) -> Result<HttpResponse, AppError> {
+    // Defaults to the last hour.
+    let to = match query.to {
+        Some(val) => val,
+        None => current_unix_timestamp() as i64
+    };
+    let from = match query.from {
+        Some(val) => val,
+        None => to - 3600
+    };
+    let history = dashboard_metrics_history::get_metrics_history(from, to, &req, pool.get_ref()).await?;
+
+    let response_json = json!({
+        "response": true,
+        "resolution": history.resolution,
+        "samples": history.samples
+    });
+
+    Ok(HttpResponse::Ok().json(response_json))
+}
@@ -0,0 +1,27 @@
+use actix_web::{HttpRequest, HttpResponse, web};
+use sqlx::{Pool, Postgres};
+use serde_json::json;
+
+use crate::{errors::AppError, view_models::gallery_delete_audio};
+
+pub async fn delete_audio(
+    req: HttpRequest,
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<i64>
+) -> Result<HttpResponse, AppError> {
+    let audio_id = path.into_inner();
+    let delete_audio = gallery_delete_audio::delete_audio(audio_id, &pool.into_inner(), &req).await?;
+    let message = if delete_audio {
+        "The audio was deleted."
+    } else {
+        "Failed to delete audio."
+    };
+
+    let response_json = json!({
+        "response": true,
+        "delete" : delete_audio,
+        "message": message
+    });
+
+    Ok(HttpResponse::Ok().json(response_json))
+}
\ No newline at end of file
@@ -0,0 +1,27 @@
+use actix_web::{HttpRequest, HttpResponse, web};
+use sqlx::{Pool, Postgres};
+use serde_json::json;
+
+use crate::{errors::AppError, view_models::gallery_delete_image};
+
+pub async fn delete_image(
+    req: HttpRequest,
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<i64>
+) -> Result<HttpResponse, AppError> {
+    let image_id = path.into_inner();
+    let delete_image = gallery_delete_image::delete_image(image_id, &pool.into_inner(), &req).await?;
+    let message = if delete_image {
+        "The image was deleted."
+    } else {
+        "Failed to delete image."
+    };
+
+    let response_json = json!({
+        "response": true,
+        "delete" : delete_image,
+        "message": message
+    });
+
+    Ok(HttpResponse::Ok().json(response_json))
+}
\ No newline at end of file
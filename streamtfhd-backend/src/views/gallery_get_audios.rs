@@ -0,0 +1,29 @@
+use actix_web::{HttpRequest, HttpResponse, web};
+use sqlx::{Pool, Postgres};
+use crate::errors::AppError;
+use crate::view_models::gallery_get_audios;
+use serde_json::json;
+
+pub async fn get_audios(
+    req: HttpRequest,
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<(u32, u32, String)>,
+) -> Result<HttpResponse, AppError> {
+    let pool = pool.into_inner();
+    let (page, page_size, order) = path.into_inner();
+    let (audios, count) = gallery_get_audios::get_audios(&req, &pool, page, page_size, &order).await?;
+
+    let data = json!({
+        "audios": audios,
+        "page": page,
+        "page_size": page_size,
+        "total_audios": count
+    });
+
+    let response_json = json!({
+        "response": true,
+        "data": data
+    });
+
+    Ok(HttpResponse::Ok().json(response_json))
+}
\ No newline at end of file
@@ -0,0 +1,29 @@
+use actix_web::{HttpRequest, HttpResponse, web};
+use sqlx::{Pool, Postgres};
+use crate::errors::AppError;
+use crate::view_models::gallery_get_images;
+use serde_json::json;
+
+pub async fn get_images(
+    req: HttpRequest,
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<(u32, u32, String)>,
+) -> Result<HttpResponse, AppError> {
+    let pool = pool.into_inner();
+    let (page, page_size, order) = path.into_inner();
+    let (images, count) = gallery_get_images::get_images(&req, &pool, page, page_size, &order).await?;
+
+    let data = json!({
+        "images": images,
+        "page": page,
+        "page_size": page_size,
+        "total_images": count
+    });
+
+    let response_json = json!({
+        "response": true,
+        "data": data
+    });
+
+    Ok(HttpResponse::Ok().json(response_json))
+}
\ No newline at end of file
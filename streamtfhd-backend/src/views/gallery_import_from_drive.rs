@@ -1,3 +1,4 @@
+use std::sync::Arc;
 use actix_web::{HttpRequest, HttpResponse, web};
 use serde::Deserialize;
 use sqlx::{Pool, Postgres};
@@ -6,6 +7,7 @@ use urlencoding::decode;
 use tracing::{error, debug};
 
 use crate::errors::AppError;
+use crate::dto::metrics_state::MetricsState;
 use crate::view_models::gallery_import_from_drive;
 
 #[derive(Deserialize)]
@@ -16,6 +18,7 @@ pub struct Query {
 pub async fn import_from_drive(
     req: HttpRequest,
     pool: web::Data<Pool<Postgres>>,
+    metrics_state: web::Data<Arc<MetricsState>>,
     query: web::Query<Query>
 ) -> Result<HttpResponse, AppError> {
     let google_drive_url = match query.google_drive_url.clone() {
@@ -33,7 +36,7 @@ pub async fn import_from_drive(
             return Err(AppError::InternalError(String::from("Failed to decode decoded url parameter")));
         }
     };
-    let import = gallery_import_from_drive::import_from_drive(&google_drive_url_decoded, &req, &pool).await?;
+    let import = gallery_import_from_drive::import_from_drive(&google_drive_url_decoded, &req, &pool, metrics_state.get_ref()).await?;
 
     let response_json = json!({
         "response": true,
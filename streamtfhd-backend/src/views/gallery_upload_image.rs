@@ -0,0 +1,24 @@
+use std::sync::Arc;
+use actix_multipart::Multipart;
+use actix_web::{HttpRequest, HttpResponse, web};
+use crate::errors::AppError;
+use crate::dto::metrics_state::MetricsState;
+use serde_json::json;
+use crate::view_models::gallery_upload_image;
+use sqlx::{Pool, Postgres};
+
+pub async fn upload_image(
+    payload: Multipart,
+    req: HttpRequest,
+    pool: web::Data<Pool<Postgres>>,
+    metrics_state: web::Data<Arc<MetricsState>>
+) -> Result<HttpResponse, AppError> {
+    let upload = gallery_upload_image::upload_image(&pool, &req, payload, metrics_state.get_ref()).await?;
+
+    let response_json = json!({
+        "response": upload,
+        "upload": upload
+    });
+
+    Ok(HttpResponse::Ok().json(response_json))
+}
\ No newline at end of file
@@ -1,6 +1,8 @@
+use std::sync::Arc;
 use actix_multipart::Multipart;
 use actix_web::{HttpRequest, HttpResponse, web};
 use crate::errors::AppError;
+use crate::dto::metrics_state::MetricsState;
 use serde_json::json;
 use crate::view_models::gallery_upload_video;
 use sqlx::{Pool, Postgres};
@@ -8,9 +10,10 @@ use sqlx::{Pool, Postgres};
 pub async fn upload_video(
     payload: Multipart,
     req: HttpRequest,
-    pool: web::Data<Pool<Postgres>>
+    pool: web::Data<Pool<Postgres>>,
+    metrics_state: web::Data<Arc<MetricsState>>
 ) -> Result<HttpResponse, AppError> {
-    let upload = gallery_upload_video::upload_video(&pool, &req, payload).await?;
+    let upload = gallery_upload_video::upload_video(&pool, &req, payload, metrics_state.get_ref()).await?;
 
     let response_json = json!({
         "response": upload,
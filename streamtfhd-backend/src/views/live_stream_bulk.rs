@@ -0,0 +1,41 @@
+use std::sync::Arc;
+use actix_web::{HttpRequest, HttpResponse, web};
+use serde_json::json;
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    dto::live_stream_bulk::BulkRequest,
+    dto::live_stream_state::LiveStreamState,
+    errors::AppError,
+    view_models::live_stream_bulk
+};
+
+pub async fn bulk(
+    req: HttpRequest,
+    data: web::Json<BulkRequest>,
+    state: web::Data<Arc<LiveStreamState>>,
+    pool: web::Data<Pool<Postgres>>
+) -> Result<HttpResponse, AppError> {
+    let results = live_stream_bulk::bulk(&data.into_inner(), &req, &state.into_inner(), pool.get_ref()).await?;
+
+    let response_json = json!({
+        "response": true,
+        "results": results
+    });
+
+    Ok(HttpResponse::Ok().json(response_json))
+}
+
+pub async fn get_groups(
+    req: HttpRequest,
+    pool: web::Data<Pool<Postgres>>
+) -> Result<HttpResponse, AppError> {
+    let groups = live_stream_bulk::get_groups(&req, pool.get_ref()).await?;
+
+    let response_json = json!({
+        "response": true,
+        "groups": groups
+    });
+
+    Ok(HttpResponse::Ok().json(response_json))
+}
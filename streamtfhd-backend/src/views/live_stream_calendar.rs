@@ -0,0 +1,40 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+use serde_json::json;
+
+use crate::{
+    errors::AppError,
+    utils::time::current_unix_timestamp,
+    view_models::live_stream_calendar
+};
+
+#[derive(Deserialize)]
+pub struct Query {
+    from: Option<i64>,
+    to: Option<i64>
+}
+
+pub async fn get_calendar(
+    req: HttpRequest,
+    pool: web::Data<Pool<Postgres>>,
+    query: web::Query<Query>
+) -> Result<HttpResponse, AppError> {
+    // Defaults to the next 30 days.
+    let from = match query.from {
+        Some(val) => val,
+        None => current_unix_timestamp() as i64
+    };
+    let to = match query.to {
+        Some(val) => val,
+        None => from + 2592000
+    };
+    let calendar = live_stream_calendar::get_calendar(from, to, &req, pool.get_ref()).await?;
+
+    let response_json = json!({
+        "response": true,
+        "calendar": calendar
+    });
+
+    Ok(HttpResponse::Ok().json(response_json))
+}
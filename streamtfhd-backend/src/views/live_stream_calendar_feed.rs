@@ -0,0 +1,40 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+use serde_json::json;
+
+use crate::{
+    errors::AppError,
+    utils::icalendar::CONTENT_TYPE,
+    view_models::live_stream_calendar_feed
+};
+
+#[derive(Deserialize)]
+pub struct Query {
+    regenerate: Option<bool>
+}
+
+pub async fn get_feed_token(
+    req: HttpRequest,
+    pool: web::Data<Pool<Postgres>>,
+    query: web::Query<Query>
+) -> Result<HttpResponse, AppError> {
+    let regenerate = query.regenerate.unwrap_or(false);
+    let token = live_stream_calendar_feed::get_feed_token(regenerate, &req, pool.get_ref()).await?;
+
+    let response_json = json!({
+        "response": true,
+        "token": token
+    });
+
+    Ok(HttpResponse::Ok().json(response_json))
+}
+
+pub async fn get_feed(
+    token: web::Path<String>,
+    pool: web::Data<Pool<Postgres>>
+) -> Result<HttpResponse, AppError> {
+    let calendar = live_stream_calendar_feed::get_feed(&token.into_inner(), pool.get_ref()).await?;
+
+    Ok(HttpResponse::Ok().content_type(CONTENT_TYPE).body(calendar))
+}
@@ -0,0 +1,24 @@
+use actix_web::{HttpRequest, HttpResponse, web};
+use serde_json::json;
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    dto::live_stream_template::CloneRequest,
+    errors::AppError,
+    view_models::live_stream_clone
+};
+
+pub async fn clone_live_stream(
+    req: HttpRequest,
+    data: web::Json<CloneRequest>,
+    pool: web::Data<Pool<Postgres>>
+) -> Result<HttpResponse, AppError> {
+    let id = live_stream_clone::clone_live_stream(&data.into_inner(), &req, pool.get_ref()).await?;
+
+    let response_json = json!({
+        "response": true,
+        "id": id
+    });
+
+    Ok(HttpResponse::Ok().json(response_json))
+}
\ No newline at end of file
@@ -1,9 +1,12 @@
+use std::sync::Arc;
+
 use actix_web::{web, HttpResponse, HttpRequest};
 use sqlx::{Pool, Postgres};
 use serde_json::json;
 
 use crate::{
     dto::live_stream_edit_stream_post::LiveStream,
+    dto::live_stream_state::LiveStreamState,
     errors::AppError,
     view_models::live_stream_edit_stream_post
 };
@@ -11,13 +14,15 @@ use crate::{
 pub async fn update_live_stream_data(
     req: HttpRequest,
     data: web::Json<LiveStream>,
-    pool:  web::Data<Pool<Postgres>>
+    pool:  web::Data<Pool<Postgres>>,
+    state: web::Data<Arc<LiveStreamState>>
 ) -> Result<HttpResponse, AppError> {
-    let update = live_stream_edit_stream_post::update_live_stream_data(&data.into_inner(), &req, &pool.get_ref()).await?;
+    let outcome = live_stream_edit_stream_post::update_live_stream_data(&data.into_inner(), &req, pool.get_ref(), &state.into_inner()).await?;
 
     let response_json = json!({
         "response": true,
-        "update": update
+        "update": outcome.update,
+        "restarted": outcome.restarted
     });
 
     Ok(HttpResponse::Ok().json(response_json))
@@ -0,0 +1,28 @@
+use std::sync::Arc;
+
+use actix_web::{web, HttpResponse, HttpRequest};
+use sqlx::{Pool, Postgres};
+use serde_json::json;
+
+use crate::{
+    dto::live_stream_state::LiveStreamState,
+    dto::live_stream_overlay_text::OverlayTextData,
+    errors::AppError,
+    view_models::live_stream_overlay_text
+};
+
+pub async fn update_overlay_text(
+    req: HttpRequest,
+    data: web::Json<OverlayTextData>,
+    pool: web::Data<Pool<Postgres>>,
+    state: web::Data<Arc<LiveStreamState>>
+) -> Result<HttpResponse, AppError> {
+    let update = live_stream_overlay_text::update_overlay_text(&data.into_inner(), &req, pool.get_ref(), &state.into_inner()).await?;
+
+    let response_json = json!({
+        "response": true,
+        "update": update
+    });
+
+    Ok(HttpResponse::Ok().json(response_json))
+}
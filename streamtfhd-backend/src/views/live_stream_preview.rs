@@ -0,0 +1,22 @@
+use std::sync::Arc;
+
+use actix_web::{web, HttpResponse, HttpRequest};
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    dto::live_stream_state::LiveStreamState,
+    errors::AppError,
+    view_models::live_stream_preview
+};
+
+// Segments are requested relative to the playlist, so the file is part of the path.
+pub async fn get_preview(
+    req: HttpRequest,
+    path: web::Path<(i64, String)>,
+    pool: web::Data<Pool<Postgres>>,
+    state: web::Data<Arc<LiveStreamState>>
+) -> Result<HttpResponse, AppError> {
+    let (id, file) = path.into_inner();
+
+    live_stream_preview::get_preview(id, &file, &req, pool.get_ref(), &state.into_inner()).await
+}
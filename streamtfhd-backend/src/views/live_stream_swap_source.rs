@@ -0,0 +1,28 @@
+use std::sync::Arc;
+
+use actix_web::{web, HttpResponse, HttpRequest};
+use sqlx::{Pool, Postgres};
+use serde_json::json;
+
+use crate::{
+    dto::live_stream_state::LiveStreamState,
+    dto::live_stream_swap_source::SwapSourceData,
+    errors::AppError,
+    view_models::live_stream_swap_source
+};
+
+pub async fn swap_source(
+    req: HttpRequest,
+    data: web::Json<SwapSourceData>,
+    pool: web::Data<Pool<Postgres>>,
+    state: web::Data<Arc<LiveStreamState>>
+) -> Result<HttpResponse, AppError> {
+    let swap = live_stream_swap_source::swap_live_stream_source(&data.into_inner(), &req, pool.get_ref(), &state.into_inner()).await?;
+
+    let response_json = json!({
+        "response": true,
+        "swap": swap
+    });
+
+    Ok(HttpResponse::Ok().json(response_json))
+}
@@ -0,0 +1,69 @@
+use actix_web::{HttpRequest, HttpResponse, web};
+use serde_json::json;
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    dto::live_stream_template::TemplateData,
+    errors::AppError,
+    view_models::live_stream_template
+};
+
+pub async fn create_template(
+    req: HttpRequest,
+    data: web::Json<TemplateData>,
+    pool: web::Data<Pool<Postgres>>
+) -> Result<HttpResponse, AppError> {
+    let id = live_stream_template::create_template(&data.into_inner(), &req, pool.get_ref()).await?;
+
+    let response_json = json!({
+        "response": true,
+        "id": id
+    });
+
+    Ok(HttpResponse::Ok().json(response_json))
+}
+
+pub async fn get_templates(
+    req: HttpRequest,
+    pool: web::Data<Pool<Postgres>>
+) -> Result<HttpResponse, AppError> {
+    let templates = live_stream_template::get_templates(&req, pool.get_ref()).await?;
+
+    let response_json = json!({
+        "response": true,
+        "templates": templates
+    });
+
+    Ok(HttpResponse::Ok().json(response_json))
+}
+
+pub async fn update_template(
+    req: HttpRequest,
+    data: web::Json<TemplateData>,
+    pool: web::Data<Pool<Postgres>>
+) -> Result<HttpResponse, AppError> {
+    let update = live_stream_template::update_template(&data.into_inner(), &req, pool.get_ref()).await?;
+
+    let response_json = json!({
+        "response": true,
+        "update": update
+    });
+
+    Ok(HttpResponse::Ok().json(response_json))
+}
+
+pub async fn delete_template(
+    req: HttpRequest,
+    pool: web::Data<Pool<Postgres>>,
+    path: web::Path<i64>
+) -> Result<HttpResponse, AppError> {
+    let id = path.into_inner();
+    let delete = live_stream_template::delete_template(id, &req, pool.get_ref()).await?;
+
+    let response_json = json!({
+        "response": true,
+        "delete": delete
+    });
+
+    Ok(HttpResponse::Ok().json(response_json))
+}
\ No newline at end of file
@@ -0,0 +1,28 @@
+use std::sync::Arc;
+
+use actix_web::{web, HttpResponse, HttpRequest};
+use sqlx::{Pool, Postgres};
+use serde_json::json;
+
+use crate::{
+    dto::live_stream_state::LiveStreamState,
+    dto::live_stream_test_destination::TestDestinationData,
+    errors::AppError,
+    view_models::live_stream_test_destination
+};
+
+pub async fn test_destination(
+    req: HttpRequest,
+    data: web::Json<TestDestinationData>,
+    pool: web::Data<Pool<Postgres>>,
+    state: web::Data<Arc<LiveStreamState>>
+) -> Result<HttpResponse, AppError> {
+    let test = live_stream_test_destination::test_live_stream_destination(&data.into_inner(), &req, pool.get_ref(), &state.into_inner()).await?;
+
+    let response_json = json!({
+        "response": true,
+        "test": test
+    });
+
+    Ok(HttpResponse::Ok().json(response_json))
+}
@@ -0,0 +1,20 @@
+use std::sync::Arc;
+use actix_web::{web, HttpRequest, HttpResponse};
+
+use crate::{
+    dto::live_stream_state::LiveStreamState,
+    dto::metrics_state::MetricsState,
+    errors::AppError,
+    utils::prometheus::CONTENT_TYPE,
+    view_models::metrics
+};
+
+pub async fn metrics(
+    req: HttpRequest,
+    metrics_state: web::Data<Arc<MetricsState>>,
+    live_stream_state: web::Data<Arc<LiveStreamState>>
+) -> Result<HttpResponse, AppError> {
+    let body = metrics::metrics(&req, metrics_state.get_ref(), live_stream_state.get_ref())?;
+
+    Ok(HttpResponse::Ok().content_type(CONTENT_TYPE).body(body))
+}